@@ -0,0 +1,147 @@
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    io::{self, Read, Write},
+    path::PathBuf,
+};
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use sha2::{Digest, Sha256};
+
+/// The size files are split at, region files are rewritten in place by 4 KiB sectors so most chunks of a world stay the same between backups
+pub(super) const CHUNK_SIZE: usize = 512 * 1024;
+
+/// Compressed chunks named after the SHA-256 of their content, so a chunk shared by many backups is only stored once
+pub(super) struct ChunkStore {
+    root: PathBuf,
+}
+
+impl ChunkStore {
+    pub(super) const fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    fn chunk_path(&self, hash: &str) -> PathBuf {
+        self.root.join(&hash[..2]).join(hash)
+    }
+
+    /// Stores `data` unless an identical chunk already is
+    ///
+    /// returns the chunk's hash and how many bytes were written to disk for it
+    pub(super) fn put(&self, data: &[u8]) -> io::Result<(String, u64)> {
+        let hash = hex::encode(Sha256::digest(data));
+        let path = self.chunk_path(&hash);
+        if path.exists() {
+            return Ok((hash, 0));
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // written aside first so a crash never leaves a truncated chunk behind
+        let temp_path = path.with_extension("tmp");
+        let mut encoder = GzEncoder::new(File::create(&temp_path)?, Compression::fast());
+        encoder.write_all(data)?;
+        let written = encoder.finish()?.metadata()?.len();
+        fs::rename(temp_path, path)?;
+
+        Ok((hash, written))
+    }
+
+    /// Reads the chunk `hash`, `None` if it is missing or doesn't match its hash anymore
+    pub(super) fn get(&self, hash: &str) -> io::Result<Option<Vec<u8>>> {
+        let file = match File::open(self.chunk_path(hash)) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+
+        let mut data = Vec::with_capacity(CHUNK_SIZE);
+        if GzDecoder::new(file).read_to_end(&mut data).is_err() {
+            return Ok(None);
+        }
+
+        match hex::encode(Sha256::digest(&data)) == hash {
+            true => Ok(Some(data)),
+            false => Ok(None),
+        }
+    }
+
+    /// Removes every chunk that isn't in `referenced`, returns how many bytes were freed
+    pub(super) fn collect_garbage(&self, referenced: &HashSet<String>) -> io::Result<u64> {
+        let prefixes = match fs::read_dir(&self.root) {
+            Ok(prefixes) => prefixes,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(err) => return Err(err),
+        };
+
+        let mut freed = 0;
+        for prefix in prefixes {
+            for chunk in fs::read_dir(prefix?.path())? {
+                let chunk = chunk?;
+                let name = chunk.file_name();
+                if referenced.contains(name.to_string_lossy().as_ref()) {
+                    continue;
+                }
+
+                freed += chunk.metadata()?.len();
+                fs::remove_file(chunk.path())?;
+            }
+        }
+        Ok(freed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn put_deduplicates_and_get_round_trips() {
+        let temp = tempfile::tempdir().unwrap();
+        let store = ChunkStore::new(temp.path().to_path_buf());
+
+        let (hash, written) = store.put(b"some chunk").unwrap();
+        assert!(written > 0);
+        assert_eq!(store.put(b"some chunk").unwrap(), (hash.clone(), 0));
+        assert_eq!(
+            store.get(&hash).unwrap().as_deref(),
+            Some(&b"some chunk"[..])
+        );
+
+        let missing = hex::encode(Sha256::digest(b"never stored"));
+        assert_eq!(store.get(&missing).unwrap(), None);
+    }
+
+    #[test]
+    fn get_rejects_corrupted_chunks() {
+        let temp = tempfile::tempdir().unwrap();
+        let store = ChunkStore::new(temp.path().to_path_buf());
+
+        let (hash, _) = store.put(b"original").unwrap();
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
+        encoder.write_all(b"tampered").unwrap();
+        fs::write(store.chunk_path(&hash), encoder.finish().unwrap()).unwrap();
+        assert_eq!(store.get(&hash).unwrap(), None);
+
+        fs::write(store.chunk_path(&hash), b"not gzip").unwrap();
+        assert_eq!(store.get(&hash).unwrap(), None);
+    }
+
+    #[test]
+    fn collect_garbage_keeps_referenced_chunks() {
+        let temp = tempfile::tempdir().unwrap();
+        let store = ChunkStore::new(temp.path().to_path_buf());
+
+        let (kept, _) = store.put(b"kept").unwrap();
+        let (dropped, _) = store.put(b"dropped").unwrap();
+        let freed = store
+            .collect_garbage(&HashSet::from([kept.clone()]))
+            .unwrap();
+
+        assert!(freed > 0);
+        assert!(store.get(&kept).unwrap().is_some());
+        assert!(store.get(&dropped).unwrap().is_none());
+    }
+}
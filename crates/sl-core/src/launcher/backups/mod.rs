@@ -0,0 +1,797 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, File},
+    io::{self, BufReader, Read, Write},
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard},
+    time::SystemTime,
+};
+
+use chrono::{DateTime, Datelike, Local, Utc};
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use sl_utils::{dlog, errors::BackupError, log, wlog};
+use strum_macros::Display;
+
+use crate::{
+    launcher::{
+        backups::chunk_store::{ChunkStore, CHUNK_SIZE},
+        instances::{
+            instance_config::{self, BackupsConfig},
+            instance_metadata::InstanceMetadata,
+        },
+    },
+    BACKUPS_DIR, GAME_PROCESSES,
+};
+
+mod chunk_store;
+
+/// Locked by every process using the store, in [`BACKUPS_DIR`]
+const STORE_LOCK_FILE_NAME: &str = ".lock";
+
+lazy_static! {
+    /// Held along with [`StoreLock`]'s file, so the threads of this process take turns too
+    static ref STORE_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// Held while the store is written to or cleaned up, so a chunk is never collected between being stored and referenced
+///
+/// the lock is on a file as the launcher and the app share the store, it is released when dropped
+struct StoreLock {
+    _file: File,
+    _guard: MutexGuard<'static, ()>,
+}
+
+fn lock_store() -> Result<StoreLock, BackupError> {
+    let guard = STORE_LOCK.lock().unwrap();
+    fs::create_dir_all(&*BACKUPS_DIR)?;
+    let file = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(BACKUPS_DIR.join(STORE_LOCK_FILE_NAME))?;
+    file.lock()?;
+
+    Ok(StoreLock {
+        _file: file,
+        _guard: guard,
+    })
+}
+
+/// What a backup was taken for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum BackupTrigger {
+    Manual,
+    BeforeLaunch,
+    AfterExit,
+    BeforeUpdate,
+    /// Taken of a world right before a backup is restored over it
+    BeforeRestore,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotFile {
+    /// Relative to the world's directory, always separated by `/`
+    pub path: String,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+    /// The hashes of the file's chunks in the store, in order
+    pub chunks: Vec<String>,
+}
+
+/// A backup of a single world, stored as `<id>.json` in the instance's backups directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// When the backup was taken in local time, unique among the instance's backups
+    pub id: String,
+    pub world: String,
+    pub created_at: DateTime<Utc>,
+    pub trigger: BackupTrigger,
+    /// The size of the world when it was backed up
+    pub size: u64,
+    /// How many bytes this backup added to the store, the rest is shared with older backups
+    pub stored_size: u64,
+    /// Every directory of the world, so empty ones are restored too
+    pub dirs: Vec<String>,
+    pub files: Vec<SnapshotFile>,
+}
+
+fn store() -> ChunkStore {
+    ChunkStore::new(BACKUPS_DIR.join("chunks"))
+}
+
+fn snapshots_dir(instance_id: &str) -> PathBuf {
+    BACKUPS_DIR.join("snapshots").join(instance_id)
+}
+
+fn saves_dir(instance: &InstanceMetadata) -> PathBuf {
+    instance.instance_dir().join("saves")
+}
+
+/// World names are directory names in `saves`, anything that could point outside of it is refused
+fn validate_world_name(world: &str) -> Result<(), BackupError> {
+    let invalid = world.is_empty()
+        || world.starts_with('.')
+        || world.contains(['/', '\\'])
+        || Path::new(world).components().count() != 1;
+
+    match invalid {
+        true => Err(BackupError::InvalidWorldName(world.to_string())),
+        false => Ok(()),
+    }
+}
+
+/// Joins a `/` separated path from a snapshot to `root`, refusing the ones that would escape it
+fn join_relative(root: &Path, relative: &str) -> Result<PathBuf, BackupError> {
+    let mut path = root.to_path_buf();
+    for component in relative.split('/') {
+        if component.is_empty() || component == "." || component == ".." {
+            return Err(BackupError::InvalidWorldName(relative.to_string()));
+        }
+        path.push(component);
+    }
+    Ok(path)
+}
+
+fn to_relative(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// The worlds of the instance, sorted by name
+pub fn worlds(instance: &InstanceMetadata) -> io::Result<Vec<String>> {
+    let entries = match fs::read_dir(saves_dir(instance)) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+
+    let mut worlds = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if entry.file_type()?.is_dir() && validate_world_name(&name).is_ok() {
+            worlds.push(name);
+        }
+    }
+    worlds.sort();
+    Ok(worlds)
+}
+
+fn read_snapshot(path: &Path) -> Result<Snapshot, BackupError> {
+    Ok(serde_json::from_reader(BufReader::new(File::open(path)?))?)
+}
+
+/// The backups of the instance, only the ones of `world` if given, newest first
+pub fn list_snapshots(
+    instance: &InstanceMetadata,
+    world: Option<&str>,
+) -> Result<Vec<Snapshot>, BackupError> {
+    let entries = match fs::read_dir(snapshots_dir(&instance.id)) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut snapshots = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+
+        match read_snapshot(&path) {
+            Ok(snapshot) if world.is_none_or(|world| snapshot.world == world) => {
+                snapshots.push(snapshot)
+            }
+            Ok(_) => {}
+            Err(err) => wlog!("Ignoring unreadable backup {}: {err}", path.display()),
+        }
+    }
+
+    snapshots.sort_by_key(|snapshot| std::cmp::Reverse(snapshot.created_at));
+    Ok(snapshots)
+}
+
+fn find_snapshot(instance: &InstanceMetadata, id: &str) -> Result<Snapshot, BackupError> {
+    let path = snapshots_dir(&instance.id).join(format!("{id}.json"));
+    match path.exists() {
+        true => read_snapshot(&path),
+        false => Err(BackupError::SnapshotNotFound(id.to_string())),
+    }
+}
+
+fn write_snapshot(instance_id: &str, snapshot: &Snapshot) -> Result<(), BackupError> {
+    let path = snapshots_dir(instance_id).join(format!("{}.json", snapshot.id));
+    let temp_path = path.with_extension("json.tmp");
+    fs::write(&temp_path, serde_json::to_vec(snapshot)?)?;
+    fs::rename(temp_path, path)?;
+    Ok(())
+}
+
+/// Walks the world's directory, storing the files that changed since `previous`
+struct SnapshotBuilder<'a> {
+    store: ChunkStore,
+    root: &'a Path,
+    previous: HashMap<&'a str, &'a SnapshotFile>,
+    dirs: Vec<String>,
+    files: Vec<SnapshotFile>,
+    size: u64,
+    stored_size: u64,
+}
+
+impl SnapshotBuilder<'_> {
+    fn add_dir(&mut self, dir: &Path) -> io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+
+            if entry.file_type()?.is_dir() {
+                self.dirs.push(to_relative(self.root, &path));
+                self.add_dir(&path)?;
+            } else {
+                self.add_file(&path, entry.metadata()?)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn add_file(&mut self, path: &Path, metadata: fs::Metadata) -> io::Result<()> {
+        let relative = to_relative(self.root, path);
+        let size = metadata.len();
+        let modified = metadata.modified().ok();
+        self.size += size;
+
+        // unchanged files aren't even read again
+        if let Some(previous) = self.previous.get(relative.as_str()) {
+            if modified.is_some() && previous.modified == modified && previous.size == size {
+                self.files.push(SnapshotFile {
+                    path: relative,
+                    ..(*previous).clone()
+                });
+                return Ok(());
+            }
+        }
+
+        let mut file = File::open(path)?;
+        let mut buffer = vec![0; CHUNK_SIZE];
+        let mut chunks = Vec::new();
+        loop {
+            let read = read_chunk(&mut file, &mut buffer)?;
+            if read == 0 {
+                break;
+            }
+
+            let (hash, written) = self.store.put(&buffer[..read])?;
+            self.stored_size += written;
+            chunks.push(hash);
+        }
+
+        self.files.push(SnapshotFile {
+            path: relative,
+            size,
+            modified,
+            chunks,
+        });
+        Ok(())
+    }
+}
+
+/// Fills `buffer` as much as possible, only returns less at the end of the file
+fn read_chunk(file: &mut File, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match file.read(&mut buffer[filled..])? {
+            0 => break,
+            read => filled += read,
+        }
+    }
+    Ok(filled)
+}
+
+fn new_snapshot_id(instance_id: &str, created_at: DateTime<Utc>) -> String {
+    let base_id = created_at
+        .with_timezone(&Local)
+        .format("%Y-%m-%d_%H-%M-%S")
+        .to_string();
+    let dir = snapshots_dir(instance_id);
+
+    let mut id = base_id.clone();
+    let mut n = 1;
+    while dir.join(format!("{id}.json")).exists() {
+        n += 1;
+        id = format!("{base_id}-{n}");
+    }
+    id
+}
+
+fn backup_world_locked(
+    instance: &InstanceMetadata,
+    world: &str,
+    trigger: BackupTrigger,
+) -> Result<Snapshot, BackupError> {
+    validate_world_name(world)?;
+    let world_dir = saves_dir(instance).join(world);
+    if !world_dir.is_dir() {
+        return Err(BackupError::WorldNotFound(world.to_string()));
+    }
+
+    if GAME_PROCESSES.is_running(&instance.id) {
+        wlog!(
+            "Backing up world '{world}' while the game is running, the backup may be inconsistent"
+        );
+    }
+
+    let previous = list_snapshots(instance, Some(world))?.into_iter().next();
+    let mut builder = SnapshotBuilder {
+        store: store(),
+        root: &world_dir,
+        previous: previous
+            .iter()
+            .flat_map(|snapshot| &snapshot.files)
+            .map(|file| (file.path.as_str(), file))
+            .collect(),
+        dirs: Vec::new(),
+        files: Vec::new(),
+        size: 0,
+        stored_size: 0,
+    };
+    builder.add_dir(&world_dir)?;
+
+    fs::create_dir_all(snapshots_dir(&instance.id))?;
+    let created_at = Utc::now();
+    let snapshot = Snapshot {
+        id: new_snapshot_id(&instance.id, created_at),
+        world: world.to_string(),
+        created_at,
+        trigger,
+        size: builder.size,
+        stored_size: builder.stored_size,
+        dirs: builder.dirs,
+        files: builder.files,
+    };
+    write_snapshot(&instance.id, &snapshot)?;
+
+    log!(
+        "Backed up world '{world}' of instance '{}' as {} ({} bytes, {} new)",
+        instance.name,
+        snapshot.id,
+        snapshot.size,
+        snapshot.stored_size
+    );
+    Ok(snapshot)
+}
+
+/// Backs up a single world of the instance, only storing what changed since its previous backup
+pub fn backup_world(
+    instance: &InstanceMetadata,
+    world: &str,
+    trigger: BackupTrigger,
+) -> Result<Snapshot, BackupError> {
+    let _lock = lock_store()?;
+    backup_world_locked(instance, world, trigger)
+}
+
+/// Backs up every world of the instance
+pub fn backup_instance(
+    instance: &InstanceMetadata,
+    trigger: BackupTrigger,
+) -> Result<Vec<Snapshot>, BackupError> {
+    let _lock = lock_store()?;
+    worlds(instance)?
+        .iter()
+        .map(|world| backup_world_locked(instance, world, trigger))
+        .collect()
+}
+
+/// Restores the backup `snapshot_id` as the world `target`, or as the world it was taken of if `None`
+///
+/// restoring over an existing world is only allowed for the world the backup was taken of,
+/// which is backed up first. returns the name of the restored world
+pub fn restore(
+    instance: &InstanceMetadata,
+    snapshot_id: &str,
+    target: Option<&str>,
+) -> Result<String, BackupError> {
+    GAME_PROCESSES
+        .ensure_not_running(&instance.id)
+        .map_err(|_| BackupError::InstanceRunning(instance.name.clone()))?;
+
+    let _lock = lock_store()?;
+    let snapshot = find_snapshot(instance, snapshot_id)?;
+    let target = target.unwrap_or(&snapshot.world);
+    validate_world_name(target)?;
+
+    let saves_dir = saves_dir(instance);
+    let world_dir = saves_dir.join(target);
+    if world_dir.exists() {
+        if target != snapshot.world {
+            return Err(BackupError::WorldExists(target.to_string()));
+        }
+        backup_world_locked(instance, target, BackupTrigger::BeforeRestore)?;
+    }
+
+    // restored aside first, so a failure never leaves a half restored world behind
+    let staging_dir = saves_dir.join(format!(".{target}.restoring"));
+    if staging_dir.exists() {
+        fs::remove_dir_all(&staging_dir)?;
+    }
+
+    if let Err(err) = restore_into(&store(), &snapshot, &staging_dir) {
+        _ = fs::remove_dir_all(&staging_dir);
+        return Err(err);
+    }
+
+    if world_dir.exists() {
+        fs::remove_dir_all(&world_dir)?;
+    }
+    fs::rename(&staging_dir, &world_dir)?;
+
+    log!(
+        "Restored backup {} of world '{}' as '{target}'",
+        snapshot.id,
+        snapshot.world
+    );
+    Ok(target.to_string())
+}
+
+fn restore_into(store: &ChunkStore, snapshot: &Snapshot, dir: &Path) -> Result<(), BackupError> {
+    fs::create_dir_all(dir)?;
+    for relative in &snapshot.dirs {
+        fs::create_dir_all(join_relative(dir, relative)?)?;
+    }
+
+    for file in &snapshot.files {
+        let path = join_relative(dir, &file.path)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut output = File::create(&path)?;
+        for hash in &file.chunks {
+            let chunk = store
+                .get(hash)?
+                .ok_or_else(|| BackupError::CorruptedChunk(hash.clone()))?;
+            output.write_all(&chunk)?;
+        }
+
+        // keeps the next backup from storing the restored files all over again
+        if let Some(modified) = file.modified {
+            _ = output.set_modified(modified);
+        }
+    }
+    Ok(())
+}
+
+/// Removes every chunk no backup refers to anymore
+fn collect_garbage() -> Result<u64, BackupError> {
+    let mut referenced = HashSet::new();
+    let instances = match fs::read_dir(BACKUPS_DIR.join("snapshots")) {
+        Ok(instances) => instances,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(err) => return Err(err.into()),
+    };
+
+    for instance in instances {
+        for entry in fs::read_dir(instance?.path())? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+
+            // a backup that can't be read may still be repaired, so nothing is collected rather than its chunks
+            let snapshot = read_snapshot(&path)?;
+            referenced.extend(snapshot.files.into_iter().flat_map(|file| file.chunks));
+        }
+    }
+
+    let freed = store().collect_garbage(&referenced)?;
+    dlog!("Collected {freed} bytes of unused backup chunks");
+    Ok(freed)
+}
+
+fn remove_snapshot(instance_id: &str, snapshot_id: &str) -> Result<(), BackupError> {
+    let path = snapshots_dir(instance_id).join(format!("{snapshot_id}.json"));
+    match fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            Err(BackupError::SnapshotNotFound(snapshot_id.to_string()))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Deletes the backup `snapshot_id`, freeing the chunks only it used
+pub fn delete_snapshot(instance: &InstanceMetadata, snapshot_id: &str) -> Result<(), BackupError> {
+    let _lock = lock_store()?;
+    remove_snapshot(&instance.id, snapshot_id)?;
+    collect_garbage()?;
+    Ok(())
+}
+
+/// Deletes every backup of the instance, along with the chunks only they used
+pub fn delete_all(instance_id: &str) -> Result<(), BackupError> {
+    let _lock = lock_store()?;
+    match fs::remove_dir_all(snapshots_dir(instance_id)) {
+        Ok(()) => {}
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err.into()),
+    }
+    collect_garbage()?;
+    Ok(())
+}
+
+/// The IDs of the automatic backups of a single world that `config` keeps, `snapshots` are sorted newest first
+fn retained<'a>(snapshots: &[&'a Snapshot], config: &BackupsConfig) -> HashSet<&'a str> {
+    let mut kept = snapshots
+        .iter()
+        .take(config.keep_last)
+        .map(|snapshot| snapshot.id.as_str())
+        .collect::<HashSet<_>>();
+
+    let mut days = HashSet::new();
+    let mut weeks = HashSet::new();
+    for snapshot in snapshots {
+        let created_at = snapshot.created_at.with_timezone(&Local);
+        let week = created_at.iso_week();
+
+        // the first backup seen of a day or week is its latest one
+        if days.len() < config.keep_daily && days.insert(created_at.date_naive()) {
+            kept.insert(&snapshot.id);
+        }
+        if weeks.len() < config.keep_weekly && weeks.insert((week.year(), week.week())) {
+            kept.insert(&snapshot.id);
+        }
+    }
+    kept
+}
+
+/// Deletes the automatic backups of the instance that its config doesn't keep anymore, returns how many were deleted
+pub fn prune(instance: &InstanceMetadata) -> Result<usize, BackupError> {
+    let config = instance_config::read_backups_config(&instance.instance_dir());
+    prune_with(instance, &config)
+}
+
+fn prune_with(instance: &InstanceMetadata, config: &BackupsConfig) -> Result<usize, BackupError> {
+    let _lock = lock_store()?;
+    let snapshots = list_snapshots(instance, None)?;
+
+    let mut by_world = HashMap::<&str, Vec<&Snapshot>>::new();
+    for snapshot in snapshots
+        .iter()
+        .filter(|snapshot| snapshot.trigger != BackupTrigger::Manual)
+    {
+        by_world.entry(&snapshot.world).or_default().push(snapshot);
+    }
+
+    let mut removed = 0;
+    for world_snapshots in by_world.values() {
+        let kept = retained(world_snapshots, config);
+        for snapshot in world_snapshots {
+            if !kept.contains(snapshot.id.as_str()) {
+                dlog!(
+                    "Pruning backup {} of world '{}'",
+                    snapshot.id,
+                    snapshot.world
+                );
+                remove_snapshot(&instance.id, &snapshot.id)?;
+                removed += 1;
+            }
+        }
+    }
+
+    if removed > 0 {
+        collect_garbage()?;
+    }
+    Ok(removed)
+}
+
+/// Backs up every world of the instance if its config asks for it on `trigger`, then prunes its old backups
+pub fn auto_backup(
+    instance: &InstanceMetadata,
+    trigger: BackupTrigger,
+) -> Result<(), BackupError> {
+    let config = instance_config::read_backups_config(&instance.instance_dir());
+    let enabled = match trigger {
+        BackupTrigger::BeforeLaunch => config.before_launch,
+        BackupTrigger::AfterExit => config.after_exit,
+        BackupTrigger::BeforeUpdate => config.before_update,
+        BackupTrigger::Manual | BackupTrigger::BeforeRestore => true,
+    };
+    if !enabled {
+        return Ok(());
+    }
+
+    backup_instance(instance, trigger)?;
+    prune_with(instance, &config)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    /// Noon UTC is on the same day in every timezone tests are likely to run in
+    fn snapshot_at(year: i32, month: u32, day: u32, hour: u32) -> Snapshot {
+        let created_at = Utc.with_ymd_and_hms(year, month, day, hour, 0, 0).unwrap();
+        Snapshot {
+            id: created_at.to_rfc3339(),
+            world: "world".to_string(),
+            created_at,
+            trigger: BackupTrigger::AfterExit,
+            size: 0,
+            stored_size: 0,
+            dirs: Vec::new(),
+            files: Vec::new(),
+        }
+    }
+
+    fn config(keep_last: usize, keep_daily: usize, keep_weekly: usize) -> BackupsConfig {
+        BackupsConfig {
+            keep_last,
+            keep_daily,
+            keep_weekly,
+            ..BackupsConfig::default()
+        }
+    }
+
+    fn kept_ids(snapshots: &[Snapshot], config: &BackupsConfig) -> Vec<String> {
+        let snapshots = snapshots.iter().collect::<Vec<_>>();
+        let mut kept = retained(&snapshots, config)
+            .into_iter()
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        kept.sort();
+        kept
+    }
+
+    #[test]
+    fn retained_keeps_the_latest() {
+        let snapshots = [
+            snapshot_at(2026, 10, 14, 13),
+            snapshot_at(2026, 10, 14, 12),
+            snapshot_at(2026, 10, 13, 12),
+        ];
+        let mut expected = vec![snapshots[0].id.clone(), snapshots[1].id.clone()];
+        expected.sort();
+        assert_eq!(kept_ids(&snapshots, &config(2, 0, 0)), expected);
+        assert!(kept_ids(&snapshots, &config(0, 0, 0)).is_empty());
+    }
+
+    #[test]
+    fn retained_keeps_the_latest_of_each_day() {
+        let snapshots = [
+            snapshot_at(2026, 10, 14, 14),
+            snapshot_at(2026, 10, 14, 12),
+            snapshot_at(2026, 10, 13, 13),
+            snapshot_at(2026, 10, 13, 11),
+            snapshot_at(2026, 10, 12, 12),
+        ];
+        let mut expected = vec![snapshots[0].id.clone(), snapshots[2].id.clone()];
+        expected.sort();
+        assert_eq!(kept_ids(&snapshots, &config(0, 2, 0)), expected);
+    }
+
+    #[test]
+    fn retained_keeps_the_latest_of_each_week() {
+        // 2026-10-12 is a Monday, so the Sunday before it is in the previous ISO week
+        let snapshots = [
+            snapshot_at(2026, 10, 12, 12),
+            snapshot_at(2026, 10, 11, 12),
+            snapshot_at(2026, 10, 10, 12),
+            snapshot_at(2026, 10, 4, 12),
+        ];
+        let mut expected = vec![snapshots[0].id.clone(), snapshots[1].id.clone()];
+        expected.sort();
+        assert_eq!(kept_ids(&snapshots, &config(0, 0, 2)), expected);
+
+        let mut expected = vec![
+            snapshots[0].id.clone(),
+            snapshots[1].id.clone(),
+            snapshots[3].id.clone(),
+        ];
+        expected.sort();
+        assert_eq!(kept_ids(&snapshots, &config(0, 0, 3)), expected);
+    }
+
+    #[test]
+    fn retained_combines_the_rules() {
+        let snapshots = [
+            snapshot_at(2026, 10, 14, 14),
+            snapshot_at(2026, 10, 14, 12),
+            snapshot_at(2026, 10, 13, 12),
+            snapshot_at(2026, 10, 5, 12),
+        ];
+        let mut expected = vec![
+            snapshots[0].id.clone(),
+            snapshots[1].id.clone(),
+            snapshots[2].id.clone(),
+            snapshots[3].id.clone(),
+        ];
+        expected.sort();
+        assert_eq!(kept_ids(&snapshots, &config(2, 2, 2)), expected);
+    }
+
+    fn build_snapshot(
+        store: ChunkStore,
+        world_dir: &Path,
+        previous: Option<&Snapshot>,
+    ) -> Snapshot {
+        let mut builder = SnapshotBuilder {
+            store,
+            root: world_dir,
+            previous: previous
+                .iter()
+                .flat_map(|snapshot| &snapshot.files)
+                .map(|file| (file.path.as_str(), file))
+                .collect(),
+            dirs: Vec::new(),
+            files: Vec::new(),
+            size: 0,
+            stored_size: 0,
+        };
+        builder.add_dir(world_dir).unwrap();
+
+        Snapshot {
+            id: "test".to_string(),
+            world: "world".to_string(),
+            created_at: Utc::now(),
+            trigger: BackupTrigger::Manual,
+            size: builder.size,
+            stored_size: builder.stored_size,
+            dirs: builder.dirs,
+            files: builder.files,
+        }
+    }
+
+    #[test]
+    fn snapshot_round_trip() {
+        let temp = tempfile::tempdir().unwrap();
+        let store_dir = temp.path().join("chunks");
+        let world_dir = temp.path().join("world");
+        fs::create_dir_all(world_dir.join("region")).unwrap();
+        fs::create_dir_all(world_dir.join("empty")).unwrap();
+
+        // spans more than one chunk, with a repeated one
+        let mut region = vec![7u8; CHUNK_SIZE * 2];
+        region.extend(b"tail");
+        fs::write(world_dir.join("region/r.0.0.mca"), &region).unwrap();
+        fs::write(world_dir.join("level.dat"), b"level").unwrap();
+
+        let snapshot = build_snapshot(ChunkStore::new(store_dir.clone()), &world_dir, None);
+        assert_eq!(snapshot.size, region.len() as u64 + 5);
+        let region_file = snapshot
+            .files
+            .iter()
+            .find(|file| file.path == "region/r.0.0.mca")
+            .unwrap();
+        assert_eq!(region_file.chunks.len(), 3);
+        assert_eq!(region_file.chunks[0], region_file.chunks[1]);
+
+        let restored_dir = temp.path().join("restored");
+        restore_into(
+            &ChunkStore::new(store_dir.clone()),
+            &snapshot,
+            &restored_dir,
+        )
+        .unwrap();
+        assert_eq!(
+            fs::read(restored_dir.join("region/r.0.0.mca")).unwrap(),
+            region
+        );
+        assert_eq!(fs::read(restored_dir.join("level.dat")).unwrap(), b"level");
+        assert!(restored_dir.join("empty").is_dir());
+
+        // nothing changed, so nothing new is stored
+        let again = build_snapshot(ChunkStore::new(store_dir), &world_dir, Some(&snapshot));
+        assert_eq!(again.stored_size, 0);
+    }
+}
@@ -0,0 +1,562 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    process::ExitStatus,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sl_utils::{dlog, errors::InstanceError, log, wlog};
+use sysinfo::{Pid, Process, ProcessRefreshKind, ProcessStatus, ProcessesToUpdate, System};
+use tokio::{
+    process::Child,
+    sync::{mpsc, watch},
+};
+
+use crate::{
+    launcher::instances::crash_analyzer::{self, CrashAnalysis},
+    INSTANCES_DIR,
+};
+
+/// Written to the instance's directory while its game is running, so a restarted launcher can find it again
+const PID_FILE_NAME: &str = ".game.pid";
+/// How often games started by a previous run of the launcher are checked for having exited
+const REATTACHED_POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// PIDs get reused, a process is only considered to be the game if it started within this many seconds of it
+const START_TIME_TOLERANCE: u64 = 5;
+
+/// How long [`GameProcessManager::stop`] should usually wait for the game to exit before killing it
+pub const DEFAULT_STOP_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum GameState {
+    Running,
+    /// The game was asked to exit, and will be killed if it doesn't in time
+    Stopping,
+    Exited {
+        /// `None` if the game was killed by a signal, or was started by a previous run of the launcher
+        code: Option<i32>,
+        /// The signal that terminated the game, unix only
+        signal: Option<i32>,
+    },
+}
+
+impl GameState {
+    pub const fn is_exited(&self) -> bool {
+        matches!(self, GameState::Exited { .. })
+    }
+
+    /// Whether the game exited with a zero exit code
+    pub const fn is_success(&self) -> bool {
+        matches!(self, GameState::Exited { code: Some(0), .. })
+    }
+}
+
+impl From<ExitStatus> for GameState {
+    fn from(status: ExitStatus) -> Self {
+        #[cfg(unix)]
+        let signal = std::os::unix::process::ExitStatusExt::signal(&status);
+        #[cfg(not(unix))]
+        let signal = None;
+
+        GameState::Exited {
+            code: status.code(),
+            signal,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PidFile {
+    pid: u32,
+    started_at: DateTime<Utc>,
+}
+
+/// A snapshot of a game process tracked by the [`GameProcessManager`]
+#[derive(Debug, Clone, Serialize)]
+pub struct GameProcessInfo {
+    pub instance_id: String,
+    pub pid: u32,
+    pub started_at: DateTime<Utc>,
+    pub state: GameState,
+    /// Whether the game was started by a previous run of the launcher, in which case its exit code can't be known
+    pub reattached: bool,
+}
+
+struct GameProcess {
+    pid: u32,
+    started_at: DateTime<Utc>,
+    reattached: bool,
+    state: Arc<watch::Sender<GameState>>,
+    /// Asks the task owning the child to kill it, `None` for reattached games
+    kill_tx: Option<mpsc::UnboundedSender<()>>,
+}
+
+impl GameProcess {
+    fn is_running(&self) -> bool {
+        !self.state.borrow().is_exited()
+    }
+
+    fn info(&self, instance_id: &str) -> GameProcessInfo {
+        GameProcessInfo {
+            instance_id: instance_id.to_string(),
+            pid: self.pid,
+            started_at: self.started_at,
+            state: *self.state.borrow(),
+            reattached: self.reattached,
+        }
+    }
+
+    fn kill(&self) {
+        match self.kill_tx {
+            Some(ref kill_tx) => _ = kill_tx.send(()),
+            None => _ = with_process(self.pid, self.started_at, Process::kill),
+        }
+    }
+}
+
+/// A handle to a game started by [`LoadedInstance::execute`](super::instances::loaded_instance::LoadedInstance::execute)
+#[derive(Debug, Clone)]
+pub struct GameHandle {
+    instance_id: String,
+    instance_dir: PathBuf,
+    pid: u32,
+    started_at: DateTime<Utc>,
+    state: watch::Receiver<GameState>,
+    /// Set once the exit handler given to [`GameProcessManager::track`] is done
+    exit_handled: watch::Receiver<bool>,
+}
+
+impl GameHandle {
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
+    pub const fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    pub const fn started_at(&self) -> DateTime<Utc> {
+        self.started_at
+    }
+
+    pub fn state(&self) -> GameState {
+        *self.state.borrow()
+    }
+
+    /// Looks for known failures in the files written by the game during this run, meant to be called once it exited unsuccessfully
+    pub fn analyze_crash(&self) -> CrashAnalysis {
+        crash_analyzer::analyze(&self.instance_dir, SystemTime::from(self.started_at))
+    }
+
+    /// Waits for the game to exit and for the launcher to be done with it, returning the state it exited with
+    pub async fn wait(&mut self) -> GameState {
+        // the sender is dropped without setting it if the exit handler panicked
+        _ = self.exit_handled.wait_for(|handled| *handled).await;
+        wait_exited(&mut self.state).await
+    }
+}
+
+async fn wait_exited(state: &mut watch::Receiver<GameState>) -> GameState {
+    // the sender lives as long as the process is tracked, which is forever once it exited
+    if let Ok(exited) = state.wait_for(GameState::is_exited).await {
+        return *exited;
+    }
+    *state.borrow()
+}
+
+fn pid_file_path(instance_dir: &Path) -> PathBuf {
+    instance_dir.join(PID_FILE_NAME)
+}
+
+/// Calls `f` with the process `pid` if it is still alive and is the one that was started at `started_at`
+fn with_process<R>(
+    pid: u32,
+    started_at: DateTime<Utc>,
+    f: impl FnOnce(&Process) -> R,
+) -> Option<R> {
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        true,
+        ProcessRefreshKind::nothing(),
+    );
+
+    let process = system.process(pid)?;
+    let started_at = started_at.timestamp().max(0) as u64;
+    if process.status() == ProcessStatus::Zombie
+        || process.start_time().abs_diff(started_at) > START_TIME_TOLERANCE
+    {
+        return None;
+    }
+
+    Some(f(process))
+}
+
+/// Reads the PID file in `instance_dir`, removing it if the game it points to is no longer running
+fn read_live_pid_file(instance_dir: &Path) -> Option<PidFile> {
+    let path = pid_file_path(instance_dir);
+    let contents = std::fs::read_to_string(&path).ok()?;
+
+    match serde_json::from_str::<PidFile>(&contents) {
+        Ok(pid_file) if with_process(pid_file.pid, pid_file.started_at, |_| ()).is_some() => {
+            Some(pid_file)
+        }
+        _ => {
+            dlog!("Removing stale PID file {}", path.display());
+            _ = std::fs::remove_file(&path);
+            None
+        }
+    }
+}
+
+#[derive(Default)]
+struct Processes {
+    games: HashMap<String, GameProcess>,
+    /// The instances being prepared for a launch, whose game isn't spawned yet
+    starting: HashSet<String>,
+}
+
+impl Processes {
+    fn is_running(&self, instance_id: &str) -> bool {
+        self.games
+            .get(instance_id)
+            .is_some_and(GameProcess::is_running)
+            || read_live_pid_file(&INSTANCES_DIR.join(instance_id)).is_some()
+    }
+}
+
+/// Reserves an instance for a launch from [`GameProcessManager::reserve`] until its game is tracked
+///
+/// the reservation is released when dropped, if the launch failed before the game was spawned
+pub(crate) struct LaunchReservation<'a> {
+    manager: &'a GameProcessManager,
+    instance_id: String,
+}
+
+impl Drop for LaunchReservation<'_> {
+    fn drop(&mut self) {
+        let mut processes = self.manager.processes.lock().unwrap();
+        processes.starting.remove(&self.instance_id);
+    }
+}
+
+/// Owns the games launched by this launcher, keyed by their instance's ID
+///
+/// every running game has a PID file in its instance's directory, which is how games launched by a
+/// previous (or another) run of the launcher are found and guarded against being launched twice
+#[derive(Default)]
+pub struct GameProcessManager {
+    processes: Mutex<Processes>,
+}
+
+impl GameProcessManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the instance's game is running, including games launched by another run of the launcher
+    pub fn is_running(&self, instance_id: &str) -> bool {
+        self.processes.lock().unwrap().is_running(instance_id)
+    }
+
+    /// Errors with [`InstanceError::AlreadyRunning`] if the instance's game is running or being launched
+    pub fn ensure_not_running(&self, instance_id: &str) -> Result<(), InstanceError> {
+        let processes = self.processes.lock().unwrap();
+        if processes.starting.contains(instance_id) || processes.is_running(instance_id) {
+            return Err(InstanceError::AlreadyRunning(instance_id.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Like [`Self::ensure_not_running`], but also reserves the instance so no other launch can start until the game is tracked
+    pub(crate) fn reserve(
+        &self,
+        instance_id: &str,
+    ) -> Result<LaunchReservation<'_>, InstanceError> {
+        let mut processes = self.processes.lock().unwrap();
+        if processes.is_running(instance_id) || !processes.starting.insert(instance_id.to_string())
+        {
+            return Err(InstanceError::AlreadyRunning(instance_id.to_string()));
+        }
+        Ok(LaunchReservation {
+            manager: self,
+            instance_id: instance_id.to_string(),
+        })
+    }
+
+    /// Takes ownership of a freshly spawned game, tracking it until it exits, which releases its reservation
+    ///
+    /// `on_exit` is called on the blocking thread pool once the game exited and is no longer reported as running,
+    /// [`GameHandle::wait`] only returns once it is done
+    pub(crate) fn track(
+        &self,
+        reservation: LaunchReservation<'_>,
+        instance_dir: &Path,
+        mut child: Child,
+        on_exit: impl FnOnce(GameState) + Send + 'static,
+    ) -> GameHandle {
+        let instance_id = reservation.instance_id.as_str();
+        let pid = child.id().unwrap_or_default();
+        let started_at = Utc::now();
+
+        let pid_path = pid_file_path(instance_dir);
+        let pid_file = serde_json::to_string(&PidFile { pid, started_at })
+            .expect("PidFile is always serializable");
+        if let Err(err) = std::fs::write(&pid_path, pid_file) {
+            wlog!("Failed to write PID file {}: {err}", pid_path.display());
+        }
+
+        let (state_tx, state_rx) = watch::channel(GameState::Running);
+        let state_tx = Arc::new(state_tx);
+        let (handled_tx, handled_rx) = watch::channel(false);
+        let (kill_tx, mut kill_rx) = mpsc::unbounded_channel();
+
+        let task_state = state_tx.clone();
+        let id = instance_id.to_string();
+        tokio::spawn(async move {
+            let status = loop {
+                tokio::select! {
+                    status = child.wait() => break status,
+                    Some(()) = kill_rx.recv() => {
+                        if let Err(err) = child.start_kill() {
+                            wlog!("Failed to kill instance '{id}': {err}");
+                        }
+                    }
+                }
+            };
+
+            let state = match status {
+                Ok(status) => GameState::from(status),
+                Err(err) => {
+                    wlog!("Failed to wait on instance '{id}': {err}");
+                    GameState::Exited {
+                        code: None,
+                        signal: None,
+                    }
+                }
+            };
+
+            log!("Instance '{id}' exited: {state:?}");
+            // published first, the handler backs up the worlds which is refused while the game is running
+            _ = std::fs::remove_file(&pid_path);
+            task_state.send_replace(state);
+
+            if let Err(err) = tokio::task::spawn_blocking(move || on_exit(state)).await {
+                wlog!("Exit handler of instance '{id}' failed: {err}");
+            }
+            handled_tx.send_replace(true);
+        });
+
+        self.processes.lock().unwrap().games.insert(
+            instance_id.to_string(),
+            GameProcess {
+                pid,
+                started_at,
+                reattached: false,
+                state: state_tx,
+                kill_tx: Some(kill_tx),
+            },
+        );
+
+        let handle = GameHandle {
+            instance_id: instance_id.to_string(),
+            instance_dir: instance_dir.to_path_buf(),
+            pid,
+            started_at,
+            state: state_rx,
+            exit_handled: handled_rx,
+        };
+        // only released once the game is tracked, so it is guarded against a second launch throughout
+        drop(reservation);
+        handle
+    }
+
+    /// Finds the games left running by a previous run of the launcher using their PID files, and tracks them until they exit
+    pub fn reattach(&self) -> std::io::Result<()> {
+        for entry in std::fs::read_dir(&*INSTANCES_DIR)? {
+            let entry = entry?;
+            let instance_dir = entry.path();
+            let Some(pid_file) = read_live_pid_file(&instance_dir) else {
+                continue;
+            };
+
+            let instance_id = entry.file_name().to_string_lossy().into_owned();
+            let mut processes = self.processes.lock().unwrap();
+            if processes
+                .games
+                .get(&instance_id)
+                .is_some_and(GameProcess::is_running)
+            {
+                continue;
+            }
+
+            log!(
+                "Reattached to instance '{instance_id}' running with PID {}",
+                pid_file.pid
+            );
+
+            let state = Arc::new(watch::channel(GameState::Running).0);
+            let task_state = state.clone();
+            let PidFile { pid, started_at } = pid_file;
+            tokio::spawn(async move {
+                while with_process(pid, started_at, |_| ()).is_some() {
+                    tokio::time::sleep(REATTACHED_POLL_INTERVAL).await;
+                }
+
+                _ = std::fs::remove_file(pid_file_path(&instance_dir));
+                task_state.send_replace(GameState::Exited {
+                    code: None,
+                    signal: None,
+                });
+            });
+
+            processes.games.insert(
+                instance_id,
+                GameProcess {
+                    pid,
+                    started_at,
+                    reattached: true,
+                    state,
+                    kill_tx: None,
+                },
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn get(&self, instance_id: &str) -> Option<GameProcessInfo> {
+        let processes = self.processes.lock().unwrap();
+        processes
+            .games
+            .get(instance_id)
+            .map(|process| process.info(instance_id))
+    }
+
+    /// Every tracked game, including the ones that already exited
+    pub fn list(&self) -> Vec<GameProcessInfo> {
+        let processes = self.processes.lock().unwrap();
+        processes
+            .games
+            .iter()
+            .map(|(id, process)| process.info(id))
+            .collect()
+    }
+
+    /// The IDs of the instances whose game is currently running
+    pub fn running(&self) -> Vec<String> {
+        let processes = self.processes.lock().unwrap();
+        processes
+            .games
+            .iter()
+            .filter(|(_, process)| process.is_running())
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    /// Asks the instance's game to exit, killing it if it is still running after `timeout`
+    ///
+    /// on platforms without graceful termination (Windows) the game is killed right away
+    pub async fn stop(
+        &self,
+        instance_id: &str,
+        timeout: Duration,
+    ) -> Result<GameState, InstanceError> {
+        let (pid, started_at, mut state) = {
+            let processes = self.processes.lock().unwrap();
+            let process = processes
+                .games
+                .get(instance_id)
+                .filter(|process| process.is_running())
+                .ok_or_else(|| InstanceError::NotRunning(instance_id.to_string()))?;
+
+            process.state.send_if_modified(|state| {
+                let running = *state == GameState::Running;
+                if running {
+                    *state = GameState::Stopping;
+                }
+                running
+            });
+            (process.pid, process.started_at, process.state.subscribe())
+        };
+
+        log!("Stopping instance '{instance_id}'");
+        let terminated = with_process(pid, started_at, |process| {
+            process.kill_with(sysinfo::Signal::Term)
+        });
+
+        if terminated.flatten() == Some(true) {
+            if let Ok(exited) = tokio::time::timeout(timeout, wait_exited(&mut state)).await {
+                return Ok(exited);
+            }
+            wlog!("Instance '{instance_id}' didn't exit after {timeout:?}, killing it");
+        }
+
+        // errors if the game exited in the meantime, which is what is being waited for anyways
+        _ = self.kill(instance_id);
+        Ok(wait_exited(&mut state).await)
+    }
+
+    /// Kills the instance's game right away
+    pub fn kill(&self, instance_id: &str) -> Result<(), InstanceError> {
+        let processes = self.processes.lock().unwrap();
+        let process = processes
+            .games
+            .get(instance_id)
+            .filter(|process| process.is_running())
+            .ok_or_else(|| InstanceError::NotRunning(instance_id.to_string()))?;
+
+        log!("Killing instance '{instance_id}'");
+        process.kill();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reservation_guards_until_dropped() {
+        let manager = GameProcessManager::new();
+        let id = "reservation-test-instance";
+
+        let reservation = manager.reserve(id).unwrap();
+        assert!(manager.reserve(id).is_err());
+        assert!(manager.ensure_not_running(id).is_err());
+        assert!(!manager.is_running(id));
+
+        drop(reservation);
+        assert!(manager.ensure_not_running(id).is_ok());
+        assert!(manager.reserve(id).is_ok());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn exited_before_the_exit_handler_runs() {
+        let manager: &'static GameProcessManager = Box::leak(Box::default());
+        let id = "exit-handler-test-instance";
+        let instance_dir = tempfile::tempdir().unwrap();
+
+        let reservation = manager.reserve(id).unwrap();
+        let child = tokio::process::Command::new("true").spawn().unwrap();
+        let (running_tx, running_rx) = std::sync::mpsc::channel();
+        let mut handle = manager.track(reservation, instance_dir.path(), child, move |state| {
+            running_tx
+                .send((state.is_exited(), manager.is_running(id)))
+                .unwrap();
+        });
+
+        let state = handle.wait().await;
+        assert!(state.is_success());
+        // sent by the handler, which is done once waiting returns
+        assert_eq!(running_rx.try_recv().unwrap(), (true, false));
+        assert!(!manager.is_running(id));
+        assert!(!instance_dir.path().join(PID_FILE_NAME).exists());
+    }
+}
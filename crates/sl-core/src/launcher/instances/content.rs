@@ -0,0 +1,222 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use sl_utils::errors::{BackendError, InstanceError};
+use strum_macros::{Display, EnumString};
+
+use crate::launcher::instances::{content_lock, instance_metadata::InstanceMetadata};
+
+/// Appended to the name of a file to disable it, the game only loads the files with their usual extension
+pub const DISABLED_SUFFIX: &str = ".disabled";
+
+/// The kinds of content that can be enabled and disabled
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, EnumString, Display,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "lowercase")]
+pub enum ContentKind {
+    #[default]
+    Mod,
+    ResourcePack,
+    ShaderPack,
+}
+
+impl ContentKind {
+    /// The directory of an instance holding this kind of content
+    pub const fn dir_name(self) -> &'static str {
+        match self {
+            Self::Mod => "mods",
+            Self::ResourcePack => "resourcepacks",
+            Self::ShaderPack => "shaderpacks",
+        }
+    }
+}
+
+/// A mod, resource pack or shader pack of an instance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentFile {
+    /// The name of the file without [`DISABLED_SUFFIX`]
+    pub file_name: String,
+    pub enabled: bool,
+}
+
+/// Splits the name of a content file into its name without [`DISABLED_SUFFIX`] and whether it is enabled
+pub fn parse_file_name(file_name: &str) -> (&str, bool) {
+    match file_name.strip_suffix(DISABLED_SUFFIX) {
+        Some(name) => (name, false),
+        None => (file_name, true),
+    }
+}
+
+/// The name of the content file `file_name` without [`DISABLED_SUFFIX`], refusing anything but a file right in the content's directory
+fn content_name(file_name: &str) -> Result<&str, InstanceError> {
+    let (name, _) = parse_file_name(file_name);
+    match sl_utils::fs::is_file_name(name) {
+        true => Ok(name),
+        false => Err(InstanceError::InvalidContentName(file_name.to_string())),
+    }
+}
+
+fn content_dir(instance: &InstanceMetadata, kind: ContentKind) -> PathBuf {
+    instance.instance_dir().join(kind.dir_name())
+}
+
+/// Lists the content of `kind` of `instance`, enabled or not, sorted by name
+///
+/// packs may be directories, mods are always files
+pub fn list(
+    instance: &InstanceMetadata,
+    kind: ContentKind,
+) -> Result<Vec<ContentFile>, BackendError> {
+    let dir = content_dir(instance, kind);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if file_name.starts_with('.') || (kind == ContentKind::Mod && !entry.file_type()?.is_file())
+        {
+            continue;
+        }
+
+        let (file_name, enabled) = parse_file_name(&file_name);
+        files.push(ContentFile {
+            file_name: file_name.to_string(),
+            enabled,
+        });
+    }
+
+    files.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+    Ok(files)
+}
+
+/// Enables or disables the content file `file_name`, which may be given with or without [`DISABLED_SUFFIX`]
+///
+/// does nothing if it already is, returns whether it was renamed
+pub fn set_enabled(
+    instance: &InstanceMetadata,
+    kind: ContentKind,
+    file_name: &str,
+    enabled: bool,
+) -> Result<bool, BackendError> {
+    let file_name = content_name(file_name)?;
+    let dir = content_dir(instance, kind);
+    let enabled_path = dir.join(file_name);
+    let disabled_path = dir.join(format!("{file_name}{DISABLED_SUFFIX}"));
+
+    let (from, to) = match enabled {
+        true => (disabled_path, enabled_path),
+        false => (enabled_path, disabled_path),
+    };
+
+    if !from.exists() {
+        return match to.exists() {
+            true => Ok(false),
+            false => Err(InstanceError::ContentNotFound(file_name.to_string()).into()),
+        };
+    }
+    // renaming would replace the other copy
+    if to.exists() {
+        return Err(InstanceError::ContentConflict(file_name.to_string()).into());
+    }
+
+    fs::rename(from, to)?;
+    Ok(true)
+}
+
+/// Enables or disables all the content of `kind`, the files named in `except` are set the other way
+///
+/// returns how many files were renamed
+pub fn set_all_enabled(
+    instance: &InstanceMetadata,
+    kind: ContentKind,
+    enabled: bool,
+    except: &[String],
+) -> Result<usize, BackendError> {
+    let dir = content_dir(instance, kind);
+    let except = except
+        .iter()
+        .map(|file_name| content_name(file_name))
+        .collect::<Result<Vec<_>, _>>()?;
+    // a misspelled name would silently leave the file it meant set like the others
+    if let Some(missing) = except.iter().find(|file_name| {
+        !dir.join(file_name).exists() && !dir.join(format!("{file_name}{DISABLED_SUFFIX}")).exists()
+    }) {
+        return Err(InstanceError::ContentNotFound(missing.to_string()).into());
+    }
+
+    let mut renamed = 0;
+    for file in list(instance, kind)? {
+        let enabled = enabled != except.contains(&file.file_name.as_str());
+        if file.enabled != enabled && set_enabled(instance, kind, &file.file_name, enabled)? {
+            renamed += 1;
+        }
+    }
+    Ok(renamed)
+}
+
+/// Deletes the content file `file_name`, enabled or not, and forgets it in the instance's lock
+///
+/// packs that are directories are deleted with their content
+pub fn remove(
+    instance: &InstanceMetadata,
+    kind: ContentKind,
+    file_name: &str,
+) -> Result<(), BackendError> {
+    let file_name = content_name(file_name)?;
+    let dir = content_dir(instance, kind);
+    let paths = [
+        dir.join(file_name),
+        dir.join(format!("{file_name}{DISABLED_SUFFIX}")),
+    ];
+    if !paths.iter().any(|path| path.exists()) {
+        return Err(InstanceError::ContentNotFound(file_name.to_string()).into());
+    }
+
+    for path in paths {
+        if path.is_dir() {
+            fs::remove_dir_all(path)?;
+        } else if path.exists() {
+            fs::remove_file(path)?;
+        }
+    }
+    content_lock::unlock_files(
+        instance,
+        [Path::new(kind.dir_name()).join(file_name).as_path()],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_name_strips_the_disabled_suffix() {
+        assert_eq!(content_name("sodium.jar").unwrap(), "sodium.jar");
+        assert_eq!(content_name("sodium.jar.disabled").unwrap(), "sodium.jar");
+    }
+
+    #[test]
+    fn content_name_refuses_paths() {
+        for name in [
+            "",
+            ".",
+            "..",
+            "../config",
+            "..disabled",
+            "mods/sodium.jar",
+            "..\\sodium.jar",
+            "/etc/passwd",
+        ] {
+            assert!(content_name(name).is_err(), "'{name}' was accepted");
+        }
+    }
+}
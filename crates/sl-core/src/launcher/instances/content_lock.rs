@@ -0,0 +1,375 @@
+use std::path::{Component, Path, PathBuf};
+
+use futures::{stream::FuturesUnordered, StreamExt};
+use serde::{Deserialize, Serialize};
+use sl_utils::{
+    errors::{BackendError, HttpError, SchemaError},
+    fs::hash_file,
+    wlog,
+};
+use strum_macros::Display;
+
+use crate::{
+    launcher::{
+        instances::{
+            content::{self, ContentKind, DISABLED_SUFFIX},
+            instance_metadata::InstanceMetadata,
+        },
+        migrations::{load_versioned, save_versioned, Migration, Versioned},
+    },
+    REQUESTER,
+};
+
+pub const CONTENT_LOCK_FILE_NAME: &str = "content.lock.json";
+
+/// Where a file of an instance was installed from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ContentSource {
+    Modrinth,
+    Curseforge,
+    /// Installed with a modpack
+    Modpack,
+    /// Added by hand to the instance's directory
+    Manual,
+}
+
+/// Why a file was installed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallReason {
+    /// The player installed it
+    User,
+    /// Another project requires it
+    Dependency,
+}
+
+/// Everything the lock records about a file but its content
+#[derive(Debug, Clone)]
+pub struct FileOrigin {
+    pub source: ContentSource,
+    pub project_id: Option<String>,
+    pub version_id: Option<String>,
+    /// Where the file is downloaded from again
+    pub url: Option<String>,
+    pub reason: InstallReason,
+}
+
+impl FileOrigin {
+    /// A file added by hand, which can't be downloaded again
+    pub fn manual() -> Self {
+        Self {
+            source: ContentSource::Manual,
+            project_id: None,
+            version_id: None,
+            url: None,
+            reason: InstallReason::User,
+        }
+    }
+}
+
+impl From<LockedFile> for FileOrigin {
+    fn from(file: LockedFile) -> Self {
+        Self {
+            source: file.source,
+            project_id: file.project_id,
+            version_id: file.version_id,
+            url: file.url,
+            reason: file.reason,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockedFile {
+    /// Relative to the instance and separated by `/`, without [`DISABLED_SUFFIX`] so disabling a file keeps it locked
+    pub path: String,
+    pub sha1: String,
+    pub sha512: String,
+    pub size: u64,
+    pub source: ContentSource,
+    pub project_id: Option<String>,
+    pub version_id: Option<String>,
+    /// `None` for files that have to be added by hand
+    pub url: Option<String>,
+    pub reason: InstallReason,
+}
+
+impl LockedFile {
+    /// Whether the file stays inside the instance, a lock edited by hand could point anywhere
+    pub fn has_safe_path(&self) -> bool {
+        !self.path.is_empty()
+            && Path::new(&self.path)
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+    }
+}
+
+/// The files installed in an instance and where they came from, stored as [`CONTENT_LOCK_FILE_NAME`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentLock {
+    pub scheme_version: u32,
+    /// Sorted by path
+    pub files: Vec<LockedFile>,
+}
+
+impl Versioned for ContentLock {
+    const MIGRATIONS: &'static [Migration] = &[];
+}
+
+impl Default for ContentLock {
+    fn default() -> Self {
+        Self {
+            scheme_version: Self::SCHEME_VERSION,
+            files: Vec::new(),
+        }
+    }
+}
+
+impl ContentLock {
+    /// Loads the lock of the instance, an empty one if nothing was locked yet
+    ///
+    /// the files that would be outside of the instance are left out, so nothing is ever read or downloaded there
+    pub fn load(instance: &InstanceMetadata) -> Result<Self, SchemaError> {
+        let path = instance.instance_dir().join(CONTENT_LOCK_FILE_NAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let mut lock: Self = load_versioned(&path)?;
+        lock.files.retain(|file| {
+            let safe = file.has_safe_path();
+            if !safe {
+                wlog!(
+                    "Ignoring the locked file '{}', which is outside of the instance",
+                    file.path
+                );
+            }
+            safe
+        });
+        Ok(lock)
+    }
+
+    pub fn save(&self, instance: &InstanceMetadata) -> std::io::Result<()> {
+        save_versioned(&instance.instance_dir().join(CONTENT_LOCK_FILE_NAME), self)
+    }
+
+    pub fn get(&self, path: &str) -> Option<&LockedFile> {
+        self.files.iter().find(|file| file.path == path)
+    }
+
+    /// Locks `file`, replacing the file locked at the same path
+    pub fn insert(&mut self, file: LockedFile) {
+        match self
+            .files
+            .binary_search_by(|locked| locked.path.as_str().cmp(&file.path))
+        {
+            Ok(index) => self.files[index] = file,
+            Err(index) => self.files.insert(index, file),
+        }
+    }
+
+    pub fn remove(&mut self, path: &str) -> Option<LockedFile> {
+        let index = self.files.iter().position(|file| file.path == path)?;
+        Some(self.files.remove(index))
+    }
+}
+
+/// The path `relative` is locked as, see [`LockedFile::path`]
+pub fn lock_path(relative: &Path) -> String {
+    let path = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    content::parse_file_name(&path).0.to_string()
+}
+
+/// Where the locked file at `path` is in `instance_dir`, enabled or not, `None` if it is missing
+fn find_on_disk(instance_dir: &Path, path: &str) -> Option<PathBuf> {
+    [
+        instance_dir.join(path),
+        instance_dir.join(format!("{path}{DISABLED_SUFFIX}")),
+    ]
+    .into_iter()
+    .find(|path| path.is_file())
+}
+
+fn locked_file(path: &Path, lock_path: String, origin: FileOrigin) -> std::io::Result<LockedFile> {
+    let digest = hash_file(path)?;
+    Ok(LockedFile {
+        path: lock_path,
+        sha1: digest.sha1,
+        sha512: digest.sha512,
+        size: digest.size,
+        source: origin.source,
+        project_id: origin.project_id,
+        version_id: origin.version_id,
+        url: origin.url,
+        reason: origin.reason,
+    })
+}
+
+/// Locks the files at the given paths, relative to the instance, hashing them as they are now
+pub fn lock_files(
+    instance: &InstanceMetadata,
+    files: impl IntoIterator<Item = (PathBuf, FileOrigin)>,
+) -> Result<(), BackendError> {
+    let instance_dir = instance.instance_dir();
+    let mut lock = ContentLock::load(instance)?;
+
+    for (relative, origin) in files {
+        let path = lock_path(&relative);
+        let Some(on_disk) = find_on_disk(&instance_dir, &path) else {
+            wlog!("Can't lock '{path}', which isn't in the instance");
+            continue;
+        };
+        lock.insert(locked_file(&on_disk, path, origin)?);
+    }
+
+    lock.save(instance)?;
+    Ok(())
+}
+
+/// Forgets the files at the given paths, relative to the instance, returns what was locked for them
+pub fn unlock_files<'a>(
+    instance: &InstanceMetadata,
+    paths: impl IntoIterator<Item = &'a Path>,
+) -> Result<Vec<LockedFile>, BackendError> {
+    let mut lock = ContentLock::load(instance)?;
+    let removed = paths
+        .into_iter()
+        .filter_map(|path| lock.remove(&lock_path(path)))
+        .collect();
+
+    lock.save(instance)?;
+    Ok(removed)
+}
+
+/// What [`sync`] did
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SyncReport {
+    /// The locked files that were missing and downloaded again
+    pub restored: Vec<String>,
+    /// The locked files that are missing and couldn't be downloaded, with the reason
+    pub missing: Vec<String>,
+    /// The files that weren't locked, now locked as added by hand
+    pub added: Vec<String>,
+}
+
+/// Downloads the locked file into `instance_dir` and checks it is the one that was locked
+async fn restore_file(instance_dir: &Path, file: &LockedFile) -> Result<(), HttpError> {
+    let url = file.url.as_deref().ok_or(HttpError::InvalidURL)?;
+    let path = instance_dir.join(&file.path);
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    REQUESTER.builder().download_to(url, &path).await?;
+
+    let digest = {
+        let path = path.clone();
+        tokio::task::spawn_blocking(move || hash_file(&path))
+            .await
+            .map_err(std::io::Error::other)??
+    };
+    if !digest.sha512.eq_ignore_ascii_case(&file.sha512) {
+        _ = tokio::fs::remove_file(&path).await;
+        return Err(HttpError::HashMismatch(url.to_string()));
+    }
+    Ok(())
+}
+
+/// Brings the instance in line with its lock, on a new machine for example
+///
+/// the locked files that are missing are downloaded again, and the mods and packs that aren't locked are locked as added by hand
+pub async fn sync(instance: &InstanceMetadata) -> Result<SyncReport, BackendError> {
+    let instance_dir = instance.instance_dir();
+    let mut lock = ContentLock::load(instance)?;
+    let mut report = SyncReport::default();
+
+    for kind in [
+        ContentKind::Mod,
+        ContentKind::ResourcePack,
+        ContentKind::ShaderPack,
+    ] {
+        for file in content::list(instance, kind)? {
+            let relative = Path::new(kind.dir_name()).join(&file.file_name);
+            let path = lock_path(&relative);
+            if lock.get(&path).is_some() {
+                continue;
+            }
+            // packs that are directories can't be hashed, and can't be downloaded either
+            let Some(on_disk) = find_on_disk(&instance_dir, &path) else {
+                continue;
+            };
+            lock.insert(locked_file(&on_disk, path.clone(), FileOrigin::manual())?);
+            report.added.push(path);
+        }
+    }
+
+    let mut tasks = FuturesUnordered::new();
+    for file in &lock.files {
+        if find_on_disk(&instance_dir, &file.path).is_some() {
+            continue;
+        }
+        if file.url.is_none() {
+            report
+                .missing
+                .push(format!("{} (added by hand, add it again)", file.path));
+            continue;
+        }
+
+        let instance_dir = &instance_dir;
+        tasks.push(async move { (file, restore_file(instance_dir, file).await) });
+    }
+
+    while let Some((file, result)) = tasks.next().await {
+        match result {
+            Ok(()) => report.restored.push(file.path.clone()),
+            Err(err) => report.missing.push(format!("{} ({err})", file.path)),
+        }
+    }
+    drop(tasks);
+
+    report.restored.sort();
+    report.missing.sort();
+    lock.save(instance)?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locked(path: &str) -> LockedFile {
+        LockedFile {
+            path: path.to_string(),
+            sha1: String::new(),
+            sha512: String::new(),
+            size: 0,
+            source: ContentSource::Manual,
+            project_id: None,
+            version_id: None,
+            url: None,
+            reason: InstallReason::User,
+        }
+    }
+
+    #[test]
+    fn safe_paths_stay_in_the_instance() {
+        assert!(locked("mods/sodium.jar").has_safe_path());
+        assert!(locked("config/sodium/options.json").has_safe_path());
+
+        for path in [
+            "",
+            "..",
+            "../sodium.jar",
+            "mods/../../sodium.jar",
+            "/etc/passwd",
+            "./mods",
+        ] {
+            assert!(!locked(path).has_safe_path(), "'{path}' was accepted");
+        }
+    }
+}
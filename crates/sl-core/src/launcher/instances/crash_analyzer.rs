@@ -0,0 +1,269 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::Serialize;
+use sl_utils::dlog;
+
+use crate::launcher::instances::run_logs;
+
+/// A known reason for the game to fail
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CrashKind {
+    WrongJavaVersion,
+    OutOfMemory,
+    MissingDependency,
+    MixinFailure,
+    DuplicateMod,
+    GraphicsDriver,
+    IncompatibleLoader,
+}
+
+impl CrashKind {
+    pub const fn description(&self) -> &'static str {
+        match self {
+            CrashKind::WrongJavaVersion => "The game or one of its mods needs a different Java version",
+            CrashKind::OutOfMemory => "The game ran out of memory",
+            CrashKind::MissingDependency => "A mod is missing one of its dependencies",
+            CrashKind::MixinFailure => "A mod failed to patch the game",
+            CrashKind::DuplicateMod => "The same mod is installed more than once",
+            CrashKind::GraphicsDriver => "The graphics driver failed",
+            CrashKind::IncompatibleLoader => {
+                "A mod doesn't support this mod loader or game version"
+            }
+        }
+    }
+
+    pub const fn suggestion(&self) -> &'static str {
+        match self {
+            CrashKind::WrongJavaVersion => {
+                "Set `java.path` in the instance's config.toml to the required Java version"
+            }
+            CrashKind::OutOfMemory => "Raise `java.max_ram` in the instance's config.toml",
+            CrashKind::MissingDependency => {
+                "Install the missing mod, or remove the mod that depends on it"
+            }
+            CrashKind::MixinFailure => {
+                "Update or remove the mod, it is likely incompatible with this game version or another mod"
+            }
+            CrashKind::DuplicateMod => "Remove the extra copies of the mod from the mods folder",
+            CrashKind::GraphicsDriver => {
+                "Update your graphics drivers, and make sure the game runs on your dedicated GPU"
+            }
+            CrashKind::IncompatibleLoader => {
+                "Install a version of the mod made for this mod loader and game version"
+            }
+        }
+    }
+}
+
+/// A failure detected in one of the files written by the game
+#[derive(Debug, Clone, Serialize)]
+pub struct CrashFinding {
+    pub kind: CrashKind,
+    /// What the failure is about, such as the missing mod or the required Java version, if known
+    pub details: Option<String>,
+    /// The line the failure was detected from
+    pub evidence: String,
+    /// The file `evidence` was found in
+    pub source: PathBuf,
+}
+
+/// The result of [`analyze`]
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct CrashAnalysis {
+    /// The crash reports and JVM error logs that were written during the run
+    pub reports: Vec<PathBuf>,
+    pub findings: Vec<CrashFinding>,
+}
+
+struct Rule {
+    kind: CrashKind,
+    /// the first capture group, if any, is used as the finding's details
+    pattern: Regex,
+}
+
+fn rule(kind: CrashKind, pattern: &str) -> Rule {
+    Rule {
+        kind,
+        pattern: Regex::new(pattern).expect("Failed to compile crash analyzer regex!"),
+    }
+}
+
+lazy_static! {
+    // checked in order, the first rule matching a line wins
+    static ref RULES: Vec<Rule> = vec![
+        rule(CrashKind::WrongJavaVersion, r"compiled by a more recent version of the Java Runtime \(class file version (\d+)"),
+        rule(CrashKind::WrongJavaVersion, r"UnsupportedClassVersionError"),
+        rule(CrashKind::WrongJavaVersion, r"AppClassLoader cannot be cast to (?:class )?java\.net\.URLClassLoader"),
+        rule(CrashKind::WrongJavaVersion, r"(?i)requires java (\d+)"),
+        rule(CrashKind::WrongJavaVersion, r"requires version (.+?) of '?java'?"),
+        rule(CrashKind::WrongJavaVersion, r"requires version (.+?) of mod '[^']*' \(java\)"),
+        rule(CrashKind::OutOfMemory, r"java\.lang\.OutOfMemoryError(?:: (.+))?"),
+        rule(CrashKind::OutOfMemory, r"There is insufficient memory for the Java Runtime Environment"),
+        rule(CrashKind::IncompatibleLoader, r"requires (?:version .+? of|any version of) '?(fabricloader|quilt_loader|minecraft)'?"),
+        rule(CrashKind::IncompatibleLoader, r"requires (?:version .+?|any version) of mod '[^']*' \((fabricloader|quilt_loader|minecraft)\)"),
+        rule(CrashKind::IncompatibleLoader, r"needs language provider (\S+)"),
+        rule(CrashKind::IncompatibleLoader, r"(?i)incompatible mods? found"),
+        rule(CrashKind::MissingDependency, r"requires (?:version .+? of|any version of) '?([\w-]+)'?,? which is missing"),
+        rule(CrashKind::MissingDependency, r"requires (?:version .+?|any version) of mod '[^']*' \(([\w-]+)\),? which is missing"),
+        rule(CrashKind::MissingDependency, r"Mod ID: '([^']+)', Requested by: '[^']+'"),
+        rule(CrashKind::MissingDependency, r"Missing or unsupported mandatory dependencies"),
+        rule(CrashKind::DuplicateMod, r"(?i)duplicate mods?\b.*?'([\w-]+)'"),
+        rule(CrashKind::DuplicateMod, r"(?i)duplicate mods?\b"),
+        rule(CrashKind::MixinFailure, r"Mixin apply (?:for mod (\S+) )?failed"),
+        rule(CrashKind::MixinFailure, r"Mixin \[([^\]]+)\] from phase \[\w+\] in config"),
+        rule(CrashKind::MixinFailure, r"MixinTransformerError|InvalidMixinException"),
+        rule(CrashKind::GraphicsDriver, r"Pixel format not accelerated"),
+        rule(CrashKind::GraphicsDriver, r"GLFW error (\d+)"),
+        rule(CrashKind::GraphicsDriver, r"The driver does not appear to support OpenGL"),
+        rule(CrashKind::GraphicsDriver, r"^#\s+C\s+\[(atio6axx|atioglxx|nvoglv\w*|ig\w+icd\w*|libGL\w*|iris_dri|radeonsi_dri)\b"),
+    ];
+}
+
+/// Java's class file versions are offset by 44 from the Java version that produced them
+fn java_version_of(class_file_version: &str) -> Option<String> {
+    let version = class_file_version.parse::<u32>().ok()?;
+    Some(format!("Java {}", version.checked_sub(44)?))
+}
+
+fn analyze_text(text: &str, source: &Path, findings: &mut Vec<CrashFinding>) {
+    let mut seen: HashSet<(CrashKind, Option<String>)> = findings
+        .iter()
+        .map(|finding| (finding.kind, finding.details.clone()))
+        .collect();
+
+    for line in text.lines() {
+        let line = line.trim();
+        let Some((kind, captures)) = RULES
+            .iter()
+            .find_map(|rule| Some((rule.kind, rule.pattern.captures(line)?)))
+        else {
+            continue;
+        };
+
+        let details = captures.get(1).map(|m| m.as_str());
+        let details = match kind {
+            CrashKind::WrongJavaVersion if line.contains("class file version") => {
+                details.and_then(java_version_of)
+            }
+            _ => details.map(str::to_string),
+        };
+
+        if seen.insert((kind, details.clone())) {
+            findings.push(CrashFinding {
+                kind,
+                details,
+                evidence: line.to_string(),
+                source: source.to_path_buf(),
+            });
+        }
+    }
+}
+
+/// Files in `dir` whose name matches `filter` that were modified after `since`
+fn files_since(dir: &Path, since: SystemTime, filter: impl Fn(&str) -> bool) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut files = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| filter(&entry.file_name().to_string_lossy()))
+        .filter(|entry| {
+            entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .is_ok_and(|modified| modified >= since)
+        })
+        .map(|entry| entry.path())
+        .collect::<Vec<_>>();
+
+    files.sort();
+    files
+}
+
+/// Looks for known failures in the crash reports and JVM error logs written since `since`, and the latest run's log
+pub fn analyze(instance_dir: &Path, since: SystemTime) -> CrashAnalysis {
+    let mut reports = files_since(&instance_dir.join("crash-reports"), since, |name| {
+        name.ends_with(".txt")
+    });
+    reports.extend(files_since(instance_dir, since, |name| {
+        name.starts_with("hs_err_pid") && name.ends_with(".log")
+    }));
+
+    let mut findings = Vec::new();
+    for path in &reports {
+        match std::fs::read(path) {
+            Ok(bytes) => analyze_text(&String::from_utf8_lossy(&bytes), path, &mut findings),
+            Err(err) => dlog!("Failed to read {} for crash analysis: {err}", path.display()),
+        }
+    }
+
+    let last_run = run_logs::list_runs(instance_dir)
+        .ok()
+        .and_then(|runs| runs.into_iter().next());
+    if let Some(run) = last_run {
+        match run_logs::read_run(instance_dir, &run.id) {
+            Ok(log) => analyze_text(&log, &run_logs::run_log_path(instance_dir, &run.id), &mut findings),
+            Err(err) => dlog!("Failed to read run {} for crash analysis: {err}", run.id),
+        }
+    }
+
+    CrashAnalysis { reports, findings }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn findings(text: &str) -> Vec<(CrashKind, Option<String>)> {
+        let mut findings = Vec::new();
+        analyze_text(text, Path::new("latest.log"), &mut findings);
+        findings
+            .into_iter()
+            .map(|finding| (finding.kind, finding.details))
+            .collect()
+    }
+
+    #[test]
+    fn fabric_missing_dependency() {
+        assert_eq!(
+            findings("- Mod 'Sodium Extra' (sodium-extra) 0.5.4 requires version 0.5.0 or later of mod 'Sodium' (sodium), which is missing!"),
+            [(CrashKind::MissingDependency, Some("sodium".to_string()))]
+        );
+        assert_eq!(
+            findings("- Mod 'Mod Menu' (modmenu) 11.0.1 requires any version of mod 'Fabric API' (fabric-api), which is missing!"),
+            [(CrashKind::MissingDependency, Some("fabric-api".to_string()))]
+        );
+        assert_eq!(
+            findings("- Mod 'Foo' (foo) 1.0 requires any version of fabric-api, which is missing!"),
+            [(CrashKind::MissingDependency, Some("fabric-api".to_string()))]
+        );
+    }
+
+    #[test]
+    fn fabric_loader_and_java_requirements() {
+        assert_eq!(
+            findings("- Mod 'Foo' (foo) 1.0 requires version 0.16.0 or later of mod 'Fabric Loader' (fabricloader), but only the wrong version is present: 0.15.11!"),
+            [(CrashKind::IncompatibleLoader, Some("fabricloader".to_string()))]
+        );
+        assert_eq!(
+            findings("- Mod 'Foo' (foo) 1.0 requires version 21 or later of mod 'OpenJDK 64-Bit Server VM' (java), but only the wrong version is present: 17!"),
+            [(CrashKind::WrongJavaVersion, Some("21 or later".to_string()))]
+        );
+    }
+
+    #[test]
+    fn class_file_version() {
+        assert_eq!(
+            findings("java.lang.UnsupportedClassVersionError: Foo has been compiled by a more recent version of the Java Runtime (class file version 65.0)"),
+            [(CrashKind::WrongJavaVersion, Some("Java 21".to_string()))]
+        );
+    }
+}
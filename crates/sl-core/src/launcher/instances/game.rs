@@ -4,24 +4,23 @@ use sha1::Digest;
 use sha2::Sha512;
 use sl_utils::{errors::BackendError, wlog};
 use std::{
-    io::{BufReader, Cursor, Read},
+    io::{Cursor, Read},
     path::Path,
 };
 use zip::ZipArchive;
 
-use crate::{launcher::instances::instance_metadata::ModLoader, INSTANCES_DIR};
-
-#[derive(Debug, Deserialize, Serialize)]
-pub struct ModMetadata {
-    pub id: String,
-    pub name: String,
-    pub version: String,
-}
+use crate::launcher::instances::{
+    content,
+    instance_metadata::ModLoader,
+    mod_metadata::{read_mod_metadata, ModMetadata},
+};
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Mod {
     pub metadata: ModMetadata,
+    /// The name of the file without [`content::DISABLED_SUFFIX`]
     pub file_name: String,
+    pub enabled: bool,
     pub sha512: String,
     pub icon: Option<String>,
 }
@@ -31,51 +30,30 @@ pub fn get_mod_metadata(
     mod_path: &Path,
 ) -> Result<Option<Mod>, BackendError> {
     let mod_data = std::fs::read(mod_path)?;
-    let sha512 = hex::encode(Sha512::digest(&mod_data));
-
-    let meta_entry_name = match instance_mod_loader {
-        ModLoader::Fabric => "fabric.mod.json",
-        ModLoader::Quilt => "quilt.mod.json",
-        ModLoader::Forge => unimplemented!("Forge metadata parsing"),
-        ModLoader::NeoForge => unimplemented!("NeoForge parsing"),
-        ModLoader::Vanilla => return Ok(None),
-    };
-
     let mut archive = ZipArchive::new(Cursor::new(&mod_data))?;
 
-    let metadata = {
-        let entry = match archive.by_name(meta_entry_name) {
-            Ok(e) => e,
-            Err(_) => return Ok(None),
-        };
-
-        let entry_reader = BufReader::new(entry);
-        serde_json::from_reader::<_, ModMetadata>(entry_reader)?
+    let Some(metadata) = read_mod_metadata(&mut archive, *instance_mod_loader)? else {
+        return Ok(None);
     };
+    let sha512 = hex::encode(Sha512::digest(&mod_data));
 
-    let icon = {
-        (0..archive.len()).find_map(|i| {
-            let mut entry = archive.by_index(i).ok()?;
-            let entry_name = entry.name();
-
-            if !entry_name.contains('/') && entry_name.ends_with(".png") {
-                let mut buf2 = Vec::new();
-                entry.read_to_end(&mut buf2).ok()?;
-                return Some(general_purpose::STANDARD.encode(buf2));
-            }
-            None
-        })
-    };
+    let icon = metadata.icon.as_deref().and_then(|icon_path| {
+        let mut entry = archive.by_name(icon_path).ok()?;
+        let mut buf = Vec::new();
+        entry.read_to_end(&mut buf).ok()?;
+        Some(general_purpose::STANDARD.encode(buf))
+    });
 
     let file_name = mod_path
         .file_name()
         .and_then(|s| s.to_str())
-        .expect("mod path isn't valid utf8")
-        .to_string();
+        .expect("mod path isn't valid utf8");
+    let (file_name, enabled) = content::parse_file_name(file_name);
 
     Ok(Some(Mod {
         metadata,
-        file_name,
+        file_name: file_name.to_string(),
+        enabled,
         sha512,
         icon,
     }))
@@ -169,7 +147,8 @@ pub fn get_game_info(
         Ok(results_iter.collect())
     }
 
-    let instance_path = &*INSTANCES_DIR.join(instance_name);
+    let (instance, _) = super::get_existing(instance_name)?;
+    let instance_path = &*instance.instance_dir();
     let saves_path = instance_path.join("saves");
 
     let worlds = if saves_path.exists() {
@@ -0,0 +1,96 @@
+use std::{fs, path::Path};
+
+use serde::Deserialize;
+use serde_json::Value;
+use sl_utils::errors::InstanceImportErr;
+
+use crate::launcher::instances::{
+    importers::{ForeignInstance, ImportedSettings},
+    instance_metadata::ModLoader,
+    INSTANCE_FILE_NAME,
+};
+
+/// ATLauncher's own files, the game's files are next to them
+const LAUNCHER_FILES: &[&str] = &["disabledmods"];
+
+fn excludes(file_name: &str) -> bool {
+    LAUNCHER_FILES.contains(&file_name)
+}
+
+#[derive(Debug, Deserialize)]
+struct LoaderVersion {
+    version: String,
+    r#type: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LauncherInfo {
+    name: String,
+    loader_version: Option<LoaderVersion>,
+    initial_memory: Option<usize>,
+    maximum_memory: Option<usize>,
+    java_path: Option<String>,
+    java_arguments: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Instance {
+    /// The Minecraft version
+    id: String,
+    launcher: LauncherInfo,
+}
+
+/// Whether the `instance.json` parsed as `document` is ATLauncher's, which unlike ours has a `launcher` section
+pub(crate) fn is_atlauncher_instance(document: &Value) -> bool {
+    document.get("launcher").is_some_and(Value::is_object)
+}
+
+/// Reads the ATLauncher instance at `root`, the directory holding its `instance.json`
+pub(crate) fn read(root: &Path) -> Result<ForeignInstance, InstanceImportErr> {
+    let instance: Instance =
+        serde_json::from_str(&fs::read_to_string(root.join(INSTANCE_FILE_NAME))?)?;
+    let launcher = instance.launcher;
+
+    let (mod_loader, mod_loader_version) = match launcher.loader_version {
+        Some(loader) => {
+            let mod_loader = match loader.r#type.as_str() {
+                "Fabric" => ModLoader::Fabric,
+                "Quilt" => ModLoader::Quilt,
+                "Forge" => ModLoader::Forge,
+                "NeoForge" => ModLoader::NeoForge,
+                _ => return Err(InstanceImportErr::UnsupportedModLoader(loader.r#type)),
+            };
+            // Forge versions may be prefixed with the Minecraft version
+            let version = loader
+                .version
+                .strip_prefix(&format!("{}-", instance.id))
+                .unwrap_or(&loader.version)
+                .to_string();
+            (mod_loader, version)
+        }
+        None => (ModLoader::Vanilla, String::new()),
+    };
+
+    let mut settings = ImportedSettings {
+        min_ram: launcher.initial_memory,
+        max_ram: launcher.maximum_memory,
+        ..Default::default()
+    };
+    if let Some(java_path) = launcher.java_path.filter(|path| !path.is_empty()) {
+        settings.set_java_path(java_path.into());
+    }
+    if let Some(java_arguments) = &launcher.java_arguments {
+        settings.add_jvm_args(java_arguments);
+    }
+
+    Ok(ForeignInstance {
+        name: launcher.name,
+        mc_version: instance.id,
+        mod_loader,
+        mod_loader_version,
+        game_dir: root.to_path_buf(),
+        excludes,
+        settings,
+    })
+}
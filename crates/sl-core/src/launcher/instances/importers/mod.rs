@@ -0,0 +1,194 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use sl_java_manager::JAVA_BINARY;
+use sl_utils::{errors::InstanceImportErr, wlog};
+
+use crate::{
+    launcher::{
+        instances::{
+            instance_config::CONFIG_FILE_NAME,
+            instance_importer::instance_name,
+            instance_metadata::{InstanceMetadata, ModLoader},
+            INSTANCE_FILE_NAME,
+        },
+        migrations::save_versioned,
+    },
+    VERSION_MANIFEST,
+};
+
+pub(super) mod atlauncher;
+pub(super) mod multimc;
+pub(super) mod vanilla;
+
+/// The launcher settings of an imported instance that have an equivalent in its `config.toml`
+#[derive(Debug, Default)]
+pub(super) struct ImportedSettings {
+    pub min_ram: Option<usize>,
+    pub max_ram: Option<usize>,
+    pub java_path: Option<PathBuf>,
+    pub jvm_args: Vec<String>,
+    pub window_width: Option<u32>,
+    pub window_height: Option<u32>,
+}
+
+/// Parses a memory size as given to `-Xmx`, like `4G` or `512m`, into MiB
+fn parse_memory(size: &str) -> Option<usize> {
+    let (amount, unit) = size.split_at(size.find(|c: char| !c.is_ascii_digit())?);
+    let amount = amount.parse::<usize>().ok()?;
+
+    match unit {
+        "k" | "K" => Some(amount / 1024),
+        "m" | "M" => Some(amount),
+        "g" | "G" => Some(amount * 1024),
+        _ => None,
+    }
+}
+
+impl ImportedSettings {
+    /// Takes the heap sizes out of `args`, the other arguments are kept as extra arguments
+    pub fn add_jvm_args(&mut self, args: &str) {
+        for arg in args.split_whitespace() {
+            if let Some(max_ram) = arg.strip_prefix("-Xmx").and_then(parse_memory) {
+                self.max_ram = Some(max_ram);
+            } else if let Some(min_ram) = arg.strip_prefix("-Xms").and_then(parse_memory) {
+                self.min_ram = Some(min_ram);
+            } else {
+                self.jvm_args.push(arg.to_string());
+            }
+        }
+    }
+
+    /// Sets the Java the instance was using, unless it isn't installed on this system
+    ///
+    /// `path` is either the `java` executable or the directory Java is installed in
+    pub fn set_java_path(&mut self, path: PathBuf) {
+        let path = match path.is_dir() {
+            true => path.join("bin").join(JAVA_BINARY),
+            false => path,
+        };
+
+        match path.exists() {
+            true => self.java_path = Some(path),
+            false => wlog!(
+                "The Java at '{}' the instance was using doesn't exist, the default one is used instead",
+                path.display()
+            ),
+        }
+    }
+
+    fn to_toml(&self) -> toml::Table {
+        let mut java = toml::Table::new();
+        if let Some(min_ram) = self.min_ram {
+            java.insert("min_ram".to_string(), (min_ram as i64).into());
+        }
+        if let Some(max_ram) = self.max_ram {
+            java.insert("max_ram".to_string(), (max_ram as i64).into());
+        }
+        if let Some(java_path) = &self.java_path {
+            java.insert(
+                "path".to_string(),
+                java_path.to_string_lossy().to_string().into(),
+            );
+        }
+        if !self.jvm_args.is_empty() {
+            java.insert("extra_args".to_string(), self.jvm_args.clone().into());
+        }
+
+        let mut minecraft = toml::Table::new();
+        if let Some(width) = self.window_width {
+            minecraft.insert("window_width".to_string(), i64::from(width).into());
+        }
+        if let Some(height) = self.window_height {
+            minecraft.insert("window_height".to_string(), i64::from(height).into());
+        }
+
+        let mut config = toml::Table::new();
+        if !java.is_empty() {
+            config.insert("java".to_string(), java.into());
+        }
+        if !minecraft.is_empty() {
+            config.insert("minecraft".to_string(), minecraft.into());
+        }
+        config
+    }
+}
+
+/// An instance of another launcher, read but not imported yet
+#[derive(Debug)]
+pub(super) struct ForeignInstance {
+    pub name: String,
+    pub mc_version: String,
+    pub mod_loader: ModLoader,
+    pub mod_loader_version: String,
+    /// The directory holding the game's files (`.minecraft`), its content becomes the instance's directory
+    pub game_dir: PathBuf,
+    /// Whether a top level entry of `game_dir` belongs to the other launcher and is left out
+    pub excludes: fn(&str) -> bool,
+    pub settings: ImportedSettings,
+}
+
+fn copy_game_dir(src: &Path, dst: &Path, excludes: fn(&str) -> bool) -> std::io::Result<()> {
+    fs::create_dir_all(dst)?;
+    // instances that were never launched have no game files yet
+    if !src.is_dir() {
+        return Ok(());
+    }
+
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        let name = file_name.to_string_lossy();
+        // the files the imported instance is made of are written after the copy
+        if excludes(&name) || name.starts_with(INSTANCE_FILE_NAME) || name == CONFIG_FILE_NAME {
+            continue;
+        }
+
+        if entry.file_type()?.is_dir() {
+            sl_utils::fs::copy_dir_all(entry.path(), dst.join(&file_name))?;
+        } else {
+            fs::copy(entry.path(), dst.join(&file_name))?;
+        }
+    }
+    Ok(())
+}
+
+impl ForeignInstance {
+    /// Adds the instance named `name`, or as in the other launcher, copying its game files and converting its settings
+    pub fn import(self, name: Option<String>) -> Result<InstanceMetadata, InstanceImportErr> {
+        let version = VERSION_MANIFEST
+            .get_version_by_id(&self.mc_version)
+            .ok_or_else(|| InstanceImportErr::MinecraftVersionNotFound(self.mc_version.clone()))?;
+
+        let instance = InstanceMetadata::new_unchecked(
+            instance_name(name, &self.name)?,
+            None,
+            version.id.clone(),
+            version.r#type,
+            version.release_time.clone(),
+            self.mod_loader,
+            self.mod_loader_version,
+        );
+
+        let instance_dir = instance.instance_dir();
+        let result = (|| -> Result<(), InstanceImportErr> {
+            copy_game_dir(&self.game_dir, &instance_dir, self.excludes)?;
+
+            let config = self.settings.to_toml();
+            if !config.is_empty() {
+                fs::write(instance_dir.join(CONFIG_FILE_NAME), config.to_string())?;
+            }
+
+            save_versioned(&instance_dir.join(INSTANCE_FILE_NAME), &instance)?;
+            Ok(())
+        })();
+
+        if let Err(err) = result {
+            _ = fs::remove_dir_all(&instance_dir);
+            return Err(err);
+        }
+        Ok(instance)
+    }
+}
@@ -0,0 +1,119 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use serde::Deserialize;
+use sl_utils::errors::InstanceImportErr;
+
+use crate::launcher::instances::{
+    importers::{ForeignInstance, ImportedSettings},
+    instance_metadata::ModLoader,
+};
+
+pub(crate) const CONFIG_NAME: &str = "instance.cfg";
+const PACK_NAME: &str = "mmc-pack.json";
+
+#[derive(Debug, Deserialize)]
+struct Component {
+    uid: String,
+    version: Option<String>,
+    /// Older packs only have the version here
+    #[serde(rename = "cachedVersion")]
+    cached_version: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Pack {
+    components: Vec<Component>,
+}
+
+/// Reads the `key=value` lines of `instance.cfg`, section headers are ignored
+fn read_config(path: &Path) -> std::io::Result<HashMap<String, String>> {
+    let config = fs::read_to_string(path)?
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| {
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .unwrap_or(value);
+            (key.trim().to_string(), value.to_string())
+        })
+        .collect();
+    Ok(config)
+}
+
+fn read_settings(config: &HashMap<String, String>) -> ImportedSettings {
+    let enabled = |key: &str| config.get(key).is_some_and(|value| value == "true");
+    let number = |key: &str| config.get(key).and_then(|value| value.parse().ok());
+
+    let mut settings = ImportedSettings::default();
+    if enabled("OverrideMemory") {
+        settings.min_ram = number("MinMemAlloc");
+        settings.max_ram = number("MaxMemAlloc");
+    }
+    if enabled("OverrideJavaLocation") || enabled("OverrideJava") {
+        if let Some(java_path) = config.get("JavaPath").filter(|path| !path.is_empty()) {
+            settings.set_java_path(java_path.into());
+        }
+    }
+    if enabled("OverrideJavaArgs") || enabled("OverrideJava") {
+        if let Some(jvm_args) = config.get("JvmArgs") {
+            settings.add_jvm_args(jvm_args);
+        }
+    }
+    if enabled("OverrideWindow") {
+        settings.window_width = number("MinecraftWinWidth").map(|width: usize| width as u32);
+        settings.window_height = number("MinecraftWinHeight").map(|height: usize| height as u32);
+    }
+    settings
+}
+
+/// Reads the MultiMC (or Prism Launcher) instance at `root`, the directory holding its `instance.cfg`
+pub(crate) fn read(root: &Path) -> Result<ForeignInstance, InstanceImportErr> {
+    let config = read_config(&root.join(CONFIG_NAME))?;
+    let pack: Pack = serde_json::from_str(&fs::read_to_string(root.join(PACK_NAME))?)?;
+
+    let mut mc_version = None;
+    let mut mod_loader = (ModLoader::Vanilla, String::new());
+    for component in pack.components {
+        let Some(version) = component.version.or(component.cached_version) else {
+            continue;
+        };
+
+        match component.uid.as_str() {
+            "net.minecraft" => mc_version = Some(version),
+            "net.minecraftforge" => mod_loader = (ModLoader::Forge, version),
+            "net.neoforged" => mod_loader = (ModLoader::NeoForge, version),
+            "net.fabricmc.fabric-loader" => mod_loader = (ModLoader::Fabric, version),
+            "org.quiltmc.quilt-loader" => mod_loader = (ModLoader::Quilt, version),
+            "com.mumfrey.liteloader" => {
+                return Err(InstanceImportErr::UnsupportedModLoader(component.uid))
+            }
+            // LWJGL, intermediary mappings and other libraries the loaders pull in
+            _ => {}
+        }
+    }
+    let mc_version = mc_version.ok_or(InstanceImportErr::Corrupted)?;
+
+    // Prism Launcher uses `minecraft` for new instances, MultiMC always used `.minecraft`
+    let game_dir = [".minecraft", "minecraft"]
+        .into_iter()
+        .map(|dir| root.join(dir))
+        .find(|dir| dir.is_dir())
+        .unwrap_or_else(|| root.join(".minecraft"));
+
+    let name = config
+        .get("name")
+        .cloned()
+        .unwrap_or_else(|| format!("Minecraft {mc_version}"));
+
+    Ok(ForeignInstance {
+        name,
+        mc_version,
+        mod_loader: mod_loader.0,
+        mod_loader_version: mod_loader.1,
+        game_dir,
+        excludes: |_| false,
+        settings: read_settings(&config),
+    })
+}
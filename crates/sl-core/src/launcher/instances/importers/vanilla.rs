@@ -0,0 +1,166 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use serde::Deserialize;
+use sl_utils::errors::InstanceImportErr;
+
+use crate::{
+    launcher::instances::{
+        importers::{ForeignInstance, ImportedSettings},
+        instance_metadata::ModLoader,
+    },
+    VERSION_MANIFEST,
+};
+
+pub(crate) const PROFILES_NAME: &str = "launcher_profiles.json";
+
+/// The launcher's own directories and the files the game downloads, only what the player made is imported
+const LAUNCHER_DIRS: &[&str] = &[
+    "versions",
+    "libraries",
+    "assets",
+    "runtime",
+    "bin",
+    "webcache2",
+];
+
+fn excludes(file_name: &str) -> bool {
+    LAUNCHER_DIRS.contains(&file_name)
+        || file_name.starts_with("launcher_")
+        || file_name == "treatment_tags.json"
+}
+
+#[derive(Debug, Deserialize)]
+struct Resolution {
+    width: u32,
+    height: u32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Profile {
+    #[serde(default)]
+    name: String,
+    last_version_id: String,
+    /// When the profile was last played, RFC 3339
+    #[serde(default)]
+    last_used: String,
+    game_dir: Option<String>,
+    /// The `java` executable, despite the name
+    java_dir: Option<String>,
+    java_args: Option<String>,
+    resolution: Option<Resolution>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Profiles {
+    profiles: HashMap<String, Profile>,
+}
+
+/// The part of a version's JSON telling which Minecraft version a mod loader's version is for
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VersionInfo {
+    inherits_from: Option<String>,
+}
+
+/// Finds the Minecraft version and mod loader of the version `version_id` installed at `root`
+///
+/// the mod loaders name their versions `fabric-loader-<loader>-<mc>`, `quilt-loader-<loader>-<mc>`, `<mc>-forge-<loader>` and `neoforge-<loader>`
+fn resolve_version(root: &Path, version_id: &str) -> (String, ModLoader, String) {
+    match version_id {
+        "latest-release" => {
+            return (
+                VERSION_MANIFEST.latest().release.clone(),
+                ModLoader::Vanilla,
+                String::new(),
+            )
+        }
+        "latest-snapshot" => {
+            return (
+                VERSION_MANIFEST.latest().snapshot.clone(),
+                ModLoader::Vanilla,
+                String::new(),
+            )
+        }
+        _ => {}
+    }
+
+    let version_file = root
+        .join("versions")
+        .join(version_id)
+        .join(format!("{version_id}.json"));
+    let inherits_from = fs::read_to_string(version_file)
+        .ok()
+        .and_then(|json| serde_json::from_str::<VersionInfo>(&json).ok())
+        .and_then(|info| info.inherits_from);
+
+    let Some(mc_version) = inherits_from else {
+        return (version_id.to_string(), ModLoader::Vanilla, String::new());
+    };
+
+    let suffix = format!("-{mc_version}");
+    let (mod_loader, loader_version) = if let Some(rest) = version_id.strip_prefix("fabric-loader-")
+    {
+        (
+            ModLoader::Fabric,
+            rest.strip_suffix(&suffix).unwrap_or(rest),
+        )
+    } else if let Some(rest) = version_id.strip_prefix("quilt-loader-") {
+        (ModLoader::Quilt, rest.strip_suffix(&suffix).unwrap_or(rest))
+    } else if let Some(rest) = version_id.strip_prefix("neoforge-") {
+        (ModLoader::NeoForge, rest)
+    } else if let Some((_, rest)) = version_id.split_once("-forge") {
+        // older Forge versions repeat the Minecraft version, as in `1.12.2-forge1.12.2-14.23.5.2859`
+        let rest = rest.trim_start_matches('-');
+        let rest = rest.strip_prefix(&format!("{mc_version}-")).unwrap_or(rest);
+        (ModLoader::Forge, rest)
+    } else {
+        (ModLoader::Vanilla, "")
+    };
+
+    (mc_version, mod_loader, loader_version.to_string())
+}
+
+/// Reads the most recently played profile of the official launcher's `.minecraft` at `root`
+pub(crate) fn read(root: &Path) -> Result<ForeignInstance, InstanceImportErr> {
+    let profiles: Profiles = serde_json::from_str(&fs::read_to_string(root.join(PROFILES_NAME))?)?;
+    let profile = profiles
+        .profiles
+        .into_values()
+        .max_by(|a, b| a.last_used.cmp(&b.last_used))
+        .ok_or(InstanceImportErr::NotAnInstance)?;
+
+    let (mc_version, mod_loader, mod_loader_version) =
+        resolve_version(root, &profile.last_version_id);
+
+    let mut settings = ImportedSettings::default();
+    if let Some(java_path) = profile.java_dir {
+        settings.set_java_path(java_path.into());
+    }
+    if let Some(java_args) = &profile.java_args {
+        settings.add_jvm_args(java_args);
+    }
+    if let Some(resolution) = profile.resolution {
+        settings.window_width = Some(resolution.width);
+        settings.window_height = Some(resolution.height);
+    }
+
+    // the default profiles have no name
+    let name = match profile.name.is_empty() {
+        true => format!("Minecraft {mc_version}"),
+        false => profile.name,
+    };
+    let game_dir = profile
+        .game_dir
+        .map_or_else(|| root.to_path_buf(), Into::into);
+
+    Ok(ForeignInstance {
+        name,
+        mc_version,
+        mod_loader,
+        mod_loader_version,
+        game_dir,
+        excludes,
+        settings,
+    })
+}
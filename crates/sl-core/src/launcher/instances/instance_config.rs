@@ -1,13 +1,22 @@
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 use serde::{Deserialize, Serialize};
 use sl_java_manager::{jre_manifest::installer::download_jre_manifest_version, JAVA_BINARY};
 use sl_meta::{minecraft::loaders::vanilla::JavaComponent};
-use sl_utils::errors::BackendError;
+use sl_utils::errors::{BackendError, InstanceError};
+use sl_utils::progress::Progress;
 use sl_utils::wlog;
 
 use crate::{
-    JAVAS_DIR, JRE_MANIFEST, LAUNCHER_DIR, REQUESTER
+    launcher::{
+        ensure_online_for,
+        instances::java_tuning::{JvmPreset, MemoryMode},
+        java::read_jre_manifest,
+    },
+    JAVAS_DIR, LAUNCHER_DIR, REQUESTER,
 };
 
 /// Defines the config file name, relative to the launcher directory and the instance directory.
@@ -28,15 +37,40 @@ const fn default_max_memory() -> usize {
     2048
 }
 
-async fn default_java_path(component: &JavaComponent) -> Result<PathBuf, BackendError> {
+const fn default_max_runs() -> usize {
+    20
+}
+
+const fn default_true() -> bool {
+    true
+}
+
+const fn default_keep_last() -> usize {
+    10
+}
+
+const fn default_keep_daily() -> usize {
+    7
+}
+
+const fn default_keep_weekly() -> usize {
+    4
+}
+
+async fn default_java_path(
+    component: &JavaComponent,
+    progress: &Progress,
+) -> Result<PathBuf, BackendError> {
     let java_path = JAVAS_DIR.join(component.to_string());
 
     if !java_path.exists() {
+        ensure_online_for(&java_path)?;
         download_jre_manifest_version(
             &REQUESTER,
-            &JRE_MANIFEST,
+            &read_jre_manifest()?,
             &JAVAS_DIR,
-            component
+            component,
+            progress,
         ).await?;
     }
 
@@ -44,7 +78,85 @@ async fn default_java_path(component: &JavaComponent) -> Result<PathBuf, Backend
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct MinecraftConfig {}
+pub struct MinecraftConfig {
+    /// The game window's width, the game's default is used if neither this or the height is set
+    pub window_width: Option<u32>,
+    pub window_height: Option<u32>,
+    #[serde(default)]
+    pub fullscreen: bool,
+    /// Launches the game in demo mode
+    #[serde(default)]
+    pub demo: bool,
+    /// Passed to the game after the arguments generated by the launcher
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+}
+
+/// How the game's process is started, and the commands run around it
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct LaunchConfig {
+    /// Commands the java command is prefixed with, such as `["gamemoderun", "mangohud"]`
+    #[serde(default)]
+    pub wrapper: Vec<String>,
+    /// Environment variables set for the game and the hooks
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// A shell command run in the instance's directory before launching, the launch is aborted if it fails
+    pub pre_launch: Option<String>,
+    /// A shell command run in the instance's directory after the game exits, `SL_EXIT_CODE` is set to its exit code
+    pub post_exit: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogsConfig {
+    /// How many runs' logs are kept, the oldest ones are removed when launching
+    #[serde(default = "default_max_runs")]
+    pub max_runs: usize,
+}
+
+impl Default for LogsConfig {
+    fn default() -> Self {
+        Self {
+            max_runs: default_max_runs(),
+        }
+    }
+}
+
+/// When the instance's worlds are backed up automatically, and which automatic backups are kept
+///
+/// manual backups are never removed automatically
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupsConfig {
+    #[serde(default)]
+    pub before_launch: bool,
+    #[serde(default = "default_true")]
+    pub after_exit: bool,
+    /// Before the instance's Minecraft version, mod loader or content is changed
+    #[serde(default = "default_true")]
+    pub before_update: bool,
+    /// How many of the latest backups of each world are kept
+    #[serde(default = "default_keep_last")]
+    pub keep_last: usize,
+    /// For how many days the latest backup of each day is kept
+    #[serde(default = "default_keep_daily")]
+    pub keep_daily: usize,
+    /// For how many weeks the latest backup of each week is kept
+    #[serde(default = "default_keep_weekly")]
+    pub keep_weekly: usize,
+}
+
+impl Default for BackupsConfig {
+    fn default() -> Self {
+        Self {
+            before_launch: false,
+            after_exit: default_true(),
+            before_update: default_true(),
+            keep_last: default_keep_last(),
+            keep_daily: default_keep_daily(),
+            keep_weekly: default_keep_weekly(),
+        }
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JavaConfig {
@@ -56,6 +168,15 @@ pub struct JavaConfig {
     // please give everything a default value somewhere
     pub path: PathBuf,
     pub javac_path: Option<PathBuf>,
+    /// The JVM flags the game is launched with, before `extra_args`
+    #[serde(default)]
+    pub preset: JvmPreset,
+    /// Whether `min_ram` and `max_ram` are used, or picked automatically
+    #[serde(default)]
+    pub memory: MemoryMode,
+    /// Passed to the JVM after the arguments generated by the launcher
+    #[serde(default)]
+    pub extra_args: Vec<String>,
 }
 
 impl JavaConfig {
@@ -105,11 +226,18 @@ pub struct InstanceConfig {
     #[serde(default)]
     pub minecraft: MinecraftConfig,
     pub java: JavaConfig,
+    #[serde(default)]
+    pub launch: LaunchConfig,
+    #[serde(default)]
+    pub logs: LogsConfig,
+    #[serde(default)]
+    pub backups: BackupsConfig,
 }
 
 async fn get_instance_config(
     instance_local_config_path: &Path,
     java_component: &JavaComponent,
+    progress: &Progress,
 ) -> Result<config::Config, BackendError> {
     let instance_local_config_name = instance_local_config_path
         .to_str()
@@ -119,7 +247,7 @@ async fn get_instance_config(
     let mut config_builder = config::Config::builder()
         .set_default(
             "java.path",
-            default_java_path(java_component)
+            default_java_path(java_component, progress)
                 .await?
                 .to_str()
                 .expect("java path isn't valid UTF-8"),
@@ -135,9 +263,9 @@ async fn get_instance_config(
             config_builder.add_source(config::File::with_name(instance_local_config_name));
     }
 
-    let config = config_builder.build().expect("failed to get config");
-
-    Ok(config)
+    config_builder
+        .build()
+        .map_err(|err| InstanceError::InvalidConfig(err.to_string()).into())
 }
 
 /// Reads the instance configuration from the given directory.
@@ -148,12 +276,36 @@ async fn get_instance_config(
 pub(crate) async fn read_instance_config(
     instance_directory: &Path,
     java_version: &JavaComponent,
+    progress: &Progress,
 ) -> Result<InstanceConfig, BackendError> {
     let instance_local_config_path = instance_directory.join(CONFIG_FILE_NAME);
-    get_instance_config(&instance_local_config_path, java_version)
-        .await
-        .map(|con| {
-            con.try_deserialize::<InstanceConfig>()
-                .expect("failed to deserialize config")
-        })
+    // a typo in config.toml, like an unknown preset, is reported rather than taking the backend down
+    get_instance_config(&instance_local_config_path, java_version, progress)
+        .await?
+        .try_deserialize::<InstanceConfig>()
+        .map_err(|err| InstanceError::InvalidConfig(err.to_string()).into())
+}
+
+/// Reads only the backups section of the instance configuration, which unlike the rest doesn't need the game's Java version
+pub(crate) fn read_backups_config(instance_directory: &Path) -> BackupsConfig {
+    let launcher_config_name = launcher_config_name();
+    let instance_local_config_path = instance_directory.join(CONFIG_FILE_NAME);
+
+    let mut config_builder = config::Config::builder();
+    if std::fs::exists(&launcher_config_name).is_ok_and(|r| r) {
+        config_builder = config_builder.add_source(config::File::with_name(&launcher_config_name));
+    }
+    if instance_local_config_path.exists() {
+        config_builder =
+            config_builder.add_source(config::File::from(instance_local_config_path.as_path()));
+    }
+
+    match config_builder.build().map(|config| config.get::<BackupsConfig>("backups")) {
+        Ok(Ok(backups)) => backups,
+        Ok(Err(config::ConfigError::NotFound(_))) => BackupsConfig::default(),
+        Ok(Err(err)) | Err(err) => {
+            wlog!("Invalid backups config, using the defaults: {err}");
+            BackupsConfig::default()
+        }
+    }
 }
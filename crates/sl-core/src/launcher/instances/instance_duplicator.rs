@@ -0,0 +1,114 @@
+use std::{fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+use sl_utils::{
+    errors::{BackendError, InstanceError},
+    log,
+};
+
+use crate::launcher::instances::{self, instance_metadata::InstanceMetadata, INSTANCE_FILE_NAME};
+
+/// Directories whose files are shared with the duplicate through copy on write, on filesystems supporting it
+const SHARED_DIRS: &[&str] = &["mods", "resourcepacks", "shaderpacks"];
+
+/// What is carried over to a duplicated instance, everything by default
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DuplicateOptions {
+    /// The `saves` directory
+    pub worlds: bool,
+    /// The `screenshots` directory
+    pub screenshots: bool,
+    /// The `logs` and `crash-reports` directories
+    pub logs: bool,
+}
+
+impl Default for DuplicateOptions {
+    fn default() -> Self {
+        Self {
+            worlds: true,
+            screenshots: true,
+            logs: true,
+        }
+    }
+}
+
+impl DuplicateOptions {
+    fn excludes(&self, file_name: &str) -> bool {
+        match file_name {
+            "saves" => !self.worlds,
+            "screenshots" => !self.screenshots,
+            "logs" | "crash-reports" => !self.logs,
+            // the metadata is written separately, along with its backups and the PID file of a running game
+            _ => file_name.starts_with(INSTANCE_FILE_NAME) || file_name.starts_with('.'),
+        }
+    }
+}
+
+fn duplicate_dir(
+    source_dir: &Path,
+    duplicate_dir: &Path,
+    options: DuplicateOptions,
+) -> io::Result<()> {
+    fs::create_dir_all(duplicate_dir)?;
+
+    for entry in fs::read_dir(source_dir)? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        if options.excludes(&file_name.to_string_lossy()) {
+            continue;
+        }
+
+        let source_path = entry.path();
+        let duplicate_path = duplicate_dir.join(&file_name);
+
+        if !entry.file_type()?.is_dir() {
+            fs::copy(source_path, duplicate_path)?;
+        } else if SHARED_DIRS.iter().any(|dir| file_name == *dir) {
+            sl_utils::fs::link_dir_all(source_path, duplicate_path)?;
+        } else {
+            sl_utils::fs::copy_dir_all(source_path, duplicate_path)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Creates a new instance named `new_name` from the instance `name_or_id`
+///
+/// mods, resource packs and shader packs are reflinked when the filesystem allows it,
+/// everything else (worlds, configs...) is copied so the two instances can't affect each other
+pub fn duplicate_instance(
+    name_or_id: &str,
+    new_name: &str,
+    options: DuplicateOptions,
+) -> Result<InstanceMetadata, BackendError> {
+    let (source, _) = instances::get_existing(name_or_id)?;
+
+    if instances::find_by_name(new_name)?.is_some() {
+        return Err(BackendError::InstanceError(
+            InstanceError::InstanceAlreadyExists(new_name.to_string()),
+        ));
+    }
+
+    let mut duplicate = source.clone();
+    duplicate.id = instances::generate_id();
+    duplicate.name = new_name.to_string();
+    duplicate.last_played = None;
+    duplicate.playtime_secs = 0;
+    duplicate.launch_count = 0;
+
+    log!("Duplicating instance '{}' as '{new_name}'", source.name);
+
+    let duplicate_path = duplicate.instance_dir();
+    let result = duplicate_dir(&source.instance_dir(), &duplicate_path, options)
+        .map_err(BackendError::from)
+        .and_then(|()| instances::add_new(&duplicate));
+
+    if let Err(err) = result {
+        _ = fs::remove_dir_all(&duplicate_path);
+        return Err(err);
+    }
+
+    Ok(duplicate)
+}
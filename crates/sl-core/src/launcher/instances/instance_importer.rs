@@ -1,69 +1,141 @@
-use std::{fs::{File, OpenOptions}, io::{BufReader, BufWriter, Read, Seek, SeekFrom}, path::Path};
+use std::{fs::File, io::{BufReader, Read, Seek}, path::{Path, PathBuf}};
 
-use sl_utils::{errors::InstanceImportErr, zip::ZipExtractor};
+use serde_json::Value;
+use sl_utils::{errors::{InstanceImportErr, SchemaError}, zip::ZipExtractor};
 use tempfile::TempDir;
 
-use crate::{launcher::instances::{instance_metadata::InstanceMetadata, INSTANCE_FILE_NAME}, INSTANCES_DIR};
+use crate::launcher::{instances::{self, importers::{atlauncher, multimc, vanilla}, instance_metadata::InstanceMetadata, INSTANCE_FILE_NAME}, migrations::{load_versioned, save_versioned}};
 
-
-/// Imports an Instance exported in a Zip format from a file at `file_path`
-pub fn import_instance_from_path(file_path: &Path) -> Result<(), InstanceImportErr> {
-    import_instance(BufReader::new(File::open(file_path)?))
+/// The launchers instances can be imported from
+enum InstanceFormat {
+    /// SynthLauncher's own, as exported by [`InstanceMetadata::exporter`]
+    Native,
+    MultiMC,
+    Vanilla,
+    ATLauncher,
 }
 
-/// Imports an Instance exported in a Zip format, from a reader
-pub fn import_instance<R: Read + Seek>(reader: R) -> Result<(), InstanceImportErr> {
-    // ==================================
-    // Extract & Cache the data to Import
-    let cache_dir = TempDir::new()?;
-    let cache_dir_path = cache_dir.path();
-    assert!(cache_dir_path.exists());
+/// Tells which launcher the instance at `root` comes from, if any
+fn detect_format(root: &Path) -> Result<Option<InstanceFormat>, InstanceImportErr> {
+    if root.join(multimc::CONFIG_NAME).exists() {
+        return Ok(Some(InstanceFormat::MultiMC));
+    }
+    if root.join(vanilla::PROFILES_NAME).exists() {
+        return Ok(Some(InstanceFormat::Vanilla));
+    }
 
-    let extractor = ZipExtractor::new(reader);
-    extractor.extract(cache_dir_path)?;
-    let cached_instance_file_path = cache_dir_path.join(INSTANCE_FILE_NAME);
+    let instance_file_path = root.join(INSTANCE_FILE_NAME);
+    if !instance_file_path.exists() {
+        return Ok(None);
+    }
 
-    if !cached_instance_file_path.exists() {
-        return Err(InstanceImportErr::NotAnInstance);
+    // ATLauncher names its instance file the same as ours
+    let document: Value = serde_json::from_str(&std::fs::read_to_string(instance_file_path)?)
+        .map_err(|_| InstanceImportErr::Corrupted)?;
+    match atlauncher::is_atlauncher_instance(&document) {
+        true => Ok(Some(InstanceFormat::ATLauncher)),
+        false => Ok(Some(InstanceFormat::Native)),
     }
+}
 
-    let mut cached_instance_file = OpenOptions::new()
-        .read(true)
-        .write(true)
-        .create(false)
-        .open(cached_instance_file_path)?;
+/// Finds the instance in `dir`, archives often wrap it in a directory named after it
+fn find_instance_root(dir: &Path) -> Result<(PathBuf, InstanceFormat), InstanceImportErr> {
+    if let Some(format) = detect_format(dir)? {
+        return Ok((dir.to_path_buf(), format));
+    }
 
-    let cached_instance_file_reader = BufReader::new(&mut cached_instance_file);
-    // =======================
-    // validate and modify the instance metadata until we get a valid metadata
-    let mut instance_metadata: InstanceMetadata =
-        serde_json::from_reader(cached_instance_file_reader)
-            .map_err(|_| InstanceImportErr::Corrupted)?;
+    let entries = std::fs::read_dir(dir)?.collect::<Result<Vec<_>, _>>()?;
+    if let [entry] = entries.as_slice() {
+        if entry.file_type()?.is_dir() {
+            if let Some(format) = detect_format(&entry.path())? {
+                return Ok((entry.path(), format));
+            }
+        }
+    }
 
-    let orig_name = &instance_metadata.name;
+    Err(InstanceImportErr::NotAnInstance)
+}
 
-    let mut name = instance_metadata.name.clone();
-    let mut instance_import_path = INSTANCES_DIR.join(&name);
+/// `name` if no instance has it, otherwise the first free `name (n)`
+fn unique_name(name: &str) -> Result<String, InstanceImportErr> {
+    let mut unique = name.to_string();
     let mut n = 1;
 
-    while std::fs::exists(&instance_import_path).is_ok_and(|e| e) {
-        name = format!("{orig_name} ({n})");
-        instance_import_path = INSTANCES_DIR.join(&name);
+    while instances::find_by_name(&unique)?.is_some() {
+        unique = format!("{name} ({n})");
         n += 1;
     }
+    Ok(unique)
+}
 
-    instance_metadata.name = name;
-    // ======================
-    // write the new metadata
-
-    cached_instance_file.set_len(0)?;
-    cached_instance_file.seek(SeekFrom::Start(0))?;
+/// `name` if it was given and no instance has it, otherwise the first free name after `default`, see [`unique_name`]
+pub(super) fn instance_name(name: Option<String>, default: &str) -> Result<String, InstanceImportErr> {
+    match name {
+        Some(name) if instances::find_by_name(&name)?.is_some() => Err(InstanceImportErr::NameTaken(name)),
+        Some(name) => Ok(name),
+        None => unique_name(default),
+    }
+}
 
-    let cached_instance_file_writer = BufWriter::new(cached_instance_file);
-    serde_json::to_writer_pretty(cached_instance_file_writer, &instance_metadata)?;
+/// Imports the SynthLauncher instance at `root`, named `name` or as it was exported
+fn import_native(root: &Path, name: Option<String>) -> Result<InstanceMetadata, InstanceImportErr> {
+    // =======================
+    // validate and modify the instance metadata until we get a valid metadata
+    // instances exported by older versions are migrated here
+    let mut instance_metadata: InstanceMetadata = load_versioned(&root.join(INSTANCE_FILE_NAME))
+        .map_err(|err| match err {
+            SchemaError::Corrupted { .. } => InstanceImportErr::Corrupted,
+            err => InstanceImportErr::Schema(err),
+        })?;
+
+    instance_metadata.name = instance_name(name, &instance_metadata.name)?;
+    // the exported ID may belong to an instance that is already there
+    instance_metadata.id = instances::generate_id();
+    // the launch bookkeeping belongs to whoever exported the instance
+    instance_metadata.last_played = None;
+    instance_metadata.playtime_secs = 0;
+    instance_metadata.launch_count = 0;
+    let instance_import_path = instance_metadata.instance_dir();
 
     // =======================
-    // Copy the cached data to the destination
-    sl_utils::fs::copy_dir_all(cache_dir_path, instance_import_path)?;
-    Ok(())
+    // Copy the data to the destination and write the new metadata over the imported one
+    sl_utils::fs::copy_dir_all(root, &instance_import_path)?;
+    save_versioned(&instance_import_path.join(INSTANCE_FILE_NAME), &instance_metadata)?;
+    Ok(instance_metadata)
+}
+
+/// Imports the instance at `root`, converting it if it comes from another launcher
+fn import_dir(root: &Path, name: Option<String>) -> Result<InstanceMetadata, InstanceImportErr> {
+    let (root, format) = find_instance_root(root)?;
+    match format {
+        InstanceFormat::Native => import_native(&root, name),
+        InstanceFormat::MultiMC => multimc::read(&root)?.import(name),
+        InstanceFormat::Vanilla => vanilla::read(&root)?.import(name),
+        InstanceFormat::ATLauncher => atlauncher::read(&root)?.import(name),
+    }
+}
+
+/// Imports an instance from `path`, either a directory or a Zip file, as `name` or under the name it had
+///
+/// besides SynthLauncher's own exports, MultiMC and Prism Launcher instances, the official launcher's `.minecraft`
+/// and ATLauncher instances are converted
+pub fn import_instance_from_path(path: &Path, name: Option<String>) -> Result<InstanceMetadata, InstanceImportErr> {
+    match path.is_dir() {
+        true => import_dir(path, name),
+        false => import_instance(BufReader::new(File::open(path)?), name),
+    }
+}
+
+/// Imports an instance exported in a Zip format, from a reader
+pub fn import_instance<R: Read + Seek>(reader: R, name: Option<String>) -> Result<InstanceMetadata, InstanceImportErr> {
+    // ==================================
+    // Extract & Cache the data to Import
+    let cache_dir = TempDir::new()?;
+    let cache_dir_path = cache_dir.path();
+    assert!(cache_dir_path.exists());
+
+    let extractor = ZipExtractor::new(reader);
+    extractor.extract(cache_dir_path)?;
+
+    import_dir(cache_dir_path, name)
 }
@@ -4,7 +4,9 @@ use std::{
     path::{Path, PathBuf},
 };
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 use sl_meta::minecraft::{
     loaders::{
         forge, neoforge,
@@ -12,12 +14,16 @@ use sl_meta::minecraft::{
     },
     version_manifest::VersionType,
 };
-use sl_utils::errors::{BackendError, HttpError, InstanceError};
+use sl_utils::{
+    errors::{BackendError, HttpError, InstanceError},
+    progress::Progress,
+};
 use strum_macros::{AsRefStr, Display, EnumString};
 
 use crate::{
     launcher::{
         instances::{self, instance_exporter::InstanceExporter, loaded_instance::LoadedInstance},
+        migrations::{Migration, Versioned},
         minecraft_version::MinecraftVersionID,
     },
     INSTANCES_DIR, REQUESTER, VERSION_MANIFEST,
@@ -124,6 +130,8 @@ impl ModLoader {
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct InstanceMetadata {
     pub scheme_version: u32,
+    /// Names the instance's directory, generated once and never changed unlike `name`
+    pub id: String,
     pub name: String,
     pub mc_version: String,
     pub mc_release_time: String,
@@ -131,11 +139,44 @@ pub struct InstanceMetadata {
     pub mod_loader_version: String,
     pub mod_loader: ModLoader,
     pub icon: Option<String>,
+    /// The group (folder) the instance is shown in, `None` for ungrouped instances
+    pub group: Option<String>,
+    pub tags: Vec<String>,
+    pub favorite: bool,
+    /// When the game of this instance last exited
+    pub last_played: Option<DateTime<Utc>>,
+    /// The total time spent in game in seconds
+    pub playtime_secs: u64,
+    pub launch_count: u32,
+}
+
+impl Versioned for InstanceMetadata {
+    const MIGRATIONS: &'static [Migration] = &[add_id, add_organization];
+}
+
+/// Instances used to be identified by their name, which their directory is named after
+fn add_id(document: &mut Value) {
+    if let Some(document) = document.as_object_mut() {
+        let name = document.get("name").cloned().unwrap_or_default();
+        document.insert("id".to_string(), name);
+    }
+}
+
+/// Adds the groups, tags and favorites along with the launch bookkeeping, all empty
+fn add_organization(document: &mut Value) {
+    if let Some(document) = document.as_object_mut() {
+        document.insert("group".to_string(), Value::Null);
+        document.insert("tags".to_string(), json!([]));
+        document.insert("favorite".to_string(), json!(false));
+        document.insert("last_played".to_string(), Value::Null);
+        document.insert("playtime_secs".to_string(), json!(0));
+        document.insert("launch_count".to_string(), json!(0));
+    }
 }
 
 impl InstanceMetadata {
-    /// Constructs a new instance metadata, without doing any version checks.
-    pub(super) const fn new_unchecked(
+    /// Constructs a new instance metadata with a freshly generated ID, without doing any version checks.
+    pub(super) fn new_unchecked(
         name: String,
         icon: Option<String>,
         mc_version: String,
@@ -145,7 +186,8 @@ impl InstanceMetadata {
         mod_loader_version: String,
     ) -> Self {
         Self {
-            scheme_version: 0,
+            scheme_version: Self::SCHEME_VERSION,
+            id: instances::generate_id(),
             name,
             icon,
             mc_version,
@@ -153,6 +195,12 @@ impl InstanceMetadata {
             mc_type,
             mod_loader_version,
             mod_loader,
+            group: None,
+            tags: Vec::new(),
+            favorite: false,
+            last_played: None,
+            playtime_secs: 0,
+            launch_count: 0,
         }
     }
 
@@ -170,9 +218,6 @@ impl InstanceMetadata {
                     InstanceError::MinecraftVersionNotFound(mc_version.to_string()),
                 ))?;
 
-        // TODO: remove this line
-        std::fs::create_dir_all(INSTANCES_DIR.join(&name))?;
-
         let mod_loader_version = match mod_loader_version {
             Some(specific) => specific,
             None => mod_loader.get_latest_version(mc_version).await?,
@@ -203,12 +248,15 @@ impl InstanceMetadata {
         Ok(instance)
     }
 
-    fn instance_dir(&self) -> PathBuf {
-        INSTANCES_DIR.join(&self.name)
+    /// The directory of the instance, named after its ID
+    pub fn instance_dir(&self) -> PathBuf {
+        INSTANCES_DIR.join(&self.id)
     }
 
     /// Loads ('Upgrades' information to) an instance's in memory representation
-    pub async fn load_init(self) -> Result<LoadedInstance, BackendError> {
+    ///
+    /// the progress of every download performed by this and by the returned instance is reported to `progress`
+    pub async fn load_init(self, progress: Progress) -> Result<LoadedInstance, BackendError> {
         let instance_dir = self.instance_dir();
 
         let version_id = MinecraftVersionID::new(
@@ -217,13 +265,14 @@ impl InstanceMetadata {
             self.mc_version.clone(),
         );
 
-        let (loaded_version, config) = version_id.load_init(&instance_dir).await?;
+        let (loaded_version, config) = version_id.load_init(&instance_dir, &progress).await?;
 
         Ok(LoadedInstance::new(
             self,
             instance_dir,
             loaded_version,
             config,
+            progress,
         ))
     }
 
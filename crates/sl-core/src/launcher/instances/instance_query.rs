@@ -0,0 +1,128 @@
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+use sl_utils::errors::BackendError;
+use strum_macros::{Display, EnumString};
+
+use crate::launcher::instances::{
+    self,
+    instance_metadata::{InstanceMetadata, ModLoader},
+};
+
+/// The field instances are sorted by
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, EnumString, Display,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum SortBy {
+    #[default]
+    Name,
+    /// Most recently played first, instances that were never played come last
+    LastPlayed,
+    /// Most played first
+    Playtime,
+    /// Most launched first
+    LaunchCount,
+    /// Newest Minecraft release first
+    McVersion,
+}
+
+/// Filters and sorts the instances, an empty (default) query returns every instance sorted by name
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InstanceQuery {
+    /// Only keeps the instances whose name contains this, case insensitive
+    pub search: Option<String>,
+    /// Only keeps the instances of this group
+    pub group: Option<String>,
+    /// Only keeps the instances having all of these tags
+    pub tags: Vec<String>,
+    pub favorites_only: bool,
+    pub mod_loader: Option<ModLoader>,
+    pub sort_by: SortBy,
+    /// Reverses the order given by `sort_by`
+    pub reverse: bool,
+    /// Puts the favorites before the other instances whatever the order is
+    pub favorites_first: bool,
+}
+
+impl InstanceQuery {
+    /// Whether `instance` passes every filter of the query
+    pub fn matches(&self, instance: &InstanceMetadata) -> bool {
+        let search = self.search.as_ref().is_none_or(|search| {
+            instance
+                .name
+                .to_lowercase()
+                .contains(&search.to_lowercase())
+        });
+
+        search
+            && self
+                .group
+                .as_ref()
+                .is_none_or(|group| instance.group.as_ref() == Some(group))
+            && self.tags.iter().all(|tag| instance.tags.contains(tag))
+            && (!self.favorites_only || instance.favorite)
+            && self
+                .mod_loader
+                .is_none_or(|mod_loader| instance.mod_loader == mod_loader)
+    }
+
+    fn compare(&self, a: &InstanceMetadata, b: &InstanceMetadata) -> Ordering {
+        let ordering = match self.sort_by {
+            SortBy::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            // `None` is smaller than any `Some`, so the instances that were never played come last
+            SortBy::LastPlayed => b.last_played.cmp(&a.last_played),
+            SortBy::Playtime => b.playtime_secs.cmp(&a.playtime_secs),
+            SortBy::LaunchCount => b.launch_count.cmp(&a.launch_count),
+            // release times are RFC 3339, which sort the same as strings
+            SortBy::McVersion => b.mc_release_time.cmp(&a.mc_release_time),
+        };
+        let ordering = match self.reverse {
+            true => ordering.reverse(),
+            false => ordering,
+        };
+
+        let favorites = match self.favorites_first {
+            true => b.favorite.cmp(&a.favorite),
+            false => Ordering::Equal,
+        };
+        // ties are broken by name so the order is stable between calls
+        favorites.then(ordering).then_with(|| a.name.cmp(&b.name))
+    }
+
+    /// Filters and sorts `instances`
+    pub fn apply(&self, mut instances: Vec<InstanceMetadata>) -> Vec<InstanceMetadata> {
+        instances.retain(|instance| self.matches(instance));
+        instances.sort_by(|a, b| self.compare(a, b));
+        instances
+    }
+}
+
+/// Gets the instances matching `query`, in the order it asks for
+pub fn query_instances(query: &InstanceQuery) -> Result<Vec<InstanceMetadata>, BackendError> {
+    Ok(query.apply(instances::get_all_instances()?))
+}
+
+/// Every group used by at least one instance, sorted
+pub fn groups() -> Result<Vec<String>, BackendError> {
+    let mut groups = instances::get_all_instances()?
+        .into_iter()
+        .filter_map(|instance| instance.group)
+        .collect::<Vec<_>>();
+    groups.sort();
+    groups.dedup();
+    Ok(groups)
+}
+
+/// Every tag used by at least one instance, sorted
+pub fn tags() -> Result<Vec<String>, BackendError> {
+    let mut tags = instances::get_all_instances()?
+        .into_iter()
+        .flat_map(|instance| instance.tags)
+        .collect::<Vec<_>>();
+    tags.sort();
+    tags.dedup();
+    Ok(tags)
+}
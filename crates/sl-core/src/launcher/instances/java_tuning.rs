@@ -0,0 +1,183 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use sl_utils::wlog;
+
+/// A named set of JVM flags tuned for running the game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JvmPreset {
+    /// Leaves everything to the JVM's defaults
+    #[default]
+    Default,
+    /// Aikar's G1 flags, widely used for heavily modded instances
+    AikarG1,
+    /// Generational ZGC for very low pauses, requires Java 21 or newer
+    ZgcGenerational,
+    /// Keeps the memory footprint small at some cost of performance
+    LowMemory,
+}
+
+/// The flags of [`JvmPreset::AikarG1`] that don't depend on the heap size
+const AIKAR_G1_FLAGS: &[&str] = &[
+    "-XX:+UseG1GC",
+    "-XX:+ParallelRefProcEnabled",
+    "-XX:MaxGCPauseMillis=200",
+    "-XX:+UnlockExperimentalVMOptions",
+    "-XX:+DisableExplicitGC",
+    "-XX:+AlwaysPreTouch",
+    "-XX:G1HeapWastePercent=5",
+    "-XX:G1MixedGCCountTarget=4",
+    "-XX:G1MixedGCLiveThresholdPercent=90",
+    "-XX:G1RSetUpdatingPauseTimePercent=5",
+    "-XX:SurvivorRatio=32",
+    "-XX:+PerfDisableSharedMem",
+    "-XX:MaxTenuringThreshold=1",
+];
+
+impl JvmPreset {
+    /// The flags of this preset for the given Java version and maximum heap size in MiB
+    pub fn flags(&self, java_major: u16, max_ram: usize) -> Vec<String> {
+        let flags: &[&str] = match self {
+            JvmPreset::Default => &[],
+            JvmPreset::AikarG1 => {
+                // bigger heaps get a bigger young generation and bigger regions
+                let sized: &[&str] = if max_ram > 12 * 1024 {
+                    &[
+                        "-XX:G1NewSizePercent=40",
+                        "-XX:G1MaxNewSizePercent=50",
+                        "-XX:G1HeapRegionSize=16M",
+                        "-XX:G1ReservePercent=15",
+                        "-XX:InitiatingHeapOccupancyPercent=20",
+                    ]
+                } else {
+                    &[
+                        "-XX:G1NewSizePercent=30",
+                        "-XX:G1MaxNewSizePercent=40",
+                        "-XX:G1HeapRegionSize=8M",
+                        "-XX:G1ReservePercent=20",
+                        "-XX:InitiatingHeapOccupancyPercent=15",
+                    ]
+                };
+                return AIKAR_G1_FLAGS
+                    .iter()
+                    .chain(sized)
+                    .map(|flag| flag.to_string())
+                    .collect();
+            }
+            JvmPreset::ZgcGenerational if java_major < 21 => {
+                wlog!("Generational ZGC requires Java 21 or newer, found Java {java_major}, using the JVM's defaults");
+                &[]
+            }
+            // generational mode is the only mode since Java 23, where the flag got deprecated
+            JvmPreset::ZgcGenerational if java_major >= 23 => &["-XX:+UseZGC"],
+            JvmPreset::ZgcGenerational => &["-XX:+UseZGC", "-XX:+ZGenerational"],
+            JvmPreset::LowMemory => &[
+                "-XX:+UseSerialGC",
+                "-XX:MinHeapFreeRatio=10",
+                "-XX:MaxHeapFreeRatio=30",
+                "-XX:ReservedCodeCacheSize=64M",
+            ],
+        };
+
+        flags.iter().map(|flag| flag.to_string()).collect()
+    }
+}
+
+/// How the heap of the game is sized
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryMode {
+    /// Uses `min_ram` and `max_ram` as they are
+    #[default]
+    Fixed,
+    /// Sizes the heap from the system's memory, the amount of mods and the Java version
+    Auto,
+}
+
+/// The physical memory of the system in MiB, `None` if it couldn't be read
+pub fn total_memory_mb() -> Option<usize> {
+    #[cfg(target_os = "linux")]
+    {
+        let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+        let kib = meminfo
+            .lines()
+            .find_map(|line| line.strip_prefix("MemTotal:"))?
+            .trim()
+            .trim_end_matches("kB")
+            .trim()
+            .parse::<usize>()
+            .ok()?;
+        Some(kib / 1024)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let mut system = sysinfo::System::new();
+        system.refresh_memory();
+        match system.total_memory() {
+            0 => None,
+            bytes => Some((bytes / 1024 / 1024) as usize),
+        }
+    }
+}
+
+/// The amount of enabled mods in the instance's `mods` directory
+pub fn mod_count(instance_dir: &Path) -> usize {
+    let Ok(entries) = std::fs::read_dir(instance_dir.join("mods")) else {
+        return 0;
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry
+                .path()
+                .extension()
+                .is_some_and(|ext| ext == "jar" || ext == "zip")
+        })
+        .count()
+}
+
+const fn round_to_512(mb: usize) -> usize {
+    mb.div_ceil(512) * 512
+}
+
+/// Picks the minimum and maximum heap sizes in MiB for an instance with `mods` mods
+///
+/// never takes more than half of `total_memory`, the system and the game's off-heap memory need the rest
+pub fn auto_heap_size(
+    total_memory: Option<usize>,
+    mods: usize,
+    java_major: u16,
+    preset: JvmPreset,
+) -> (usize, usize) {
+    let mut max_ram = match mods {
+        0 => 2048,
+        // big modpacks settle somewhere around 16 MiB per mod on top of the game itself
+        mods => 3072 + mods * 16,
+    };
+
+    // the collectors of Java 8 pause for too long with big heaps
+    if java_major < 11 {
+        max_ram = max_ram.min(6 * 1024);
+    }
+
+    if preset == JvmPreset::LowMemory {
+        max_ram /= 2;
+    }
+
+    match total_memory {
+        Some(total_memory) => max_ram = max_ram.min(total_memory / 2),
+        None => wlog!("Couldn't read the system's memory, sizing the heap from the mods only"),
+    }
+
+    let max_ram = round_to_512(max_ram.clamp(1024, 16 * 1024));
+    // Aikar's flags pre-touch the whole heap, so it should be allocated up front
+    let min_ram = match preset {
+        JvmPreset::AikarG1 => max_ram,
+        _ => round_to_512(max_ram / 2),
+    };
+
+    (min_ram, max_ram)
+}
@@ -0,0 +1,76 @@
+use std::{
+    collections::HashMap,
+    io,
+    path::Path,
+    process::{Command, ExitStatus},
+};
+
+use sl_utils::{errors::InstanceError, log};
+
+/// A shell command configured to run around the game
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Hook {
+    /// Runs before the game is launched, the launch is aborted if it fails
+    PreLaunch,
+    /// Runs after the game exited
+    PostExit,
+}
+
+impl Hook {
+    const fn name(&self) -> &'static str {
+        match self {
+            Hook::PreLaunch => "pre_launch",
+            Hook::PostExit => "post_exit",
+        }
+    }
+}
+
+/// Builds the command running `command` through the platform's shell in the instance's directory
+fn shell_command(command: &str, instance_dir: &Path, env: &HashMap<String, String>) -> Command {
+    #[cfg(target_os = "windows")]
+    let mut shell = {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    };
+    #[cfg(not(target_os = "windows"))]
+    let mut shell = {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+
+    shell.arg(command).current_dir(instance_dir).envs(env);
+    shell
+}
+
+fn check_status(hook: Hook, status: io::Result<ExitStatus>) -> Result<(), InstanceError> {
+    match status {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(InstanceError::HookFailed(hook.name(), status.to_string())),
+        Err(err) => Err(InstanceError::HookFailed(hook.name(), err.to_string())),
+    }
+}
+
+/// Runs the hook's `command` and waits for it to finish, errors if it didn't exit successfully
+pub(crate) async fn run_hook(
+    hook: Hook,
+    command: &str,
+    instance_dir: &Path,
+    env: &HashMap<String, String>,
+) -> Result<(), InstanceError> {
+    log!("Running {} hook: {command}", hook.name());
+    let mut shell = tokio::process::Command::from(shell_command(command, instance_dir, env));
+    check_status(hook, shell.status().await)
+}
+
+/// Same as [`run_hook`] but blocks the current thread
+pub(crate) fn run_hook_blocking(
+    hook: Hook,
+    command: &str,
+    instance_dir: &Path,
+    env: &HashMap<String, String>,
+) -> Result<(), InstanceError> {
+    log!("Running {} hook: {command}", hook.name());
+    check_status(hook, shell_command(command, instance_dir, env).status())
+}
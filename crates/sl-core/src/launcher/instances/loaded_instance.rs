@@ -1,30 +1,62 @@
 use crate::{
     launcher::{
-        instances::{instance_config::InstanceConfig, instance_metadata::InstanceMetadata},
+        backups::{self, BackupTrigger},
+        game_process::{GameHandle, GameState},
+        instances::{
+            self,
+            instance_config::InstanceConfig,
+            instance_metadata::InstanceMetadata,
+            java_tuning::{self, MemoryMode},
+            launch_hooks::{self, Hook},
+            run_logs::RunLog,
+        },
         minecraft_version::LoadedMinecraftVersion,
         player_accounts::PlayerAccounts,
     },
-    minecraft::install_client,
-    ASSETS_DIR, LIBS_DIR,
+    minecraft::{install_client, Verification, VerificationReport},
+    ASSETS_DIR, GAME_PROCESSES, LIBS_DIR,
 };
 use sl_java_manager::MULTI_PATH_SEPARATOR;
-use sl_meta::{minecraft::loaders::vanilla::Client, minecraft::version_manifest::VersionType};
+use sl_meta::minecraft::{
+    loaders::vanilla::{Client, LaunchFeatures},
+    version_manifest::VersionType,
+};
 use sl_player::PlayerData;
-use sl_utils::{dlog, errors::BackendError, log, wlog};
+use sl_utils::{
+    dlog,
+    errors::{BackendError, InstanceError},
+    log,
+    progress::Progress,
+    wlog,
+};
 
 use chrono::DateTime;
 use std::{
     borrow::Cow,
+    collections::HashMap,
     path::{Path, PathBuf},
 };
 use tokio::{io::AsyncRead, process::Command};
 
+/// A world, server or realm to join directly once the game starts
+#[derive(Debug, Clone)]
+pub enum QuickPlay {
+    /// The name of the world's folder in `saves`
+    Singleplayer(String),
+    /// The server's address, optionally followed by a port
+    Multiplayer(String),
+    /// The realm's ID
+    Realms(String),
+}
+
 // Represents a loaded instance of Minecraft with its configurations and things required for launching
 pub struct LoadedInstance {
     instance_metadata: InstanceMetadata,
     config: InstanceConfig,
     instance_path: PathBuf,
     loaded_version: LoadedMinecraftVersion,
+    progress: Progress,
+    quick_play: Option<QuickPlay>,
 }
 
 impl LoadedInstance {
@@ -33,15 +65,24 @@ impl LoadedInstance {
         instance_dir: PathBuf,
         loaded_version: LoadedMinecraftVersion,
         config: InstanceConfig,
+        progress: Progress,
     ) -> Self {
         Self {
             instance_metadata,
             config,
             instance_path: instance_dir,
             loaded_version,
+            progress,
+            quick_play: None,
         }
     }
 
+    /// Joins the given world, server or realm directly once the game starts, only supported by newer versions of the game
+    pub fn quick_play(mut self, quick_play: QuickPlay) -> Self {
+        self.quick_play = Some(quick_play);
+        self
+    }
+
     const fn mc_version(&self) -> &str {
         self.instance_metadata.mc_version.as_str()
     }
@@ -72,15 +113,39 @@ impl LoadedInstance {
     /// so i think splitting them makes the code more maintainable
     async fn download_minecraft(&self) -> Result<(), BackendError> {
         // will automatically perform hash verification and only re install corrupted files
-        install_client(
-            &self.loaded_version.client_json(),
-            &self.loaded_version.client_jar_path(),
+        let report = install_client(
+            self.loaded_version.client_json(),
+            self.loaded_version.client_jar_path(),
             &self.instance_path,
+            Verification::Default,
+            &self.progress,
         )
         .await?;
+
+        if !report.corrupted.is_empty() {
+            wlog!(
+                "Re-downloaded {} corrupted file(s) for instance '{}'",
+                report.corrupted.len(),
+                self.instance_metadata.name
+            );
+        }
         Ok(())
     }
 
+    /// Hashes every library, asset and client jar required by this instance, re-downloading only the missing or corrupted ones
+    pub async fn repair(&self) -> Result<VerificationReport, BackendError> {
+        log!("Repairing instance '{}'", self.instance_metadata.name);
+
+        install_client(
+            self.loaded_version.client_json(),
+            self.loaded_version.client_jar_path(),
+            &self.instance_path,
+            Verification::Full,
+            &self.progress,
+        )
+        .await
+    }
+
     /// Generates the classpath for executing minecraft for this instance
     fn generate_classpath(&self) -> String {
         let client = self.client_json();
@@ -136,6 +201,22 @@ impl LoadedInstance {
         }
     }
 
+    /// The launcher features the client's argument rules are evaluated against
+    fn launch_features(&self) -> LaunchFeatures {
+        let minecraft = &self.config.minecraft;
+        let quick_play = self.quick_play.as_ref();
+
+        LaunchFeatures {
+            is_demo_user: minecraft.demo,
+            has_custom_resolution: !minecraft.fullscreen
+                && (minecraft.window_width.is_some() || minecraft.window_height.is_some()),
+            has_quick_plays_support: quick_play.is_some(),
+            is_quick_play_singleplayer: matches!(quick_play, Some(QuickPlay::Singleplayer(_))),
+            is_quick_play_multiplayer: matches!(quick_play, Some(QuickPlay::Multiplayer(_))),
+            is_quick_play_realms: matches!(quick_play, Some(QuickPlay::Realms(_))),
+        }
+    }
+
     async fn generate_arguments(
         &self,
         player_username: &str,
@@ -149,7 +230,33 @@ impl LoadedInstance {
         let client = self.client_json();
 
         let raw_args = &client.arguments;
-        let (mut jvm_args, mut game_args) = raw_args.clone().into_raw();
+        let (mut jvm_args, mut game_args) = raw_args.clone().into_raw(&self.launch_features());
+
+        if self.config.minecraft.fullscreen {
+            game_args.push("--fullscreen".to_string());
+        }
+
+        // the game's default window size
+        let resolution_width = self
+            .config
+            .minecraft
+            .window_width
+            .unwrap_or(854)
+            .to_string();
+        let resolution_height = self
+            .config
+            .minecraft
+            .window_height
+            .unwrap_or(480)
+            .to_string();
+
+        let quick_play_path = game_dir.join("quickPlay").join("log.json");
+        let quick_play_target = match self.quick_play {
+            Some(QuickPlay::Singleplayer(ref target))
+            | Some(QuickPlay::Multiplayer(ref target))
+            | Some(QuickPlay::Realms(ref target)) => target.as_str(),
+            None => "",
+        };
 
         let regex = regex::Regex::new(r"\$\{(\w+)\}").expect("Failed to compile regex!");
 
@@ -172,6 +279,12 @@ impl LoadedInstance {
                 "classpath_separator" => MULTI_PATH_SEPARATOR,
                 "launcher_name" => "SynthLauncher",
                 "launcher_version" => "1.0",
+                "resolution_width" => &resolution_width,
+                "resolution_height" => &resolution_height,
+                "quickPlayPath" => quick_play_path.to_str()?,
+                "quickPlaySingleplayer" | "quickPlayMultiplayer" | "quickPlayRealms" => {
+                    quick_play_target
+                }
                 other => {
                     wlog!(
                         "Couldn't evaluate argument: {}, for launching minecraft",
@@ -199,6 +312,9 @@ impl LoadedInstance {
 
         fmt_args(&mut jvm_args);
 
+        jvm_args.extend(self.config.java.extra_args.iter().cloned());
+        game_args.extend(self.config.minecraft.extra_args.iter().cloned());
+
         // !!!DO NOT REMOVE!!!
         // jvm_args.push("-javaagent:/home/stierprogrammer/Desktop/synthlauncher/authlib-injector-1.2.5.jar=http://192.168.2.80:8000/".to_string());
         // jvm_args.push("-Dauthlibinjector.noShowServerName".to_string());
@@ -207,17 +323,71 @@ impl LoadedInstance {
         Ok([jvm_args, game_args].concat())
     }
 
-    fn last_log_path(&self) -> PathBuf {
-        self.instance_path.join("logs/latest-run.log")
+    const fn java_major(&self) -> u16 {
+        self.client_json().java_version.major_version
+    }
+
+    /// The minimum and maximum heap sizes in MiB, errors if the maximum is more than the system's physical memory
+    fn heap_size(&self) -> Result<(usize, usize), InstanceError> {
+        let java = &self.config.java;
+        let total_memory = java_tuning::total_memory_mb();
+
+        let (min_ram, max_ram) = match java.memory {
+            MemoryMode::Fixed => (java.min_ram, java.max_ram),
+            MemoryMode::Auto => java_tuning::auto_heap_size(
+                total_memory,
+                java_tuning::mod_count(&self.instance_path),
+                self.java_major(),
+                java.preset,
+            ),
+        };
+
+        if let Some(available) = total_memory.filter(|total| max_ram > *total) {
+            return Err(InstanceError::NotEnoughMemory {
+                requested: max_ram,
+                available,
+            });
+        }
+
+        if min_ram > max_ram {
+            wlog!("min_ram ({min_ram} MiB) is bigger than max_ram ({max_ram} MiB), using max_ram for both");
+            return Ok((max_ram, max_ram));
+        }
+        Ok((min_ram, max_ram))
+    }
+
+    /// The environment the launch hooks run in, the configured environment variables and details about the instance
+    fn hook_env(&self) -> HashMap<String, String> {
+        let mut env = self.config.launch.env.clone();
+        env.insert(
+            "SL_INSTANCE_NAME".to_string(),
+            self.instance_metadata.name.clone(),
+        );
+        env.insert(
+            "SL_INSTANCE_ID".to_string(),
+            self.instance_metadata.id.clone(),
+        );
+        env.insert(
+            "SL_INSTANCE_DIR".to_string(),
+            self.instance_path.to_string_lossy().into_owned(),
+        );
+        env.insert("SL_MC_VERSION".to_string(), self.mc_version().to_string());
+        env
     }
 
-    #[must_use = "must wait on child to exit"]
-    /// Performs the execution of the instance.
+    #[must_use = "must wait on the game to exit"]
+    /// Performs the execution of the instance, the game is then owned by [`GAME_PROCESSES`] until it exits.
     ///
     /// # Returns
-    /// - Ok((child, reader)) reader is a pipe reader that can be used to read the output of the instance (stderr and stdout)
-    /// - Err(BackendError) if the instance could not be executed
-    pub async fn execute(self) -> Result<(tokio::process::Child, impl AsyncRead), BackendError> {
+    /// - Ok((game, reader)) reader is a pipe reader that can be used to read the output of the instance (stderr and stdout)
+    /// - Err(BackendError) if the instance could not be executed, or is already running
+    pub async fn execute(self) -> Result<(GameHandle, impl AsyncRead), BackendError> {
+        // held until the game is tracked, a second launch of the instance can't start in the meantime
+        let reservation = GAME_PROCESSES
+            .reserve(&self.instance_metadata.id)
+            .map_err(|_| InstanceError::AlreadyRunning(self.instance_metadata.name.clone()))?;
+        let (min_ram, max_ram) = self.heap_size()?;
+
         // the reason why the download operation is done here is to ensure that the files are available before executing the instance.
         // AND THE REASON WHY YOU DON'T LEAVE CALLING THIS TO THE CALLER OF THE EXECUTE METHOD is because it is just better and cleaner,
         // you should aim to ensure that the caller will get a compile time error instead of causing a runtime bug and each exported function should be self-contained.
@@ -237,43 +407,112 @@ impl LoadedInstance {
 
         log!("Using Java path: {}", current_java_path.display());
 
-        let max_ram = self.config.java.max_ram;
-        let min_ram = self.config.java.min_ram;
-
         dlog!("min_ram: {}, max_ram: {}", min_ram, max_ram);
 
+        let preset_flags = self.config.java.preset.flags(self.java_major(), max_ram);
+
         let args = self.generate_arguments(&name, &data).await?;
 
         dlog!("Launching with args: {:?}", &args);
 
-        let log_path = self.last_log_path();
-        tokio::fs::create_dir_all(log_path.parent().unwrap()).await?;
+        let hook_env = self.hook_env();
+        if let Some(ref pre_launch) = self.config.launch.pre_launch {
+            launch_hooks::run_hook(Hook::PreLaunch, pre_launch, &self.instance_path, &hook_env)
+                .await?;
+        }
 
-        let log_file = std::fs::OpenOptions::new()
-            .create(true)
-            .write(true)
-            .truncate(true)
-            .open(&log_path)?;
+        let instance_metadata = self.instance_metadata.clone();
+        let backup = tokio::task::spawn_blocking(move || {
+            backups::auto_backup(&instance_metadata, BackupTrigger::BeforeLaunch)
+        });
+        if let Err(err) = backup.await? {
+            wlog!("Failed to back up the worlds before launching: {err}");
+        }
+
+        let (run_log, log_file) = RunLog::start(
+            &self.instance_path,
+            name,
+            current_java_path,
+            &args,
+            &data.access_token,
+            self.config.logs.max_runs,
+        )?;
+        let log_path = run_log.log_path();
 
         dlog!(
-            "Using Java: {}, logging to {}",
+            "Using Java: {}, logging run {} to {}",
             current_java_path.display(),
+            run_log.id(),
             log_path.display()
         );
 
-        let child = Command::new(current_java_path)
+        let mut command = match self.config.launch.wrapper.split_first() {
+            Some((wrapper, wrapper_args)) => {
+                dlog!("Wrapping java with: {:?}", self.config.launch.wrapper);
+                let mut command = Command::new(wrapper);
+                command.args(wrapper_args).arg(current_java_path);
+                command
+            }
+            None => Command::new(current_java_path),
+        };
+
+        let child = command
             .arg(format!("-Xmx{}M", max_ram))
             .arg(format!("-Xms{}M", min_ram))
+            .args(preset_flags)
             .args(args)
+            .envs(&self.config.launch.env)
             .stdout(log_file.try_clone()?)
-            .stderr(log_file.try_clone()?)
-            .current_dir(self.instance_path)
+            .stderr(log_file)
+            .current_dir(&self.instance_path)
             .spawn()?;
 
+        let post_exit = self.config.launch.post_exit.clone();
+        let instance_dir = self.instance_path.clone();
+        let instance_metadata = self.instance_metadata.clone();
+        let game = GAME_PROCESSES.track(
+            reservation,
+            &self.instance_path,
+            child,
+            move |state| {
+                let duration_secs = run_log.finish(state);
+                if let Err(err) = instances::record_play(&instance_metadata.id, duration_secs) {
+                    wlog!(
+                        "Failed to record the run of instance '{}': {err}",
+                        instance_metadata.name
+                    );
+                }
+                if let Err(err) = backups::auto_backup(&instance_metadata, BackupTrigger::AfterExit)
+                {
+                    wlog!("Failed to back up the worlds after exiting: {err}");
+                }
+
+                let Some(post_exit) = post_exit else {
+                    return;
+                };
+
+                let mut hook_env = hook_env;
+                if let GameState::Exited { code, .. } = state {
+                    let code = code.map(|code| code.to_string()).unwrap_or_default();
+                    hook_env.insert("SL_EXIT_CODE".to_string(), code);
+                }
+
+                let result = launch_hooks::run_hook_blocking(
+                    Hook::PostExit,
+                    &post_exit,
+                    &instance_dir,
+                    &hook_env,
+                );
+                if let Err(err) = result {
+                    wlog!("{err}");
+                }
+            },
+        );
+
         let log_file = tokio::fs::OpenOptions::new()
             .read(true)
             .open(log_path)
             .await?;
-        Ok((child, log_file))
+        Ok((game, log_file))
     }
 }
@@ -1,34 +1,52 @@
 use std::{
-    fs::{self, File, OpenOptions},
-    io::{self, BufReader},
-    path::PathBuf,
+    fs,
+    path::{Path, PathBuf},
 };
 
+use chrono::Utc;
+use serde::Serialize;
 use sl_utils::{
-    elog,
-    errors::{BackendError, InstanceError},
+    errors::{BackendError, InstanceError, SchemaError},
+    wlog,
 };
 
 use crate::{
-    launcher::instances::instance_metadata::InstanceMetadata, INSTANCES_DIR, VERSION_MANIFEST,
+    launcher::{
+        backups::{self, BackupTrigger},
+        instances::instance_metadata::InstanceMetadata,
+        migrations::{load_versioned, save_versioned},
+    },
+    INSTANCES_DIR, VERSION_MANIFEST,
 };
 
+pub mod content;
+pub mod content_lock;
+pub mod crash_analyzer;
 pub mod game;
+mod importers;
 pub mod instance_config;
+pub mod instance_duplicator;
 pub mod instance_exporter;
 pub mod instance_importer;
 pub mod instance_metadata;
+pub mod instance_query;
+pub mod java_tuning;
+mod launch_hooks;
 pub mod loaded_instance;
+pub mod mod_metadata;
+pub mod run_logs;
 pub mod instance_game;
 
-const INSTANCE_FILE_NAME: &str = "instance.json";
+pub const INSTANCE_FILE_NAME: &str = "instance.json";
 
-fn overwrite_instance(instance_name: &str, metadata: InstanceMetadata) -> std::io::Result<()> {
-    let instance_path = INSTANCES_DIR.join(instance_name);
-    let instance_file_path = instance_path.join(INSTANCE_FILE_NAME);
-    let instance_file = OpenOptions::new().write(true).open(&instance_file_path)?;
-    serde_json::to_writer_pretty(instance_file, &metadata)?;
-    Ok(())
+/// Generates the ID of a new instance
+pub(crate) fn generate_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+fn overwrite_instance(metadata: &InstanceMetadata) -> std::io::Result<()> {
+    let instance_file_path = metadata.instance_dir().join(INSTANCE_FILE_NAME);
+    save_versioned(&instance_file_path, metadata)
 }
 
 pub async fn edit_instance(
@@ -36,7 +54,7 @@ pub async fn edit_instance(
     new_mc_version: Option<&str>,
     new_modloader_version: Option<&str>,
 ) -> Result<(), BackendError> {
-    let (instance_metadata, _) = self::get_existing(instance_name)?;
+    let (mut instance_metadata, _) = self::get_existing(instance_name)?;
     let (mc_version, mc_release_time, mc_release_type) = match new_mc_version {
         Some(new_version) => {
             let version_info = VERSION_MANIFEST
@@ -68,75 +86,206 @@ pub async fn edit_instance(
         ));
     }
 
-    let new_metadata = InstanceMetadata::new_unchecked(
-        instance_metadata.name,
-        instance_metadata.icon,
-        mc_version.to_string(),
-        mc_release_type,
-        mc_release_time.to_string(),
-        mod_loader,
-        mod_loader_version.to_string(),
-    );
-
-    self::overwrite_instance(instance_name, new_metadata)?;
+    backups::auto_backup(&instance_metadata, BackupTrigger::BeforeUpdate)?;
+
+    instance_metadata.mc_version = mc_version.to_string();
+    instance_metadata.mc_release_time = mc_release_time.to_string();
+    instance_metadata.mc_type = mc_release_type;
+    instance_metadata.mod_loader_version = mod_loader_version.to_string();
+
+    self::overwrite_instance(&instance_metadata)?;
     Ok(())
 }
 
-/// Renames an instance with the name `instance_name` to `new_name`
+/// Renames an instance with the name `instance_name` to `new_name`, its directory stays the same
 pub fn rename_instance(instance_name: &str, new_name: &str) -> Result<(), BackendError> {
-    let (mut instance_metadata, old_instance_path) = self::get_existing(instance_name)?;
+    let (mut instance_metadata, _) = self::get_existing(instance_name)?;
 
-    let new_instance_path = INSTANCES_DIR.join(new_name);
-
-    std::fs::create_dir_all(&new_instance_path)?;
-    sl_utils::fs::copy_dir_all(&old_instance_path, new_instance_path)?;
+    if self::find_by_name(new_name)?.is_some() {
+        return Err(BackendError::InstanceError(
+            InstanceError::InstanceAlreadyExists(new_name.to_string()),
+        ));
+    }
 
     instance_metadata.name = new_name.to_string();
+    overwrite_instance(&instance_metadata)?;
+
+    Ok(())
+}
+
+/// Sets the group the instance is shown in, `None` removes it from its group
+pub fn set_group(name_or_id: &str, group: Option<String>) -> Result<(), BackendError> {
+    let (mut instance_metadata, _) = self::get_existing(name_or_id)?;
+    instance_metadata.group = group.filter(|group| !group.trim().is_empty());
+    overwrite_instance(&instance_metadata)?;
+    Ok(())
+}
+
+/// Adds `tags` to the instance's tags, ignoring the ones it already has
+pub fn add_tags(name_or_id: &str, tags: &[String]) -> Result<(), BackendError> {
+    let (mut instance_metadata, _) = self::get_existing(name_or_id)?;
+    for tag in tags {
+        if !instance_metadata.tags.contains(tag) {
+            instance_metadata.tags.push(tag.clone());
+        }
+    }
+    overwrite_instance(&instance_metadata)?;
+    Ok(())
+}
 
-    overwrite_instance(new_name, instance_metadata)?;
-    std::fs::remove_dir_all(old_instance_path)?;
+/// Removes `tags` from the instance's tags
+pub fn remove_tags(name_or_id: &str, tags: &[String]) -> Result<(), BackendError> {
+    let (mut instance_metadata, _) = self::get_existing(name_or_id)?;
+    instance_metadata.tags.retain(|tag| !tags.contains(tag));
+    overwrite_instance(&instance_metadata)?;
+    Ok(())
+}
+
+pub fn set_favorite(name_or_id: &str, favorite: bool) -> Result<(), BackendError> {
+    let (mut instance_metadata, _) = self::get_existing(name_or_id)?;
+    instance_metadata.favorite = favorite;
+    overwrite_instance(&instance_metadata)?;
+    Ok(())
+}
 
+/// Records a run of the instance's game that lasted `duration_secs` and just exited
+///
+/// the metadata is read again rather than kept from the launch, so edits made while the game was running aren't lost
+pub(crate) fn record_play(instance_id: &str, duration_secs: u64) -> Result<(), SchemaError> {
+    let instance_file_path = INSTANCES_DIR.join(instance_id).join(INSTANCE_FILE_NAME);
+    let mut instance_metadata: InstanceMetadata = load_versioned(&instance_file_path)?;
+
+    instance_metadata.last_played = Some(Utc::now());
+    instance_metadata.playtime_secs += duration_secs;
+    instance_metadata.launch_count += 1;
+
+    save_versioned(&instance_file_path, &instance_metadata)?;
     Ok(())
 }
 
-/// Gets an existing instance by name assuming it may not exist
+/// An instance whose metadata can't be read, corrupted or written by a newer launcher
+#[derive(Debug, Clone, Serialize)]
+pub struct UnreadableInstance {
+    /// The name of its directory
+    pub id: String,
+    pub error: String,
+}
+
+/// Every instance of the instances directory, the ones that can't be read are reported next to the others
+#[derive(Debug, Default, Serialize)]
+pub struct AllInstances {
+    pub instances: Vec<InstanceMetadata>,
+    pub unreadable: Vec<UnreadableInstance>,
+}
+
+/// The instances of the instances directory that were read, with the path of their metadata file
+///
+/// the ones that can't be read are logged and returned apart, one bad instance doesn't hide all the others
+struct LoadedInstances {
+    readable: Vec<(InstanceMetadata, PathBuf)>,
+    unreadable: Vec<(PathBuf, SchemaError)>,
+}
+
+/// Reads every instance in the instances directory, see [`LoadedInstances`]
+fn load_all() -> std::io::Result<LoadedInstances> {
+    let instances_dir = INSTANCES_DIR.read_dir()?;
+
+    let instances_paths = instances_dir
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|ft| ft.is_dir()))
+        .map(|entry| entry.path())
+        .map(|path| path.join(INSTANCE_FILE_NAME))
+        .filter(|instance_file_path| instance_file_path.exists());
+
+    let mut loaded = LoadedInstances {
+        readable: Vec::new(),
+        unreadable: Vec::new(),
+    };
+    // an instance that can't be read is reported rather than skipped, so its data isn't silently lost
+    for path in instances_paths {
+        match load_versioned(&path) {
+            Ok(instance) => loaded.readable.push((instance, path)),
+            Err(err) => {
+                wlog!("Skipping an unreadable instance: {err}");
+                loaded.unreadable.push((path, err));
+            }
+        }
+    }
+    Ok(loaded)
+}
+
+/// The name in the metadata file at `path`, read without its schema so it is found even if the rest can't be read
+fn peek_name(path: &Path) -> Option<String> {
+    let metadata: serde_json::Value = serde_json::from_slice(&fs::read(path).ok()?).ok()?;
+    Some(metadata.get("name")?.as_str()?.to_string())
+}
+
+/// Gets an existing instance by its display name, assuming it may not exist
+///
+/// only errors if the instance named `name` is one that can't be read, the others don't get in the way
+pub(crate) fn find_by_name(name: &str) -> Result<Option<(InstanceMetadata, PathBuf)>, SchemaError> {
+    let loaded = load_all()?;
+    if let Some(found) = loaded
+        .readable
+        .into_iter()
+        .find(|(instance, _)| instance.name == name)
+    {
+        return Ok(Some(found));
+    }
+
+    match loaded
+        .unreadable
+        .into_iter()
+        .find(|(path, _)| peek_name(path).as_deref() == Some(name))
+    {
+        Some((_, err)) => Err(err),
+        None => Ok(None),
+    }
+}
+
+/// Gets an existing instance by name or by ID assuming it may not exist, names take precedence
 /// returns Ok(None) if it does not exist
-pub(super) fn find(name: &str) -> std::io::Result<Option<(InstanceMetadata, PathBuf)>> {
-    // FIXME: maybe don't rely on the name for getting an existing instance's path
-    let instance_file_path = INSTANCES_DIR.join(name).join(INSTANCE_FILE_NAME);
-    let instance_file = match File::open(&instance_file_path) {
-        Ok(file) => file,
-        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
-        Err(err) => return Err(err),
+pub(super) fn find(name_or_id: &str) -> Result<Option<(InstanceMetadata, PathBuf)>, SchemaError> {
+    let instance_file_path = INSTANCES_DIR.join(name_or_id).join(INSTANCE_FILE_NAME);
+    let by_id = match instance_file_path.exists() {
+        true => Some((load_versioned::<InstanceMetadata>(&instance_file_path)?, instance_file_path)),
+        false => None,
     };
 
-    let read_buf = BufReader::new(instance_file);
-    let instance = serde_json::from_reader(read_buf)?;
+    // instances that were never renamed have the same name and ID
+    if by_id
+        .as_ref()
+        .is_some_and(|(instance, _)| instance.name == name_or_id)
+    {
+        return Ok(by_id);
+    }
 
-    Ok(Some((instance, instance_file_path)))
+    Ok(find_by_name(name_or_id)?.or(by_id))
 }
 
 pub(super) fn add_new(instance: &InstanceMetadata) -> Result<(), BackendError> {
-    if self::find(&instance.name)?.is_some() {
+    if self::find_by_name(&instance.name)?.is_some() {
         return Err(BackendError::InstanceError(
             InstanceError::InstanceAlreadyExists(instance.name.clone()),
         ));
     }
 
-    let new_instance_file_path = INSTANCES_DIR.join(&instance.name).join(INSTANCE_FILE_NAME);
+    let new_instance_file_path = instance.instance_dir().join(INSTANCE_FILE_NAME);
 
     if let Some(parent) = new_instance_file_path.parent() {
         std::fs::create_dir_all(parent)?;
     }
 
-    let instance_file = File::create_new(&new_instance_file_path)?;
-    serde_json::to_writer_pretty(instance_file, &instance)?;
-
+    save_versioned(&new_instance_file_path, instance)?;
     Ok(())
 }
 
-pub fn remove(name: &str) -> Result<(), BackendError> {
-    let (_, instance_file_path) = self::get_existing(name)?;
+/// Deletes the instance along with its backups
+pub fn remove(name_or_id: &str) -> Result<(), BackendError> {
+    let (instance, instance_file_path) = self::get_existing(name_or_id)?;
+    // nothing could restore them anymore, they would take up space for good
+    backups::delete_all(&instance.id)?;
 
     if let Some(parent) = instance_file_path.parent() {
         fs::remove_dir_all(parent)?;
@@ -144,49 +293,41 @@ pub fn remove(name: &str) -> Result<(), BackendError> {
     Ok(())
 }
 
-/// Gets an existing instance by name assuming it exists
+/// Gets an existing instance by name or by ID assuming it exists
 /// errors if it does not exist
-pub fn get_existing(name: &str) -> Result<(InstanceMetadata, PathBuf), BackendError> {
-    self::find(name)?.ok_or_else(|| {
-        BackendError::InstanceError(InstanceError::InstanceNotFound(name.to_string()))
+pub fn get_existing(name_or_id: &str) -> Result<(InstanceMetadata, PathBuf), BackendError> {
+    self::find(name_or_id)?.ok_or_else(|| {
+        BackendError::InstanceError(InstanceError::InstanceNotFound(name_or_id.to_string()))
     })
 }
 
-/// Gets all instances information from the instances directory
-pub fn get_all_instances() -> Result<Vec<InstanceMetadata>, BackendError> {
-    let instances_dir = INSTANCES_DIR.read_dir()?;
-
-    let instances_paths = instances_dir
+/// Gets all instances information from the instances directory, along with the instances that can't be read
+pub fn load_instances() -> Result<AllInstances, BackendError> {
+    let loaded = load_all()?;
+    let unreadable = loaded
+        .unreadable
         .into_iter()
-        .filter_map(|entry| entry.ok())
-        .filter(|entry| entry.file_type().is_ok_and(|ft| ft.is_dir()))
-        .map(|entry| entry.path())
-        .map(|path| path.join(INSTANCE_FILE_NAME))
-        .filter(|instance_file_path| instance_file_path.exists());
-
-    let instances = instances_paths
-        .map(|path| -> Result<_, BackendError> {
-            let instance_file = File::open(&path)?;
-            let deserialized: Result<InstanceMetadata, _> = serde_json::from_reader(instance_file);
-            match deserialized {
-                Ok(instance) => Ok(instance),
-                Err(e) => {
-                    elog!("Failed to load instance at {:?}: {}. Skipping.", path, e);
-                    Err(BackendError::InstanceError(InstanceError::FailedToExecute(
-                        format!("{:?}: {}", path, e),
-                    )))
-                }
-            }
+        .map(|(path, err)| UnreadableInstance {
+            id: path
+                .parent()
+                .and_then(|dir| dir.file_name())
+                .map(|id| id.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            error: err.to_string(),
         })
-        .filter_map(|instance| match instance {
-            Err(e) => {
-                elog!("failed to load an instance error: {}, ignoring...", e);
-                None
-            }
-            Ok(i) => Some(i),
-        });
-
-    let vec_instances = instances.collect();
+        .collect();
+
+    Ok(AllInstances {
+        instances: loaded
+            .readable
+            .into_iter()
+            .map(|(instance, _)| instance)
+            .collect(),
+        unreadable,
+    })
+}
 
-    Ok(vec_instances)
+/// Gets all instances information from the instances directory, the ones that can't be read are left out
+pub fn get_all_instances() -> Result<Vec<InstanceMetadata>, BackendError> {
+    Ok(load_instances()?.instances)
 }
@@ -0,0 +1,810 @@
+use std::{
+    collections::HashMap,
+    io::{Read, Seek},
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sl_utils::errors::ModMetadataError;
+use zip::{result::ZipError, ZipArchive};
+
+use crate::launcher::instances::instance_metadata::ModLoader;
+
+/// The sides a mod is made for
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModEnvironment {
+    #[default]
+    Both,
+    Client,
+    Server,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ModDependency {
+    pub id: String,
+    /// The accepted versions, in the mod loader's syntax, `None` for any
+    pub versions: Option<String>,
+    /// Whether the mod doesn't load without it, optional ones are only recommended
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ModMetadata {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub authors: Vec<String>,
+    pub description: Option<String>,
+    pub dependencies: Vec<ModDependency>,
+    pub environment: ModEnvironment,
+    /// The path of the mod's icon inside its jar
+    pub icon: Option<String>,
+}
+
+/// The files mods describe themselves with
+#[derive(Debug, Clone, Copy)]
+enum MetadataFormat {
+    Fabric,
+    Quilt,
+    Forge,
+    NeoForge,
+    /// `mcmod.info`, used by Forge before 1.13
+    LegacyForge,
+}
+
+impl MetadataFormat {
+    const fn file_name(self) -> &'static str {
+        match self {
+            Self::Fabric => "fabric.mod.json",
+            Self::Quilt => "quilt.mod.json",
+            Self::Forge => "META-INF/mods.toml",
+            Self::NeoForge => "META-INF/neoforge.mods.toml",
+            Self::LegacyForge => "mcmod.info",
+        }
+    }
+
+    /// The formats `mod_loader` loads mods with, the one it prefers first
+    ///
+    /// Quilt loads Fabric mods and NeoForge used Forge's `mods.toml` before 1.20.5
+    const fn supported_by(mod_loader: ModLoader) -> &'static [Self] {
+        match mod_loader {
+            ModLoader::Vanilla => &[],
+            ModLoader::Fabric => &[Self::Fabric],
+            ModLoader::Quilt => &[Self::Quilt, Self::Fabric],
+            ModLoader::Forge => &[Self::Forge, Self::LegacyForge],
+            ModLoader::NeoForge => &[Self::NeoForge, Self::Forge],
+        }
+    }
+}
+
+/// A field that is either one string or a list of them
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl OneOrMany {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            Self::One(one) => vec![one],
+            Self::Many(many) => many,
+        }
+    }
+
+    /// The version predicate it is, any of the listed ones matches
+    fn into_versions(self) -> Option<String> {
+        let versions = self.into_vec();
+        match versions.iter().any(|version| version == "*") || versions.is_empty() {
+            true => None,
+            false => Some(versions.join(" || ")),
+        }
+    }
+}
+
+/// Turns an empty or missing string into `None`
+fn non_empty(value: Option<String>) -> Option<String> {
+    value.filter(|value| !value.trim().is_empty())
+}
+
+/// The path of an icon inside the jar, some mods write it with a leading `/`
+fn icon_path(path: Option<String>) -> Option<String> {
+    non_empty(path).map(|path| path.trim_start_matches('/').to_string())
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum FabricPerson {
+    Name(String),
+    Full { name: String },
+}
+
+impl FabricPerson {
+    fn into_name(self) -> String {
+        match self {
+            Self::Name(name) | Self::Full { name } => name,
+        }
+    }
+}
+
+/// Either one icon or icons by their width
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum FabricIcon {
+    Path(String),
+    Sizes(HashMap<String, String>),
+}
+
+impl FabricIcon {
+    fn into_largest(self) -> Option<String> {
+        match self {
+            Self::Path(path) => Some(path),
+            Self::Sizes(sizes) => sizes
+                .into_iter()
+                .max_by_key(|(size, _)| size.parse::<u32>().unwrap_or(0))
+                .map(|(_, path)| path),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct FabricMod {
+    id: String,
+    version: String,
+    name: Option<String>,
+    description: Option<String>,
+    #[serde(default)]
+    authors: Vec<FabricPerson>,
+    #[serde(default)]
+    depends: HashMap<String, OneOrMany>,
+    #[serde(default)]
+    recommends: HashMap<String, OneOrMany>,
+    environment: Option<String>,
+    icon: Option<FabricIcon>,
+}
+
+fn parse_environment(environment: Option<&str>) -> ModEnvironment {
+    match environment {
+        Some("client") => ModEnvironment::Client,
+        Some("server" | "dedicated_server") => ModEnvironment::Server,
+        _ => ModEnvironment::Both,
+    }
+}
+
+impl From<FabricMod> for ModMetadata {
+    fn from(fabric_mod: FabricMod) -> Self {
+        let required = fabric_mod.depends.into_iter().map(|dep| (dep, true));
+        let recommended = fabric_mod.recommends.into_iter().map(|dep| (dep, false));
+        let mut dependencies = required
+            .chain(recommended)
+            .map(|((id, versions), required)| ModDependency {
+                id,
+                versions: versions.into_versions(),
+                required,
+            })
+            .collect::<Vec<_>>();
+        dependencies.sort_by(|a, b| a.id.cmp(&b.id));
+
+        Self {
+            name: non_empty(fabric_mod.name).unwrap_or_else(|| fabric_mod.id.clone()),
+            id: fabric_mod.id,
+            version: fabric_mod.version,
+            authors: fabric_mod
+                .authors
+                .into_iter()
+                .map(FabricPerson::into_name)
+                .collect(),
+            description: non_empty(fabric_mod.description),
+            dependencies,
+            environment: parse_environment(fabric_mod.environment.as_deref()),
+            icon: icon_path(fabric_mod.icon.and_then(FabricIcon::into_largest)),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct QuiltModMetadata {
+    name: Option<String>,
+    description: Option<String>,
+    /// Contributors by their name, the values are their roles
+    #[serde(default)]
+    contributors: HashMap<String, serde_json::Value>,
+    icon: Option<FabricIcon>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum QuiltDependency {
+    Id(String),
+    Full {
+        id: String,
+        versions: Option<serde_json::Value>,
+        #[serde(default)]
+        optional: bool,
+    },
+}
+
+impl From<QuiltDependency> for ModDependency {
+    fn from(dependency: QuiltDependency) -> Self {
+        let (id, versions, optional) = match dependency {
+            QuiltDependency::Id(id) => (id, None, false),
+            QuiltDependency::Full {
+                id,
+                versions,
+                optional,
+            } => (id, versions, optional),
+        };
+
+        Self {
+            // dependencies may be qualified by their maven group, as in `org.quiltmc:quilt_loader`
+            id: id.rsplit(':').next().unwrap_or(&id).to_string(),
+            // the object form combining predicates with `any` and `all` isn't kept
+            versions: versions
+                .and_then(|versions| serde_json::from_value::<OneOrMany>(versions).ok())
+                .and_then(OneOrMany::into_versions),
+            required: !optional,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct QuiltLoader {
+    id: String,
+    version: String,
+    #[serde(default)]
+    metadata: QuiltModMetadata,
+    #[serde(default)]
+    depends: Vec<QuiltDependency>,
+}
+
+#[derive(Debug, Deserialize)]
+struct QuiltMinecraft {
+    environment: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct QuiltMod {
+    quilt_loader: QuiltLoader,
+    minecraft: Option<QuiltMinecraft>,
+}
+
+impl From<QuiltMod> for ModMetadata {
+    fn from(quilt_mod: QuiltMod) -> Self {
+        let loader = quilt_mod.quilt_loader;
+        let mut authors = loader.metadata.contributors.into_keys().collect::<Vec<_>>();
+        authors.sort();
+
+        Self {
+            name: non_empty(loader.metadata.name).unwrap_or_else(|| loader.id.clone()),
+            id: loader.id,
+            version: loader.version,
+            authors,
+            description: non_empty(loader.metadata.description),
+            dependencies: loader.depends.into_iter().map(Into::into).collect(),
+            environment: parse_environment(
+                quilt_mod
+                    .minecraft
+                    .and_then(|minecraft| minecraft.environment)
+                    .as_deref(),
+            ),
+            icon: icon_path(loader.metadata.icon.and_then(FabricIcon::into_largest)),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ForgeMod {
+    mod_id: String,
+    version: Option<String>,
+    display_name: Option<String>,
+    /// Usually one string listing them all
+    authors: Option<OneOrMany>,
+    description: Option<String>,
+    logo_file: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ForgeDependency {
+    mod_id: String,
+    /// Forge's way to tell required dependencies
+    mandatory: Option<bool>,
+    /// NeoForge's way, one of `required`, `optional`, `incompatible` and `discouraged`
+    r#type: Option<String>,
+    version_range: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ModsToml {
+    mods: Vec<ForgeMod>,
+    #[serde(default)]
+    dependencies: HashMap<String, Vec<ForgeDependency>>,
+    /// The logo shared by the mods of the jar
+    logo_file: Option<String>,
+    #[serde(default)]
+    client_side_only: bool,
+}
+
+impl ModsToml {
+    /// The first mod of the jar, `jar_version` replaces the `${file.jarVersion}` placeholder
+    fn into_metadata(mut self, jar_version: Option<String>) -> Option<ModMetadata> {
+        if self.mods.is_empty() {
+            return None;
+        }
+        let forge_mod = self.mods.swap_remove(0);
+
+        let version = match forge_mod.version.as_deref() {
+            Some("${file.jarVersion}") => jar_version,
+            _ => forge_mod.version,
+        }
+        .unwrap_or_else(|| "1".to_string());
+
+        let authors = forge_mod
+            .authors
+            .map(OneOrMany::into_vec)
+            .unwrap_or_default()
+            .iter()
+            .flat_map(|authors| authors.split(','))
+            .map(|author| author.trim().to_string())
+            .filter(|author| !author.is_empty())
+            .collect();
+
+        let dependencies = self
+            .dependencies
+            .remove(&forge_mod.mod_id)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|dependency| {
+                let required = match dependency.r#type.as_deref() {
+                    Some(kind) if kind.eq_ignore_ascii_case("required") => true,
+                    Some(kind) if kind.eq_ignore_ascii_case("optional") => false,
+                    // incompatible and discouraged mods aren't dependencies
+                    Some(_) => return None,
+                    None => dependency.mandatory.unwrap_or(true),
+                };
+                Some(ModDependency {
+                    id: dependency.mod_id,
+                    versions: non_empty(dependency.version_range),
+                    required,
+                })
+            })
+            .collect();
+
+        Some(ModMetadata {
+            name: non_empty(forge_mod.display_name).unwrap_or_else(|| forge_mod.mod_id.clone()),
+            id: forge_mod.mod_id,
+            version,
+            authors,
+            description: non_empty(
+                forge_mod
+                    .description
+                    .map(|description| description.trim().to_string()),
+            ),
+            dependencies,
+            environment: match self.client_side_only {
+                true => ModEnvironment::Client,
+                false => ModEnvironment::Both,
+            },
+            icon: icon_path(forge_mod.logo_file.or(self.logo_file)),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LegacyForgeMod {
+    modid: String,
+    name: Option<String>,
+    version: Option<String>,
+    description: Option<String>,
+    #[serde(default, alias = "authors")]
+    author_list: Vec<String>,
+    logo_file: Option<String>,
+    /// Dependencies as `modid@versions`
+    #[serde(default)]
+    required_mods: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum McModInfo {
+    Mods(Vec<LegacyForgeMod>),
+    /// `modListVersion` 2 wraps the list
+    Wrapped {
+        #[serde(rename = "modList")]
+        mod_list: Vec<LegacyForgeMod>,
+    },
+}
+
+impl McModInfo {
+    fn into_metadata(self) -> Option<ModMetadata> {
+        let legacy_mod = match self {
+            Self::Mods(mods) | Self::Wrapped { mod_list: mods } => mods.into_iter().next()?,
+        };
+
+        let dependencies = legacy_mod
+            .required_mods
+            .into_iter()
+            .map(|dependency| {
+                let (id, versions) = match dependency.split_once('@') {
+                    Some((id, versions)) => (id.to_string(), non_empty(Some(versions.to_string()))),
+                    None => (dependency, None),
+                };
+                ModDependency {
+                    id,
+                    versions,
+                    required: true,
+                }
+            })
+            .collect();
+
+        Some(ModMetadata {
+            name: non_empty(legacy_mod.name).unwrap_or_else(|| legacy_mod.modid.clone()),
+            id: legacy_mod.modid,
+            version: legacy_mod.version.unwrap_or_default(),
+            authors: legacy_mod.author_list,
+            description: non_empty(legacy_mod.description),
+            dependencies,
+            environment: ModEnvironment::Both,
+            icon: icon_path(legacy_mod.logo_file),
+        })
+    }
+}
+
+/// Reads the entry `name` of `archive` as text, `None` if there is none
+fn read_entry<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    name: &str,
+) -> Result<Option<String>, ModMetadataError> {
+    let mut entry = match archive.by_name(name) {
+        Ok(entry) => entry,
+        Err(ZipError::FileNotFound) => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut bytes = Vec::new();
+    entry.read_to_end(&mut bytes)?;
+    // some files are saved with a byte order mark
+    let text = String::from_utf8_lossy(&bytes);
+    Ok(Some(text.trim_start_matches('\u{feff}').to_string()))
+}
+
+fn parse_json<T: DeserializeOwned>(
+    format: MetadataFormat,
+    json: &str,
+) -> Result<T, ModMetadataError> {
+    serde_json::from_str(json)
+        .map_err(|err| ModMetadataError::Malformed(format.file_name(), err.to_string()))
+}
+
+/// The `Implementation-Version` of the jar's manifest, Forge's `${file.jarVersion}`
+fn read_jar_version<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+) -> Result<Option<String>, ModMetadataError> {
+    let manifest = read_entry(archive, "META-INF/MANIFEST.MF")?;
+    Ok(manifest.and_then(|manifest| {
+        manifest.lines().find_map(|line| {
+            line.strip_prefix("Implementation-Version:")
+                .map(|version| version.trim().to_string())
+        })
+    }))
+}
+
+fn parse<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    format: MetadataFormat,
+    content: &str,
+) -> Result<Option<ModMetadata>, ModMetadataError> {
+    let metadata = match format {
+        MetadataFormat::Fabric => Some(parse_json::<FabricMod>(format, content)?.into()),
+        MetadataFormat::Quilt => Some(parse_json::<QuiltMod>(format, content)?.into()),
+        MetadataFormat::LegacyForge => parse_json::<McModInfo>(format, content)?.into_metadata(),
+        MetadataFormat::Forge | MetadataFormat::NeoForge => {
+            let mods_toml: ModsToml = toml::from_str(content)
+                .map_err(|err| ModMetadataError::Malformed(format.file_name(), err.to_string()))?;
+            mods_toml.into_metadata(read_jar_version(archive)?)
+        }
+    };
+    Ok(metadata)
+}
+
+/// Reads the metadata of the mod jar opened as `archive`, in the formats `mod_loader` loads
+///
+/// `None` if the jar isn't a mod of `mod_loader`
+pub fn read_mod_metadata<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    mod_loader: ModLoader,
+) -> Result<Option<ModMetadata>, ModMetadataError> {
+    for &format in MetadataFormat::supported_by(mod_loader) {
+        if let Some(content) = read_entry(archive, format.file_name())? {
+            return parse(archive, format, &content);
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+    use super::*;
+
+    fn jar(entries: &[(&str, &str)]) -> ZipArchive<Cursor<Vec<u8>>> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        for (name, content) in entries {
+            writer.start_file(*name, options).unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        ZipArchive::new(writer.finish().unwrap()).unwrap()
+    }
+
+    fn read(entries: &[(&str, &str)], mod_loader: ModLoader) -> ModMetadata {
+        read_mod_metadata(&mut jar(entries), mod_loader)
+            .unwrap()
+            .unwrap()
+    }
+
+    fn dependencies(metadata: &ModMetadata) -> Vec<(&str, Option<&str>, bool)> {
+        metadata
+            .dependencies
+            .iter()
+            .map(|dependency| {
+                (
+                    dependency.id.as_str(),
+                    dependency.versions.as_deref(),
+                    dependency.required,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fabric() {
+        let fabric_mod_json = r#"{
+            "schemaVersion": 1,
+            "id": "sodium",
+            "version": "0.5.11",
+            "name": "Sodium",
+            "authors": ["JellySquid", { "name": "IMS", "contact": {} }],
+            "depends": { "fabricloader": ">=0.12.0", "minecraft": ["1.21", "1.21.1"], "fabric-api": "*" },
+            "recommends": { "modmenu": "*" },
+            "environment": "client",
+            "icon": { "16": "assets/sodium/icon16.png", "128": "/assets/sodium/icon.png" }
+        }"#;
+        let metadata = read(&[("fabric.mod.json", fabric_mod_json)], ModLoader::Fabric);
+
+        assert_eq!(metadata.id, "sodium");
+        assert_eq!(metadata.name, "Sodium");
+        assert_eq!(metadata.version, "0.5.11");
+        assert_eq!(metadata.authors, ["JellySquid", "IMS"]);
+        assert_eq!(
+            dependencies(&metadata),
+            [
+                ("fabric-api", None, true),
+                ("fabricloader", Some(">=0.12.0"), true),
+                ("minecraft", Some("1.21 || 1.21.1"), true),
+                ("modmenu", None, false),
+            ]
+        );
+        assert_eq!(metadata.environment, ModEnvironment::Client);
+        assert_eq!(metadata.icon.as_deref(), Some("assets/sodium/icon.png"));
+    }
+
+    #[test]
+    fn quilt() {
+        let quilt_mod_json = r#"{
+            "schema_version": 1,
+            "quilt_loader": {
+                "group": "org.example",
+                "id": "example",
+                "version": "1.0.0",
+                "metadata": {
+                    "name": "Example",
+                    "contributors": { "Zed": "Owner", "Amy": "Contributor" },
+                    "icon": "assets/example/icon.png"
+                },
+                "depends": [
+                    "minecraft",
+                    { "id": "org.quiltmc:quilt_loader", "versions": ">=0.19.0" },
+                    { "id": "qsl", "versions": ["6.0.0", "6.1.0"], "optional": true }
+                ]
+            },
+            "minecraft": { "environment": "dedicated_server" }
+        }"#;
+        // Quilt prefers its own metadata over the Fabric one
+        let metadata = read(
+            &[
+                (
+                    "fabric.mod.json",
+                    r#"{ "id": "fabric_example", "version": "0" }"#,
+                ),
+                ("quilt.mod.json", quilt_mod_json),
+            ],
+            ModLoader::Quilt,
+        );
+
+        assert_eq!(metadata.id, "example");
+        assert_eq!(metadata.name, "Example");
+        assert_eq!(metadata.version, "1.0.0");
+        assert_eq!(metadata.authors, ["Amy", "Zed"]);
+        assert_eq!(
+            dependencies(&metadata),
+            [
+                ("minecraft", None, true),
+                ("quilt_loader", Some(">=0.19.0"), true),
+                ("qsl", Some("6.0.0 || 6.1.0"), false),
+            ]
+        );
+        assert_eq!(metadata.environment, ModEnvironment::Server);
+        assert_eq!(metadata.icon.as_deref(), Some("assets/example/icon.png"));
+    }
+
+    #[test]
+    fn forge() {
+        let mods_toml = r#"
+            modLoader = "javafml"
+            loaderVersion = "[47,)"
+            license = "MIT"
+            logoFile = "logo.png"
+            clientSideOnly = true
+
+            [[mods]]
+            modId = "jei"
+            version = "${file.jarVersion}"
+            displayName = "Just Enough Items"
+            authors = "mezz, Alice"
+            description = '''
+            Item and recipe viewing.
+            '''
+
+            [[dependencies.jei]]
+            modId = "forge"
+            mandatory = true
+            versionRange = "[47,)"
+            ordering = "NONE"
+            side = "BOTH"
+
+            [[dependencies.jei]]
+            modId = "jade"
+            mandatory = false
+            versionRange = ""
+            ordering = "NONE"
+            side = "CLIENT"
+        "#;
+        let manifest = "Manifest-Version: 1.0\r\nImplementation-Version: 15.2.0.27\r\n";
+        let metadata = read(
+            &[
+                ("META-INF/MANIFEST.MF", manifest),
+                ("META-INF/mods.toml", mods_toml),
+            ],
+            ModLoader::Forge,
+        );
+
+        assert_eq!(metadata.id, "jei");
+        assert_eq!(metadata.name, "Just Enough Items");
+        assert_eq!(metadata.version, "15.2.0.27");
+        assert_eq!(metadata.authors, ["mezz", "Alice"]);
+        assert_eq!(
+            metadata.description.as_deref(),
+            Some("Item and recipe viewing.")
+        );
+        assert_eq!(
+            dependencies(&metadata),
+            [("forge", Some("[47,)"), true), ("jade", None, false)]
+        );
+        assert_eq!(metadata.environment, ModEnvironment::Client);
+        assert_eq!(metadata.icon.as_deref(), Some("logo.png"));
+    }
+
+    #[test]
+    fn neoforge() {
+        let mods_toml = r#"
+            modLoader = "javafml"
+            loaderVersion = "[4,)"
+            license = "MIT"
+
+            [[mods]]
+            modId = "create"
+            version = "6.0.0"
+            authors = ["simibubi"]
+            logoFile = "/create_logo.png"
+
+            [[dependencies.create]]
+            modId = "neoforge"
+            type = "required"
+            versionRange = "[21.1,)"
+
+            [[dependencies.create]]
+            modId = "jei"
+            type = "OPTIONAL"
+
+            [[dependencies.create]]
+            modId = "optifine"
+            type = "incompatible"
+        "#;
+        let metadata = read(
+            &[("META-INF/neoforge.mods.toml", mods_toml)],
+            ModLoader::NeoForge,
+        );
+
+        assert_eq!(metadata.id, "create");
+        assert_eq!(metadata.name, "create");
+        assert_eq!(metadata.version, "6.0.0");
+        assert_eq!(metadata.authors, ["simibubi"]);
+        assert_eq!(
+            dependencies(&metadata),
+            [("neoforge", Some("[21.1,)"), true), ("jei", None, false)]
+        );
+        assert_eq!(metadata.environment, ModEnvironment::Both);
+        assert_eq!(metadata.icon.as_deref(), Some("create_logo.png"));
+    }
+
+    #[test]
+    fn legacy_forge() {
+        let v1 = "\u{feff}[{
+            \"modid\": \"ic2\",
+            \"name\": \"IndustrialCraft 2\",
+            \"version\": \"2.2.827\",
+            \"authorList\": [\"Player\", \"Alblaka\"],
+            \"logoFile\": \"/assets/ic2/logo.png\",
+            \"requiredMods\": [\"Forge@[10.13.4.1448,)\", \"CoFHCore\"]
+        }]";
+        let metadata = read(&[("mcmod.info", v1)], ModLoader::Forge);
+
+        assert_eq!(metadata.id, "ic2");
+        assert_eq!(metadata.name, "IndustrialCraft 2");
+        assert_eq!(metadata.version, "2.2.827");
+        assert_eq!(metadata.authors, ["Player", "Alblaka"]);
+        assert_eq!(
+            dependencies(&metadata),
+            [
+                ("Forge", Some("[10.13.4.1448,)"), true),
+                ("CoFHCore", None, true)
+            ]
+        );
+        assert_eq!(metadata.environment, ModEnvironment::Both);
+        assert_eq!(metadata.icon.as_deref(), Some("assets/ic2/logo.png"));
+
+        let v2 = r#"{
+            "modListVersion": 2,
+            "modList": [{ "modid": "thaumcraft", "version": "4.2.3.5", "authors": ["Azanor"] }]
+        }"#;
+        let metadata = read(&[("mcmod.info", v2)], ModLoader::Forge);
+
+        assert_eq!(metadata.id, "thaumcraft");
+        assert_eq!(metadata.name, "thaumcraft");
+        assert_eq!(metadata.version, "4.2.3.5");
+        assert_eq!(metadata.authors, ["Azanor"]);
+        assert!(metadata.dependencies.is_empty());
+        assert_eq!(metadata.icon, None);
+    }
+
+    #[test]
+    fn other_loaders_mods_are_skipped() {
+        let mut fabric_jar = jar(&[(
+            "fabric.mod.json",
+            r#"{ "id": "sodium", "version": "0.5.11" }"#,
+        )]);
+        assert!(read_mod_metadata(&mut fabric_jar, ModLoader::Forge)
+            .unwrap()
+            .is_none());
+        assert!(read_mod_metadata(&mut fabric_jar, ModLoader::Vanilla)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn malformed_metadata_is_an_error() {
+        let mut broken = jar(&[("fabric.mod.json", "{ \"id\": ")]);
+        assert!(matches!(
+            read_mod_metadata(&mut broken, ModLoader::Fabric),
+            Err(ModMetadataError::Malformed("fabric.mod.json", _))
+        ));
+    }
+}
@@ -0,0 +1,271 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufReader, Read},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local, Utc};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sl_utils::{dlog, wlog};
+
+use crate::launcher::game_process::GameState;
+
+/// Where the logs of every run are kept, relative to the instance's directory
+const RUNS_DIR: &str = "logs/runs";
+
+/// Describes a single run of the game, stored as `<id>.json` next to its log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunMetadata {
+    /// When the run started in local time, the log is named after it
+    pub id: String,
+    /// The account's name, never its token
+    pub account_name: String,
+    pub java_path: PathBuf,
+    /// SHA-256 of the launch arguments without the access token, tells whether two runs were launched the same way
+    pub args_hash: String,
+    pub started_at: DateTime<Utc>,
+    /// `None` while the game is running, or if the launcher exited before the game did
+    pub duration_secs: Option<u64>,
+    pub exit_code: Option<i32>,
+    /// The signal that terminated the game, unix only
+    pub signal: Option<i32>,
+}
+
+fn runs_dir(instance_dir: &Path) -> PathBuf {
+    instance_dir.join(RUNS_DIR)
+}
+
+fn metadata_path(runs_dir: &Path, id: &str) -> PathBuf {
+    runs_dir.join(format!("{id}.json"))
+}
+
+/// The plain log, only exists while the game is running
+fn live_log_path(runs_dir: &Path, id: &str) -> PathBuf {
+    runs_dir.join(format!("{id}.log"))
+}
+
+fn compressed_log_path(runs_dir: &Path, id: &str) -> PathBuf {
+    runs_dir.join(format!("{id}.log.gz"))
+}
+
+fn write_metadata(runs_dir: &Path, metadata: &RunMetadata) -> io::Result<()> {
+    let file = File::create(metadata_path(runs_dir, &metadata.id))?;
+    serde_json::to_writer_pretty(file, metadata)?;
+    Ok(())
+}
+
+fn hash_args(args: &[String], access_token: &str) -> String {
+    let mut hasher = Sha256::new();
+    for arg in args.iter().filter(|arg| *arg != access_token) {
+        hasher.update(arg.as_bytes());
+        hasher.update([0]);
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Replaces the plain log of the run with a compressed one
+fn compress_log(runs_dir: &Path, id: &str) -> io::Result<()> {
+    let live_path = live_log_path(runs_dir, id);
+    let mut live_log = BufReader::new(File::open(&live_path)?);
+
+    let compressed = File::create(compressed_log_path(runs_dir, id))?;
+    let mut encoder = GzEncoder::new(compressed, Compression::default());
+    io::copy(&mut live_log, &mut encoder)?;
+    encoder.finish()?;
+
+    fs::remove_file(live_path)
+}
+
+/// Every run of the instance, newest first
+pub fn list_runs(instance_dir: &Path) -> io::Result<Vec<RunMetadata>> {
+    let runs_dir = runs_dir(instance_dir);
+    let entries = match fs::read_dir(&runs_dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+
+    let mut runs = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+
+        let file = BufReader::new(File::open(&path)?);
+        match serde_json::from_reader::<_, RunMetadata>(file) {
+            Ok(run) => runs.push(run),
+            Err(err) => wlog!("Ignoring unreadable run metadata {}: {err}", path.display()),
+        }
+    }
+
+    runs.sort_by_key(|run| std::cmp::Reverse(run.started_at));
+    Ok(runs)
+}
+
+/// Reads the whole log of the run `id`, whether it is still running or not
+pub fn read_run(instance_dir: &Path, id: &str) -> io::Result<String> {
+    let runs_dir = runs_dir(instance_dir);
+    let mut bytes = Vec::new();
+
+    match File::open(compressed_log_path(&runs_dir, id)) {
+        Ok(file) => _ = GzDecoder::new(file).read_to_end(&mut bytes)?,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            _ = File::open(live_log_path(&runs_dir, id))?.read_to_end(&mut bytes)?
+        }
+        Err(err) => return Err(err),
+    }
+
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Reads the last `lines` lines of the log of the run `id`
+pub fn tail_run(instance_dir: &Path, id: &str, lines: usize) -> io::Result<Vec<String>> {
+    let log = read_run(instance_dir, id)?;
+    let all = log.lines().collect::<Vec<_>>();
+    let skip = all.len().saturating_sub(lines);
+    Ok(all[skip..].iter().map(|line| line.to_string()).collect())
+}
+
+/// Opens the plain log of the run `id` for following it while the game is running
+///
+/// returns `Ok(None)` if the run already finished
+pub fn open_live_run(instance_dir: &Path, id: &str) -> io::Result<Option<File>> {
+    match File::open(live_log_path(&runs_dir(instance_dir), id)) {
+        Ok(file) => Ok(Some(file)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// The path of the log of the run `id`, compressed once the run finished
+pub fn run_log_path(instance_dir: &Path, id: &str) -> PathBuf {
+    let runs_dir = runs_dir(instance_dir);
+    match is_run_finished(instance_dir, id) {
+        true => compressed_log_path(&runs_dir, id),
+        false => live_log_path(&runs_dir, id),
+    }
+}
+
+/// Whether the run `id` is finished, meaning its log was compressed
+pub fn is_run_finished(instance_dir: &Path, id: &str) -> bool {
+    compressed_log_path(&runs_dir(instance_dir), id).exists()
+}
+
+/// A run whose log is being written to, finished once the game exits
+pub(crate) struct RunLog {
+    runs_dir: PathBuf,
+    metadata: RunMetadata,
+}
+
+impl RunLog {
+    /// Creates the metadata and log file of a new run, removing the oldest runs so only `max_runs` are kept
+    ///
+    /// returns the log file the game's output should be written to
+    pub(crate) fn start(
+        instance_dir: &Path,
+        account_name: &str,
+        java_path: &Path,
+        args: &[String],
+        access_token: &str,
+        max_runs: usize,
+    ) -> io::Result<(Self, File)> {
+        let runs_dir = runs_dir(instance_dir);
+        fs::create_dir_all(&runs_dir)?;
+
+        finish_stale_runs(instance_dir);
+        prune_runs(instance_dir, max_runs.saturating_sub(1));
+
+        let started_at = Utc::now();
+        let base_id = started_at
+            .with_timezone(&Local)
+            .format("%Y-%m-%d_%H-%M-%S")
+            .to_string();
+        let mut id = base_id.clone();
+        let mut n = 1;
+        while metadata_path(&runs_dir, &id).exists() {
+            n += 1;
+            id = format!("{base_id}-{n}");
+        }
+
+        let metadata = RunMetadata {
+            id,
+            account_name: account_name.to_string(),
+            java_path: java_path.to_path_buf(),
+            args_hash: hash_args(args, access_token),
+            started_at,
+            duration_secs: None,
+            exit_code: None,
+            signal: None,
+        };
+        write_metadata(&runs_dir, &metadata)?;
+
+        let log_file = OpenOptions::new()
+            .create_new(true)
+            .write(true)
+            .open(live_log_path(&runs_dir, &metadata.id))?;
+
+        Ok((Self { runs_dir, metadata }, log_file))
+    }
+
+    pub(crate) fn id(&self) -> &str {
+        &self.metadata.id
+    }
+
+    pub(crate) fn log_path(&self) -> PathBuf {
+        live_log_path(&self.runs_dir, &self.metadata.id)
+    }
+
+    /// Records how the game exited and compresses the run's log
+    ///
+    /// returns how long the run lasted in seconds
+    pub(crate) fn finish(mut self, state: GameState) -> u64 {
+        if let GameState::Exited { code, signal } = state {
+            self.metadata.exit_code = code;
+            self.metadata.signal = signal;
+        }
+
+        let elapsed = Utc::now() - self.metadata.started_at;
+        let duration_secs = elapsed.num_seconds().max(0) as u64;
+        self.metadata.duration_secs = Some(duration_secs);
+
+        if let Err(err) = write_metadata(&self.runs_dir, &self.metadata) {
+            wlog!("Failed to write the metadata of run {}: {err}", self.metadata.id);
+        }
+        if let Err(err) = compress_log(&self.runs_dir, &self.metadata.id) {
+            wlog!("Failed to compress the log of run {}: {err}", self.metadata.id);
+        }
+        duration_secs
+    }
+}
+
+/// Compresses the logs of runs whose game outlived the launcher, their exit status is unknown
+///
+/// only called before launching, when no game of this instance is running
+fn finish_stale_runs(instance_dir: &Path) {
+    let runs_dir = runs_dir(instance_dir);
+    for run in list_runs(instance_dir).unwrap_or_default() {
+        if live_log_path(&runs_dir, &run.id).exists() {
+            if let Err(err) = compress_log(&runs_dir, &run.id) {
+                wlog!("Failed to compress the log of run {}: {err}", run.id);
+            }
+        }
+    }
+}
+
+/// Removes the oldest runs until only `keep` are left
+fn prune_runs(instance_dir: &Path, keep: usize) {
+    let runs_dir = runs_dir(instance_dir);
+    for run in list_runs(instance_dir).unwrap_or_default().iter().skip(keep) {
+        dlog!("Removing old run {}", run.id);
+        for path in [
+            metadata_path(&runs_dir, &run.id),
+            live_log_path(&runs_dir, &run.id),
+            compressed_log_path(&runs_dir, &run.id),
+        ] {
+            _ = fs::remove_file(path);
+        }
+    }
+}
@@ -1,25 +1,30 @@
 use sl_java_manager::jre_manifest::JreManifest;
+use sl_utils::errors::HttpError;
 use std::fs::{self};
 
 use crate::{JRE_MANIFEST_PATH, REQUESTER};
 
 const JRE_MANIFEST_DOWNLOAD_URL: &str = "https://launchermeta.mojang.com/v1/products/java-runtime/2ec0cc96c44e5a76b9c8b7c39df7210883d12871/all.json";
 
-pub(crate) async fn fetch_jre_manifest() {
+pub(crate) async fn fetch_jre_manifest() -> Result<(), HttpError> {
     let res = REQUESTER
         .builder()
         .download(JRE_MANIFEST_DOWNLOAD_URL)
-        .await;
+        .await?;
 
-    if let Ok(res) = res {
-        tokio::fs::write(&JRE_MANIFEST_PATH.as_path(), res)
-            .await
-            .expect("Failed writing into the file: jre_manifest.json");
-    }
+    tokio::fs::write(&JRE_MANIFEST_PATH.as_path(), res).await?;
+    Ok(())
 }
 
-pub fn read_jre_manifest() -> JreManifest {
-    let buffer = fs::read_to_string(&JRE_MANIFEST_PATH.as_path())
-        .expect("Failed reading the file: jre_manifest.json");
-    serde_json::from_str(buffer.as_str()).expect("Failed to parse file: jre_manifest.json")
+/// Reads the cached JRE manifest, which is only needed to download a Java
+pub fn read_jre_manifest() -> Result<JreManifest, HttpError> {
+    let path = JRE_MANIFEST_PATH.as_path();
+    if !path.exists() {
+        return Err(HttpError::OfflineMissingFile(path.to_path_buf()));
+    }
+
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|buffer| serde_json::from_str(&buffer).ok())
+        .ok_or_else(|| HttpError::UnreadableCachedFile(path.to_path_buf()))
 }
@@ -0,0 +1,194 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use sl_utils::{errors::SchemaError, log};
+
+/// Upgrades a document by a single scheme version
+pub(crate) type Migration = fn(&mut Value);
+
+/// A JSON document stored by the launcher, which carries the `scheme_version` it was written with
+pub(crate) trait Versioned: Serialize + DeserializeOwned {
+    /// `MIGRATIONS[n]` upgrades a document from scheme version `n` to `n + 1`
+    const MIGRATIONS: &'static [Migration];
+    /// The scheme version of the documents written by this version of the launcher
+    const SCHEME_VERSION: u32 = Self::MIGRATIONS.len() as u32;
+}
+
+fn backup_path(path: &Path, version: u64) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!(".v{version}.bak"));
+    path.with_file_name(file_name)
+}
+
+/// Reads the document at `path`, upgrading it step by step to the current scheme version if it is older
+///
+/// the original is backed up as `<file name>.v<version>.bak` before the upgraded document is written over it,
+/// documents written by a newer version of the launcher are refused
+pub(crate) fn load_versioned<T: Versioned>(path: &Path) -> Result<T, SchemaError> {
+    let corrupted = |source| SchemaError::Corrupted {
+        path: path.to_path_buf(),
+        source,
+    };
+
+    let content = fs::read_to_string(path)?;
+    let mut value: Value = serde_json::from_str(&content).map_err(corrupted)?;
+
+    let version = value
+        .get("scheme_version")
+        .and_then(Value::as_u64)
+        .unwrap_or(0);
+    let current = T::SCHEME_VERSION as u64;
+
+    if version > current {
+        return Err(SchemaError::UnsupportedVersion {
+            path: path.to_path_buf(),
+            found: version,
+            supported: current,
+        });
+    }
+
+    if version < current {
+        let backup = backup_path(path, version);
+        fs::copy(path, &backup)?;
+        log!(
+            "Migrating {} from scheme version {version} to {current}, the original was backed up to {}",
+            path.display(),
+            backup.display()
+        );
+
+        for migration in &T::MIGRATIONS[version as usize..] {
+            migration(&mut value);
+        }
+
+        if let Some(document) = value.as_object_mut() {
+            document.insert("scheme_version".to_string(), current.into());
+        }
+    }
+
+    let document: T = serde_json::from_value(value).map_err(corrupted)?;
+    if version < current {
+        save_versioned(path, &document)?;
+    }
+
+    Ok(document)
+}
+
+/// Writes `document` to `path` through a temporary file, so a crash never leaves it half written
+pub(crate) fn save_versioned<T: Versioned>(path: &Path, document: &T) -> std::io::Result<()> {
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    let content = serde_json::to_vec_pretty(document)?;
+    fs::write(&temp_path, content)?;
+    fs::rename(temp_path, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    /// Version 0 had `title`, version 1 renamed it to `name` and version 2 added `tags`
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Document {
+        scheme_version: u32,
+        name: String,
+        tags: Vec<String>,
+    }
+
+    impl Versioned for Document {
+        const MIGRATIONS: &'static [Migration] = &[
+            |value| {
+                if let Some(title) = value.as_object_mut().and_then(|doc| doc.remove("title")) {
+                    value["name"] = title;
+                }
+            },
+            |value| value["tags"] = Value::Array(Vec::new()),
+        ];
+    }
+
+    #[test]
+    fn migrates_step_by_step_and_backs_up_the_original() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("document.json");
+        let original = r#"{"title": "Old"}"#;
+        fs::write(&path, original).unwrap();
+
+        let document: Document = load_versioned(&path).unwrap();
+        assert_eq!(document.scheme_version, 2);
+        assert_eq!(document.name, "Old");
+        assert!(document.tags.is_empty());
+
+        let backup = temp.path().join("document.json.v0.bak");
+        assert_eq!(fs::read_to_string(backup).unwrap(), original);
+
+        let saved: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["scheme_version"], 2);
+        assert_eq!(saved["name"], "Old");
+    }
+
+    #[test]
+    fn migrates_from_an_intermediate_version() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("document.json");
+        fs::write(&path, r#"{"scheme_version": 1, "name": "Mid"}"#).unwrap();
+
+        let document: Document = load_versioned(&path).unwrap();
+        assert_eq!(document.name, "Mid");
+        assert!(temp.path().join("document.json.v1.bak").exists());
+        assert!(!temp.path().join("document.json.v0.bak").exists());
+    }
+
+    #[test]
+    fn current_documents_are_left_alone() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("document.json");
+        let document = Document {
+            scheme_version: Document::SCHEME_VERSION,
+            name: "Current".to_string(),
+            tags: vec!["a".to_string()],
+        };
+        save_versioned(&path, &document).unwrap();
+        assert!(!temp.path().join("document.json.tmp").exists());
+
+        let loaded: Document = load_versioned(&path).unwrap();
+        assert_eq!(loaded.name, "Current");
+        assert_eq!(loaded.tags, ["a"]);
+        assert_eq!(fs::read_dir(temp.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn refuses_newer_versions() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("document.json");
+        let newer = r#"{"scheme_version": 3, "name": "New", "tags": []}"#;
+        fs::write(&path, newer).unwrap();
+
+        let err = load_versioned::<Document>(&path).unwrap_err();
+        assert!(matches!(
+            err,
+            SchemaError::UnsupportedVersion {
+                found: 3,
+                supported: 2,
+                ..
+            }
+        ));
+        assert_eq!(fs::read_to_string(&path).unwrap(), newer);
+    }
+
+    #[test]
+    fn reports_corrupted_documents() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("document.json");
+        fs::write(&path, "{ not json").unwrap();
+
+        let err = load_versioned::<Document>(&path).unwrap_err();
+        assert!(matches!(err, SchemaError::Corrupted { .. }));
+    }
+}
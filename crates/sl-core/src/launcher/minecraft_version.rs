@@ -1,5 +1,5 @@
 use crate::{
-    launcher::instances::instance_config::{read_instance_config, InstanceConfig}, loaders::{
+    launcher::{ensure_online_for, instances::instance_config::{read_instance_config, InstanceConfig}}, loaders::{
         fabric::install_fabric_loader, forge::install_forge_loader,
         neoforge::install_neoforge_loader, quilt::install_quilt_loader, Loaders,
     }, minecraft::version_manifest::download_version_json, VERSIONS_DIR
@@ -7,7 +7,7 @@ use crate::{
 
 use super::instances::instance_metadata::ModLoader;
 use sl_meta::minecraft::loaders::vanilla::Client;
-use sl_utils::{dlog, errors::BackendError};
+use sl_utils::{dlog, errors::BackendError, progress::Progress};
 use std::{
     fs::OpenOptions,
     io::BufReader,
@@ -81,8 +81,9 @@ impl MinecraftVersionID {
         &self,
         instance_dir: &Path,
         vanilla_client: &Client,
+        progress: &Progress,
     ) -> Result<InstanceConfig, BackendError> {
-        read_instance_config(instance_dir, &vanilla_client.java_version.component)
+        read_instance_config(instance_dir, &vanilla_client.java_version.component, progress)
             .await
     }
 
@@ -91,10 +92,15 @@ impl MinecraftVersionID {
         modloader_json_path: &Path,
         java_path: &Path,
         javac_path: &Path,
+        progress: &Progress,
     ) -> Result<Loaders, BackendError> {
         assert!(!self.loader_version.is_empty() || self.loader == ModLoader::Vanilla);
         let loader_version = &self.loader_version;
 
+        if self.loader != ModLoader::Vanilla {
+            ensure_online_for(modloader_json_path)?;
+        }
+
         match self.loader {
             ModLoader::Vanilla => Ok(Loaders::Vanilla),
             ModLoader::NeoForge => install_neoforge_loader(
@@ -103,6 +109,7 @@ impl MinecraftVersionID {
                 java_path,
                 javac_path,
                 modloader_json_path,
+                progress,
             )
             .await
             .map(|ok| Loaders::NeoForge(ok)),
@@ -122,6 +129,7 @@ impl MinecraftVersionID {
                 java_path,
                 javac_path,
                 modloader_json_path,
+                progress,
             )
             .await
             .map(|ok| Loaders::Forge(ok)),
@@ -139,6 +147,7 @@ impl MinecraftVersionID {
         modloader_json_path: &Path,
         java_path: &Path,
         javac_path: &Path,
+        progress: &Progress,
     ) -> Result<Loaders, BackendError> {
         if self.loader == ModLoader::Vanilla {
             return Ok(Loaders::Vanilla);
@@ -147,7 +156,7 @@ impl MinecraftVersionID {
         match self.try_get_modloader(modloader_json_path) {
             Some(results) => Ok(results),
             None => {
-                self.reinit_modloader(&modloader_json_path, java_path, javac_path)
+                self.reinit_modloader(modloader_json_path, java_path, javac_path, progress)
                     .await
             }
         }
@@ -158,6 +167,7 @@ impl MinecraftVersionID {
         vanilla_json_path: &Path,
     ) -> Result<Client, BackendError> {
         dlog!("Re-initializing the instance");
+        ensure_online_for(vanilla_json_path)?;
 
         let vanilla_json_file = OpenOptions::new()
             .create(true)
@@ -180,6 +190,7 @@ impl MinecraftVersionID {
     pub async fn load_init(
         mut self,
         instance_dir: &Path,
+        progress: &Progress,
     ) -> Result<(LoadedMinecraftVersion, InstanceConfig), BackendError> {
         let dir_path = self.dir_path();
         std::fs::create_dir_all(&dir_path)?;
@@ -189,13 +200,15 @@ impl MinecraftVersionID {
         let modloader_json_path = self.modloader_json_path(&dir_path);
 
         let vanilla_client = self.init_vanilla_json(&vanilla_json_path).await?;
-        let config = self.load_config(&instance_dir, &vanilla_client).await?;
+        let config = self
+            .load_config(instance_dir, &vanilla_client, progress)
+            .await?;
 
         let java_path = config.java.java();
         let javac_path = &config.java.get_javac();
 
         let mod_loader = self
-            .init_mod_loader(&modloader_json_path, java_path, javac_path)
+            .init_mod_loader(&modloader_json_path, java_path, javac_path, progress)
             .await?;
 
         let minecraft_client_json = mod_loader.concat(vanilla_client);
@@ -1,22 +1,46 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::{env, fs::OpenOptions};
 
-use sl_utils::{dlog, log};
-use sl_utils::errors::BackendError;
+use sl_utils::{dlog, log, wlog};
+use sl_utils::errors::{BackendError, HttpError};
 use sl_utils::log::set_log_file;
 
 use crate::launcher::java::fetch_jre_manifest;
-use crate::minecraft::version_manifest::fetch_version_manifest;
+use crate::minecraft::version_manifest::{fetch_version_manifest, load_version_manifest};
 use crate::{
-    ADDONS_DIR, ASSETS_DIR, INSTANCES_DIR, JAVAS_DIR, LAUNCHER_DIR, LIBS_DIR,
-    PROFILES_PATH,
+    ADDONS_DIR, ASSETS_DIR, BACKUPS_DIR, GAME_PROCESSES, INSTANCES_DIR, JAVAS_DIR, LAUNCHER_DIR,
+    LIBS_DIR, PROFILES_PATH,
 };
 
+pub mod backups;
+pub mod game_process;
 pub mod instances;
 pub mod java;
+mod migrations;
 pub mod minecraft_version;
 pub mod player_accounts;
 
+static OFFLINE_MODE: AtomicBool = AtomicBool::new(false);
+
+/// Forces (or stops forcing) the launcher to work only from cached manifests and local files
+pub fn set_offline_mode(offline: bool) {
+    OFFLINE_MODE.store(offline, Ordering::Relaxed);
+}
+
+/// Whether the launcher is in offline mode, either forced or because the network was unreachable
+pub fn is_offline_mode() -> bool {
+    OFFLINE_MODE.load(Ordering::Relaxed)
+}
+
+/// Errors if the launcher is in offline mode, must be called before any network operation that produces `path`
+pub(crate) fn ensure_online_for(path: &Path) -> Result<(), HttpError> {
+    if is_offline_mode() {
+        return Err(HttpError::OfflineMissingFile(path.to_path_buf()));
+    }
+    Ok(())
+}
+
 pub(crate) fn get_launcher_dir() -> PathBuf {
     #[cfg(target_os = "windows")]
     {
@@ -54,6 +78,7 @@ pub async fn init_launcher_dir() -> Result<(), BackendError> {
         &(*INSTANCES_DIR),
         &(*JAVAS_DIR),
         &(*ADDONS_DIR),
+        &(*BACKUPS_DIR),
     ] {
         dlog!("{} dir initialized!", &dir.display());
         tokio::fs::create_dir_all(dir).await?;
@@ -68,13 +93,36 @@ pub async fn init_launcher_dir() -> Result<(), BackendError> {
             .open(path)?;
     }
 
-    dlog!("Fetching version manifest!");
-    fetch_version_manifest().await;
-    dlog!("Fetched version manifest!");
+    if !is_offline_mode() {
+        dlog!("Fetching version manifest!");
+        if let Err(err) = fetch_version_manifest().await {
+            wlog!("Failed to fetch the version manifest: {err}, switching to offline mode");
+            set_offline_mode(true);
+        } else {
+            dlog!("Fetched version manifest!");
+        }
+    }
+
+    if !is_offline_mode() {
+        dlog!("Fetching JRE manifest!");
+        if let Err(err) = fetch_jre_manifest().await {
+            wlog!("Failed to fetch the JRE manifest: {err}, switching to offline mode");
+            set_offline_mode(true);
+        } else {
+            dlog!("Fetched JRE manifest!");
+        }
+    }
+
+    // everything else reads the cached version manifest, so it has to be there even when offline
+    load_version_manifest()?;
+
+    if is_offline_mode() {
+        log!("Running in offline mode, only cached files will be used");
+    }
 
-    dlog!("Fetching JRE manifest!");
-    fetch_jre_manifest().await;
-    dlog!("Fetched JRE manifest!");
+    if let Err(err) = GAME_PROCESSES.reattach() {
+        wlog!("Failed to look for instances that are still running: {err}");
+    }
 
     let log_file_path = LAUNCHER_DIR.join("last_run.log");
     set_log_file(log_file_path);
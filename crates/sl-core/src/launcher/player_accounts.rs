@@ -1,9 +1,13 @@
-use std::{collections::HashMap, fs::OpenOptions};
+use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 use sl_player::PlayerData;
+use sl_utils::errors::SchemaError;
 
-use crate::PROFILES_PATH;
+use crate::{
+    launcher::migrations::{load_versioned, save_versioned, Migration, Versioned},
+    PROFILES_PATH,
+};
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct PlayerAccounts {
@@ -17,7 +21,7 @@ impl PlayerAccounts {
         PlayerAccounts {
             current_account: String::new(),
             accounts: HashMap::new(),
-            scheme_version: 0
+            scheme_version: Self::SCHEME_VERSION,
         }
     }
 
@@ -34,24 +38,26 @@ impl PlayerAccounts {
         (name.as_str(), data)
     }
 
-    pub fn load() -> std::io::Result<Self> {
-        let content = std::fs::read_to_string(&PROFILES_PATH.as_path())?;
-        serde_json::from_str(&content).or_else(|_| Ok(Self::new()))
+    /// Loads the accounts, migrating them from an older scheme version if needed
+    pub fn load() -> Result<Self, SchemaError> {
+        // the file is created empty when the launcher dir is initialized
+        if std::fs::metadata(PROFILES_PATH.as_path())?.len() == 0 {
+            return Ok(Self::new());
+        }
+
+        load_versioned(&PROFILES_PATH)
     }
 
     pub fn save(new_accounts: &PlayerAccounts) -> std::io::Result<()> {
-        let file = OpenOptions::new()
-            .write(true)
-            .truncate(true)
-            .open(PROFILES_PATH.as_path())?;
-
-        serde_json::to_writer_pretty(file, &new_accounts)?;
-
-        Ok(())
+        save_versioned(&PROFILES_PATH, new_accounts)
     }
 }
 
-pub fn add_account(data: PlayerData, name: String) -> std::io::Result<()> {
+impl Versioned for PlayerAccounts {
+    const MIGRATIONS: &'static [Migration] = &[];
+}
+
+pub fn add_account(data: PlayerData, name: String) -> Result<(), SchemaError> {
     let mut accounts = PlayerAccounts::load()?;
     accounts.current_account = name.clone();
     accounts.accounts.insert(name, data);
@@ -60,7 +66,7 @@ pub fn add_account(data: PlayerData, name: String) -> std::io::Result<()> {
     Ok(())
 }
 
-pub fn remove_account(name: &str) -> std::io::Result<()> {
+pub fn remove_account(name: &str) -> Result<(), SchemaError> {
     let mut accounts = PlayerAccounts::load()?;
     accounts.accounts.remove(name);
 
@@ -77,7 +83,7 @@ pub fn remove_account(name: &str) -> std::io::Result<()> {
     Ok(())
 }
 
-pub fn set_current_account(name: String) -> std::io::Result<()> {
+pub fn set_current_account(name: String) -> Result<(), SchemaError> {
     let mut accounts = PlayerAccounts::load()?;
     if accounts.accounts.contains_key(&name) {
         accounts.current_account = name;
@@ -1,7 +1,6 @@
-use launcher::java::read_jre_manifest;
+use launcher::game_process::GameProcessManager;
 use lazy_static::lazy_static;
 use minecraft::version_manifest::read_version_manifest;
-use sl_java_manager::jre_manifest::JreManifest;
 use sl_meta::minecraft::{version_manifest::VersionManifest, Arch, OsName};
 use sl_utils::requester::Requester;
 use std::path::PathBuf;
@@ -40,12 +39,14 @@ lazy_static! {
     pub static ref VERSIONS_DIR: PathBuf = LAUNCHER_DIR.join("versions");
     pub static ref JAVAS_DIR: PathBuf = LAUNCHER_DIR.join("javas");
     pub static ref ADDONS_DIR: PathBuf = LAUNCHER_DIR.join("addons");
+    pub static ref BACKUPS_DIR: PathBuf = LAUNCHER_DIR.join("backups");
 
     pub static ref VERSION_MANIFEST_PATH: PathBuf = LAUNCHER_DIR.join("version_manifest.json");
     pub static ref JRE_MANIFEST_PATH: PathBuf = LAUNCHER_DIR.join("jre_manifest.json");
     pub static ref PROFILES_PATH: PathBuf = LAUNCHER_DIR.join("profiles.json");
 
-    pub static ref JRE_MANIFEST: JreManifest = read_jre_manifest();
-    pub static ref VERSION_MANIFEST: VersionManifest = read_version_manifest();
+    /// Only available once [`init_launcher_dir`](launcher::init_launcher_dir) succeeded
+    pub static ref VERSION_MANIFEST: &'static VersionManifest = read_version_manifest();
     pub static ref REQUESTER: Requester = Requester::new();
+    pub static ref GAME_PROCESSES: GameProcessManager = GameProcessManager::new();
 }
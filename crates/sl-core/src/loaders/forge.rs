@@ -4,6 +4,7 @@ use sl_utils::{
     dlog, elog, log,
     {
         errors::{BackendError, ForgeInstallerErr, HttpError, InstanceError},
+        progress::{Phase, PhaseTracker, Progress},
     },
 };
 use std::{
@@ -34,6 +35,7 @@ struct ForgeInstaller<'a> {
     cache_dir: TempDir,
     // ForgeInstaller.java
     java_forge_installer: PathBuf,
+    progress: Progress,
 }
 
 impl<'a> ForgeInstaller<'a> {
@@ -43,6 +45,7 @@ impl<'a> ForgeInstaller<'a> {
         java_path: &'a Path,
         javac_path: &'a Path,
         output_loader_json_path: &'a Path,
+        progress: &Progress,
     ) -> Result<Self, HttpError> {
         dlog!("Forge: choose forge version {forge_version} for minecraft version: {mc_version}");
 
@@ -91,6 +94,7 @@ impl<'a> ForgeInstaller<'a> {
             java_path,
             javac_path,
             output_loader_json_path,
+            progress: progress.clone(),
         })
     }
 
@@ -140,6 +144,7 @@ impl<'a> ForgeInstaller<'a> {
         for url in urls {
             let downloaded = REQUESTER
                 .builder()
+                .progress(&self.progress, Phase::ForgeInstaller)
                 .download_to(&url, &path)
                 .await;
 
@@ -203,8 +208,9 @@ impl<'a> ForgeInstaller<'a> {
         Ok((classpath, compiled_file))
     }
 
-    async fn install_to_cache(&self) -> Result<(), ForgeInstallerErr> {
+    async fn install_to_cache(&self, tracker: &PhaseTracker<'_>) -> Result<(), ForgeInstallerErr> {
         let (classpath, compiled_path) = self.compile_installer().await?;
+        tracker.item_finished();
         // Create files to trick forge into thinking the cache dir is the launcher root
         let mut launcher_profiles =
             tokio::fs::File::create_new(self.cache_dir.path().join("launcher_profiles.json"))
@@ -250,7 +256,10 @@ impl<'a> ForgeInstaller<'a> {
             self.mc_version,
         );
 
-        self.install_to_cache().await?;
+        // downloading and compiling, running the installer, copying the results
+        let tracker = self.progress.start(Phase::ForgeInstaller, 3);
+        self.install_to_cache(&tracker).await?;
+        tracker.item_finished();
 
         let cache_dir = self.cache_dir.path();
         let forge_libraries_path = cache_dir.join("libraries");
@@ -288,6 +297,8 @@ impl<'a> ForgeInstaller<'a> {
         let loader_json_instance = serde_json::from_reader(loader_json_reader)
             .map_err(|e| Into::<std::io::Error>::into(e))?;
 
+        tracker.item_finished();
+        tracker.finish();
         log!("Forge: Installed successfully!");
         Ok(loader_json_instance)
     }
@@ -299,6 +310,7 @@ pub async fn install_forge_loader(
     java_path: &Path,
     javac_path: &Path,
     output_loader_json_path: &Path,
+    progress: &Progress,
 ) -> Result<ForgeLoaderProfile, BackendError> {
     ForgeInstaller::new(
         mc_version,
@@ -306,6 +318,7 @@ pub async fn install_forge_loader(
         java_path,
         javac_path,
         output_loader_json_path,
+        progress,
     )
     .await?
     .install()
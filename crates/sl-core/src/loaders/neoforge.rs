@@ -10,6 +10,7 @@ use sl_utils::{
     dlog, log,
     {
         errors::{BackendError, ForgeInstallerErr, HttpError, InstanceError},
+        progress::{Phase, PhaseTracker, Progress},
     },
 };
 use tempfile::TempDir;
@@ -31,6 +32,7 @@ pub struct NeoForgeInstaller<'a> {
     cache_dir: TempDir,
     // ForgeInstaller.java
     java_forge_installer: PathBuf,
+    progress: Progress,
 }
 
 impl<'a> NeoForgeInstaller<'a> {
@@ -40,6 +42,7 @@ impl<'a> NeoForgeInstaller<'a> {
         java_path: &'a Path,
         javac_path: &'a Path,
         output_loader_json_path: &'a Path,
+        progress: &Progress,
     ) -> Result<Self, HttpError> {
         let neoforge_version = NeoForgeVersion::from_str(neoforge_version);
 
@@ -60,6 +63,7 @@ impl<'a> NeoForgeInstaller<'a> {
             javac_path,
             output_loader_json_path,
             java_forge_installer,
+            progress: progress.clone(),
         })
     }
 
@@ -74,6 +78,7 @@ impl<'a> NeoForgeInstaller<'a> {
 
         REQUESTER
             .builder()
+            .progress(&self.progress, Phase::NeoForgeInstaller)
             .download_to(&url, &installer_path)
             .await?;
 
@@ -120,8 +125,9 @@ impl<'a> NeoForgeInstaller<'a> {
         Ok((classpath, compiled_file))
     }
 
-    async fn install_to_cache(&self) -> Result<(), ForgeInstallerErr> {
+    async fn install_to_cache(&self, tracker: &PhaseTracker<'_>) -> Result<(), ForgeInstallerErr> {
         let (classpath, compiled_installer_path) = self.compile_installer().await?;
+        tracker.item_finished();
 
         // Create files to trick forge into thinking the cache dir is the launcher root
         let mut launcher_profiles =
@@ -172,7 +178,10 @@ impl<'a> NeoForgeInstaller<'a> {
             self.minecraft_version,
         );
 
-        self.install_to_cache().await?;
+        // downloading and compiling, running the installer, copying the results
+        let tracker = self.progress.start(Phase::NeoForgeInstaller, 3);
+        self.install_to_cache(&tracker).await?;
+        tracker.item_finished();
 
         let cache_dir = self.cache_dir.path();
         let neoforge_libraries_path = cache_dir.join("libraries");
@@ -210,6 +219,8 @@ impl<'a> NeoForgeInstaller<'a> {
         let loader_json_instance = serde_json::from_reader(loader_json_reader)
             .map_err(|e| Into::<std::io::Error>::into(e))?;
 
+        tracker.item_finished();
+        tracker.finish();
         log!("NeoForge: Installed successfully!");
         Ok(loader_json_instance)
     }
@@ -221,6 +232,7 @@ pub async fn install_neoforge_loader(
     java_path: &Path,
     javac_path: &Path,
     output_loader_json_path: &Path,
+    progress: &Progress,
 ) -> Result<NeoForgeLoaderProfile, BackendError> {
     NeoForgeInstaller::new(
         minecraft_version,
@@ -228,6 +240,7 @@ pub async fn install_neoforge_loader(
         java_path,
         javac_path,
         output_loader_json_path,
+        progress,
     )
     .await?
     .install()
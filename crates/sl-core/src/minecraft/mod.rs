@@ -1,95 +1,187 @@
 use std::{
-    io::Cursor,
+    io::{BufReader, Cursor},
     path::{Path, PathBuf},
     time::Instant,
 };
 
 use bytes::Bytes;
 use futures::{stream::FuturesUnordered, StreamExt};
+use sha1::{Digest, Sha1};
 
 use sl_meta::minecraft::loaders::vanilla::{AssetIndex, AssetObject, Client, Download, Library};
 use sl_utils::{
-    elog, errors::{BackendError, HttpError}, log, zip::ZipExtractor
+    dlog, elog,
+    errors::{BackendError, HttpError},
+    log,
+    progress::{Phase, Progress},
+    zip::ZipExtractor,
 };
 
-use crate::{ASSETS_DIR, LIBS_DIR, REQUESTER};
+use crate::{launcher::ensure_online_for, ASSETS_DIR, LIBS_DIR, REQUESTER};
 
 pub mod version_manifest;
 
-// TODO: Implement verify_data function that is fast enough, this one is really slow so i removed it and replaced it with verifying size
-// #[inline(always)]
-// async fn verify_data(file: &mut tokio::fs::File, sha1: &str) -> bool {
-//     let mut hasher = Sha1::new();
-//     let mut buffer = [0u8; 8192];
-
-//     loop {
-//         match file.read(&mut buffer).await {
-//             Ok(0) => break, // EOF
-//             Ok(n) => hasher.update(&buffer[..n]),
-//             Err(_) => return false,
-//         }
-//     }
+/// How thoroughly files that already exist on disk are checked before being trusted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verification {
+    /// Hashes libraries and the client jar, only compares sizes for the (many) asset objects
+    Default,
+    /// Hashes every file, used when repairing an instance
+    Full,
+}
 
-//     let hash = hasher.finalize();
-//     hash.as_slice() == sha1.as_bytes()
-// }
+/// The state a file was found in before it was (re)downloaded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileState {
+    Valid,
+    Missing,
+    Corrupted,
+}
 
-#[inline(always)]
-async fn download_and_verify(download: &Download, path: &Path) -> Result<(), HttpError> {
-    if let Ok(f) = tokio::fs::File::open(path).await {
-        let valid = match download.size {
-            Some(size) => {
-                let f_metadata = f.metadata().await;
-                let f_size = f_metadata.map(|m| m.len()).ok();
-                f_size.is_none_or(|f_size| f_size == size as u64)
-            }
-            None => true,
-        };
+/// Summary of the files checked by [`install_client`]
+#[derive(Debug, Default)]
+pub struct VerificationReport {
+    pub checked: usize,
+    pub missing: Vec<PathBuf>,
+    pub corrupted: Vec<PathBuf>,
+}
 
-        if valid {
-            return Ok(());
+impl VerificationReport {
+    fn record(&mut self, (path, state): (PathBuf, FileState)) {
+        self.checked += 1;
+        match state {
+            FileState::Valid => {}
+            FileState::Missing => self.missing.push(path),
+            FileState::Corrupted => self.corrupted.push(path),
         }
     }
 
+    fn merge(&mut self, other: VerificationReport) {
+        self.checked += other.checked;
+        self.missing.extend(other.missing);
+        self.corrupted.extend(other.corrupted);
+    }
+
+    /// The amount of files that had to be downloaded again
+    pub fn fixed(&self) -> usize {
+        self.missing.len() + self.corrupted.len()
+    }
+}
+
+/// Streams the file at `path` through SHA-1 on the blocking thread pool, so many files can be hashed in parallel
+async fn verify_data(path: &Path, sha1: &str) -> bool {
+    let path = path.to_path_buf();
+    let sha1 = sha1.to_owned();
+
+    let hashed = tokio::task::spawn_blocking(move || -> std::io::Result<bool> {
+        let mut reader = BufReader::new(std::fs::File::open(path)?);
+        let mut hasher = Sha1::new();
+        std::io::copy(&mut reader, &mut hasher)?;
+
+        let hash = hex::encode(hasher.finalize());
+        Ok(hash.eq_ignore_ascii_case(&sha1))
+    })
+    .await;
+
+    matches!(hashed, Ok(Ok(true)))
+}
+
+/// Checks an existing file against the given size and SHA-1 (when available) without downloading anything
+async fn check_file(path: &Path, sha1: Option<&str>, size: Option<u64>) -> FileState {
+    let Ok(metadata) = tokio::fs::metadata(path).await else {
+        return FileState::Missing;
+    };
+
+    if size.is_some_and(|size| size != metadata.len()) {
+        return FileState::Corrupted;
+    }
+
+    match sha1 {
+        Some(sha1) if !verify_data(path, sha1).await => FileState::Corrupted,
+        _ => FileState::Valid,
+    }
+}
+
+/// Downloads `url` to `path`, verifying the result against `sha1` if given
+///
+/// the downloaded bytes are reported to `bytes_progress` if given
+async fn fetch_verified(
+    url: &str,
+    path: &Path,
+    sha1: Option<&str>,
+    bytes_progress: Option<(&Progress, Phase)>,
+) -> Result<(), HttpError> {
+    ensure_online_for(path)?;
+
     if let Some(parent) = path.parent() {
         tokio::fs::create_dir_all(parent).await?;
     }
 
-    REQUESTER
-        .builder()
-        .download_to(&download.url, &path)
-        .await?;
+    let mut request = REQUESTER.builder();
+    if let Some((progress, phase)) = bytes_progress {
+        request = request.progress(progress, phase);
+    }
+    request.download_to(url, path).await?;
+
+    if let Some(sha1) = sha1 {
+        if !verify_data(path, sha1).await {
+            return Err(HttpError::HashMismatch(url.to_string()));
+        }
+    }
 
     Ok(())
 }
 
-async fn download_and_read_file(download: &Download, path: &Path) -> Result<Bytes, HttpError> {
-    let full_path = if let Some(ref child) = download.path {
-        &path.join(child)
-    } else {
-        path
-    };
+async fn download_and_verify(
+    download: &Download,
+    path: &Path,
+    bytes_progress: Option<(&Progress, Phase)>,
+) -> Result<FileState, HttpError> {
+    let sha1 = download.sha1.as_deref();
+    let size = download.size.map(|size| size as u64);
+
+    let state = check_file(path, sha1, size).await;
+    if state != FileState::Valid {
+        fetch_verified(&download.url, path, sha1, bytes_progress).await?;
+    }
+
+    Ok(state)
+}
 
-    download_and_verify(download, full_path).await?;
-    Ok(Bytes::from(
-        tokio::fs::read(full_path)
+fn download_path(download: &Download, path: &Path) -> PathBuf {
+    match download.path {
+        Some(ref child) => path.join(child),
+        None => path.to_path_buf(),
+    }
+}
+
+async fn download_and_read_file(
+    download: &Download,
+    path: &Path,
+) -> Result<(Bytes, (PathBuf, FileState)), HttpError> {
+    let (full_path, state) = download_to(download, path, None).await?;
+
+    let bytes = Bytes::from(
+        tokio::fs::read(&full_path)
             .await
             .expect("get_download_in: failed to read downloaded file"),
-    ))
+    );
+    Ok((bytes, (full_path, state)))
 }
 
-async fn download_to(download: &Download, path: &Path) -> Result<(), HttpError> {
+async fn download_to(
+    download: &Download,
+    path: &Path,
+    bytes_progress: Option<(&Progress, Phase)>,
+) -> Result<(PathBuf, FileState), HttpError> {
+    let full_path = download_path(download, path);
+
     if download.url.is_empty() {
-        return Ok(());
+        return Ok((full_path, FileState::Valid));
     }
 
-    let full_path = if let Some(ref child) = download.path {
-        &path.join(child)
-    } else {
-        path
-    };
-
-    download_and_verify(download, full_path).await
+    let state = download_and_verify(download, &full_path, bytes_progress).await?;
+    Ok((full_path, state))
 }
 
 #[inline(always)]
@@ -124,65 +216,83 @@ where
 }
 
 // This could be made faster maybe
-async fn install_assets(client: &Client) -> Result<(), BackendError> {
+async fn install_assets(
+    client: &Client,
+    verification: Verification,
+    progress: &Progress,
+) -> Result<VerificationReport, BackendError> {
     log!("Downloading assets!");
     let assets = &client.assets;
     let indexes_dir = ASSETS_DIR.join("indexes");
     let indexes_path = indexes_dir.join(format!("{}.json", assets));
 
-    let download = download_and_read_file(&client.asset_index, &indexes_path).await?;
+    let mut report = VerificationReport::default();
+    let (download, checked) = download_and_read_file(&client.asset_index, &indexes_path).await?;
+    report.record(checked);
 
     let index: AssetIndex = serde_json::from_slice(&download)?;
     let objects = index.objects;
+    let tracker = progress.start(Phase::Assets, objects.len());
 
-    let download_object = async |object: AssetObject| -> Result<(), HttpError> {
+    let download_object = async |object: AssetObject| -> Result<(PathBuf, FileState), HttpError> {
         let dir_name = &object.hash[0..2];
-        let dir = ASSETS_DIR.join("objects").join(dir_name);
-        let path = dir.join(&object.hash);
-
-        if path.exists() {
-            return Ok(());
-        }
+        let path = ASSETS_DIR.join("objects").join(dir_name).join(&object.hash);
 
-        tokio::fs::create_dir_all(&dir).await?;
+        // hashing thousands of objects on every launch is too slow, sizes are enough unless repairing
+        let existing_sha1 = (verification == Verification::Full).then_some(object.hash.as_str());
+        let state = check_file(&path, existing_sha1, Some(object.size as u64)).await;
 
-        REQUESTER
-            .builder()
-            .download_to(&format!(
+        if state != FileState::Valid {
+            let url = format!(
                 "https://resources.download.minecraft.net/{dir_name}/{}",
                 object.hash
-            ), &path)
-            .await?;
+            );
+            fetch_verified(&url, &path, Some(&object.hash), None).await?;
+        }
 
-        Ok(())
+        tracker.item_finished();
+        Ok((path, state))
     };
 
     let iter = objects.into_iter();
     let iter = iter.map(|(_, object)| object);
     let outputs = download_futures(iter, 5, download_object).await;
     for (i, output) in outputs.into_iter().enumerate() {
-        if let Err(err) = output {
-            elog!("Failed to download object indexed {i}: {err:?}");
-            return Err(BackendError::HttpError(err));
+        match output {
+            Ok(checked) => report.record(checked),
+            Err(err) => {
+                elog!("Failed to download object indexed {i}: {err:?}");
+                return Err(BackendError::HttpError(err));
+            }
         }
     }
 
+    tracker.finish();
     log!("Downloaded assets for {}", assets);
-    Ok(())
+    Ok(report)
 }
 
-async fn install_libs(client: &Client, path: &Path) -> Result<(), BackendError> {
+async fn install_libs(
+    client: &Client,
+    path: &Path,
+    progress: &Progress,
+) -> Result<VerificationReport, BackendError> {
     log!("Downloading libraries...");
     let path = path.to_path_buf();
+    let libs_tracker = progress.start(Phase::Libraries, client.libraries().count());
+    let tracker = &libs_tracker;
+
+    let download_lib = async move |lib: &Library| -> Result<Vec<(PathBuf, FileState)>, BackendError> {
+        let mut checked = Vec::new();
 
-    let download_lib = async move |lib: &Library| -> Result<(), BackendError> {
         if let Some(ref artifact) = lib.downloads.artifact {
-            download_to(artifact, &LIBS_DIR).await?;
+            checked.push(download_to(artifact, &LIBS_DIR, None).await?);
         }
 
         if let Some(native) = lib.native_from_platform() {
             // FIXME: this is so terrible just download and return a reader at least
-            let bytes = download_and_read_file(native, &LIBS_DIR).await?;
+            let (bytes, native_checked) = download_and_read_file(native, &LIBS_DIR).await?;
+            checked.push(native_checked);
 
             if let Some(ref extract_rules) = lib.extract {
                 let natives_dir = path.join(".natives");
@@ -196,35 +306,51 @@ async fn install_libs(client: &Client, path: &Path) -> Result<(), BackendError>
                 zip.extract(&natives_dir)?;
             }
         }
-        Ok(())
+
+        tracker.item_finished();
+        Ok(checked)
     };
 
+    let mut report = VerificationReport::default();
     let outputs = download_futures(client.libraries(), 10, download_lib).await;
     for (i, output) in outputs.into_iter().enumerate() {
-        if let Err(err) = output {
-            elog!("Failed to download library indexed {i}: {err:?}");
-            return Err(err);
+        match output {
+            Ok(checked) => checked.into_iter().for_each(|c| report.record(c)),
+            Err(err) => {
+                elog!("Failed to download library indexed {i}: {err:?}");
+                return Err(err);
+            }
         }
     }
 
+    libs_tracker.finish();
     log!("Done downloading libraries");
-    Ok(())
+    Ok(report)
 }
 
+/// Downloads the assets, libraries and client jar of `client`, only (re)downloading missing or corrupted files
+///
+/// returns a report of every file that was checked and which ones had to be fixed
 pub(crate) async fn install_client(
     client: &Client,
     client_jar_path: &Path,
     instance_path: &Path,
-) -> Result<(), BackendError> {
+    verification: Verification,
+    progress: &Progress,
+) -> Result<VerificationReport, BackendError> {
     let start = Instant::now();
-    install_assets(client).await?;
-    println!("Assets download time: {:?}", start.elapsed());
+    let mut report = install_assets(client, verification, progress).await?;
+    dlog!("Assets download time: {:?}", start.elapsed());
 
-    install_libs(client, instance_path).await?;
+    report.merge(install_libs(client, instance_path, progress).await?);
 
     log!("Downloading {}", client_jar_path.display());
-    download_to(&client.downloads.client, &client_jar_path).await?;
+    let tracker = progress.start(Phase::ClientJar, 1);
+    let bytes_progress = Some((progress, Phase::ClientJar));
+    report.record(download_to(&client.downloads.client, client_jar_path, bytes_progress).await?);
+    tracker.item_finished();
+    tracker.finish();
     log!("Done downloading {}", client_jar_path.display());
 
-    Ok(())
+    Ok(report)
 }
@@ -1,30 +1,45 @@
-use std::{fs, path::Path};
+use std::{fs, path::Path, sync::OnceLock};
 
 use sl_meta::minecraft::version_manifest::VersionManifest;
-use sl_utils::errors::{BackendError, InstanceError};
+use sl_utils::errors::{BackendError, HttpError, InstanceError};
 
-use crate::{REQUESTER, VERSION_MANIFEST, VERSION_MANIFEST_PATH};
+use crate::{launcher::ensure_online_for, REQUESTER, VERSION_MANIFEST, VERSION_MANIFEST_PATH};
 
 const VERSION_MANIFEST_DOWNLOAD_URL: &str =
     "https://launchermeta.mojang.com/mc/game/version_manifest_v2.json";
 
-pub(crate) async fn fetch_version_manifest() {
+/// Set by [`load_version_manifest`], [`VERSION_MANIFEST`] is read from here
+static LOADED_VERSION_MANIFEST: OnceLock<VersionManifest> = OnceLock::new();
+
+pub(crate) async fn fetch_version_manifest() -> Result<(), HttpError> {
     let res = REQUESTER
         .builder()
         .download(VERSION_MANIFEST_DOWNLOAD_URL)
-        .await;
+        .await?;
+
+    tokio::fs::write(&VERSION_MANIFEST_PATH.as_path(), res).await?;
+    Ok(())
+}
 
-    if let Ok(res) = res {
-        tokio::fs::write(&VERSION_MANIFEST_PATH.as_path(), res)
-            .await
-            .expect("Failed writing into the file: version_manifest.json");
+/// Reads the cached version manifest once, everything else relies on it so the launcher can't start without it
+pub(crate) fn load_version_manifest() -> Result<(), HttpError> {
+    let path = VERSION_MANIFEST_PATH.as_path();
+    if !path.exists() {
+        return Err(HttpError::OfflineMissingFile(path.to_path_buf()));
     }
+
+    let manifest = fs::read_to_string(path)
+        .ok()
+        .and_then(|buffer| serde_json::from_str(&buffer).ok())
+        .ok_or_else(|| HttpError::UnreadableCachedFile(path.to_path_buf()))?;
+    _ = LOADED_VERSION_MANIFEST.set(manifest);
+    Ok(())
 }
 
-pub(crate) fn read_version_manifest() -> VersionManifest {
-    let buffer = fs::read_to_string(VERSION_MANIFEST_PATH.as_path())
-        .expect("Failed reading the file: version_manifest.json");
-    serde_json::from_str(buffer.as_str()).expect("Failed to parse file: version_manifest.json")
+pub(crate) fn read_version_manifest() -> &'static VersionManifest {
+    LOADED_VERSION_MANIFEST
+        .get()
+        .expect("The version manifest is loaded by init_launcher_dir")
 }
 
 /// Downloads the client.json of a given minecraft version
@@ -39,6 +54,8 @@ pub(crate) async fn download_version_json(
         ));
     };
 
+    ensure_online_for(to_path)?;
+
     REQUESTER
         .builder()
         .download_to(&version.url, to_path)
@@ -0,0 +1,54 @@
+use sl_core::launcher::{
+    backups::{self, BackupTrigger, Snapshot},
+    instances,
+};
+
+#[tauri::command]
+pub async fn get_instance_worlds(name: &str) -> Result<Vec<String>, String> {
+    let (instance, _) = instances::get_existing(name).map_err(|e| e.to_string())?;
+    backups::worlds(&instance).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_backups(name: &str, world: Option<String>) -> Result<Vec<Snapshot>, String> {
+    let (instance, _) = instances::get_existing(name).map_err(|e| e.to_string())?;
+    backups::list_snapshots(&instance, world.as_deref()).map_err(|e| e.to_string())
+}
+
+/// Backs up `world`, or every world of the instance if `None`
+#[tauri::command]
+pub async fn create_backup(name: &str, world: Option<String>) -> Result<Vec<Snapshot>, String> {
+    let (instance, _) = instances::get_existing(name).map_err(|e| e.to_string())?;
+    let snapshots = tauri::async_runtime::spawn_blocking(move || match world {
+        Some(world) => backups::backup_world(&instance, &world, BackupTrigger::Manual)
+            .map(|snapshot| vec![snapshot]),
+        None => backups::backup_instance(&instance, BackupTrigger::Manual),
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    snapshots.map_err(|e| e.to_string())
+}
+
+/// Restores the backup `backup`, as the world `target` if given, returns the name of the restored world
+#[tauri::command]
+pub async fn restore_backup(
+    name: &str,
+    backup: String,
+    target: Option<String>,
+) -> Result<String, String> {
+    let (instance, _) = instances::get_existing(name).map_err(|e| e.to_string())?;
+    let restored = tauri::async_runtime::spawn_blocking(move || {
+        backups::restore(&instance, &backup, target.as_deref())
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    restored.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_backup(name: &str, backup: &str) -> Result<(), String> {
+    let (instance, _) = instances::get_existing(name).map_err(|e| e.to_string())?;
+    backups::delete_snapshot(&instance, backup).map_err(|e| e.to_string())
+}
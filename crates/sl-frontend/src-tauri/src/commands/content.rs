@@ -0,0 +1,83 @@
+use sl_core::launcher::instances::{
+    self,
+    content::{self, ContentFile, ContentKind},
+    content_lock::{self, ContentLock, SyncReport},
+};
+use sl_store::modrinth::mod_updater::{self, ModRollback, ModUpdate};
+
+#[tauri::command]
+pub async fn get_content(name: &str, kind: ContentKind) -> Result<Vec<ContentFile>, String> {
+    let (instance, _) = instances::get_existing(name).map_err(|e| e.to_string())?;
+    content::list(&instance, kind).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_content_enabled(
+    name: &str,
+    kind: ContentKind,
+    file_name: &str,
+    enabled: bool,
+) -> Result<(), String> {
+    let (instance, _) = instances::get_existing(name).map_err(|e| e.to_string())?;
+    content::set_enabled(&instance, kind, file_name, enabled)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Enables or disables every file of `kind`, the ones in `except` are set the other way, returns how many were changed
+#[tauri::command]
+pub async fn set_all_content_enabled(
+    name: &str,
+    kind: ContentKind,
+    enabled: bool,
+    except: Vec<String>,
+) -> Result<usize, String> {
+    let (instance, _) = instances::get_existing(name).map_err(|e| e.to_string())?;
+    content::set_all_enabled(&instance, kind, enabled, &except).map_err(|e| e.to_string())
+}
+
+/// Deletes the file `file_name` of `kind`, enabled or not
+#[tauri::command]
+pub async fn remove_content(name: &str, kind: ContentKind, file_name: &str) -> Result<(), String> {
+    let (instance, _) = instances::get_existing(name).map_err(|e| e.to_string())?;
+    content::remove(&instance, kind, file_name).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_content_lock(name: &str) -> Result<ContentLock, String> {
+    let (instance, _) = instances::get_existing(name).map_err(|e| e.to_string())?;
+    ContentLock::load(&instance).map_err(|e| e.to_string())
+}
+
+/// Downloads the locked files missing from the instance and locks the ones added by hand
+#[tauri::command]
+pub async fn sync_content(name: &str) -> Result<SyncReport, String> {
+    let (instance, _) = instances::get_existing(name).map_err(|e| e.to_string())?;
+    content_lock::sync(&instance).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn check_mod_updates(name: &str) -> Result<Vec<ModUpdate>, String> {
+    let (instance, _) = instances::get_existing(name).map_err(|e| e.to_string())?;
+    mod_updater::check_mod_updates(&instance).await.map_err(|e| e.to_string())
+}
+
+/// Installs `updates`, as returned by [`check_mod_updates`]
+#[tauri::command]
+pub async fn update_mods(name: &str, updates: Vec<ModUpdate>) -> Result<(), String> {
+    let (instance, _) = instances::get_existing(name).map_err(|e| e.to_string())?;
+    mod_updater::update_mods(&instance, &updates).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_mod_rollbacks(name: &str) -> Result<Vec<ModRollback>, String> {
+    let (instance, _) = instances::get_existing(name).map_err(|e| e.to_string())?;
+    mod_updater::list_rollbacks(&instance).map_err(|e| e.to_string())
+}
+
+/// Puts back the mod an update replaced with `file_name`, returns the name of the restored file
+#[tauri::command]
+pub async fn rollback_mod(name: &str, file_name: &str) -> Result<String, String> {
+    let (instance, _) = instances::get_existing(name).map_err(|e| e.to_string())?;
+    mod_updater::rollback_mod(&instance, file_name).map_err(|e| e.to_string())
+}
@@ -1,16 +1,69 @@
 use std::path::Path;
 
-use sl_core::launcher::instances::{
-    self,
-    instance_metadata::{InstanceMetadata, ModLoader},
+use sl_core::{
+    launcher::{
+        game_process::DEFAULT_STOP_TIMEOUT,
+        instances::{
+            self,
+            instance_duplicator::{self, DuplicateOptions},
+            instance_metadata::{InstanceMetadata, ModLoader},
+            instance_query::{self, InstanceQuery},
+            UnreadableInstance,
+        },
+    },
+    GAME_PROCESSES,
+};
+use sl_store::{
+    curseforge::modpack_exporter::export_curseforge_modpack,
+    modrinth::mrpack_exporter::export_mrpack,
 };
 use tauri::AppHandle;
 
-use crate::core::{instances::launch_instance_inner, running_instances::RUNNING_INSTANCES};
+use crate::core::instances::launch_instance_inner;
+
+#[tauri::command]
+pub async fn get_instances(
+    query: Option<InstanceQuery>,
+) -> Result<Vec<InstanceMetadata>, String> {
+    instance_query::query_instances(&query.unwrap_or_default()).map_err(|e| e.to_string())
+}
+
+/// The instances left out of [`get_instances`] because their metadata can't be read
+#[tauri::command]
+pub async fn get_unreadable_instances() -> Result<Vec<UnreadableInstance>, String> {
+    instances::load_instances()
+        .map(|all| all.unreadable)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_instance_groups() -> Result<Vec<String>, String> {
+    instance_query::groups().map_err(|e| e.to_string())
+}
 
 #[tauri::command]
-pub async fn get_instances() -> Result<Vec<InstanceMetadata>, String> {
-    instances::get_all_instances().map_err(|e| e.to_string())
+pub async fn get_instance_tags() -> Result<Vec<String>, String> {
+    instance_query::tags().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_instance_group(name: &str, group: Option<String>) -> Result<(), String> {
+    instances::set_group(name, group).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn add_instance_tags(name: &str, tags: Vec<String>) -> Result<(), String> {
+    instances::add_tags(name, &tags).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn remove_instance_tags(name: &str, tags: Vec<String>) -> Result<(), String> {
+    instances::remove_tags(name, &tags).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_instance_favorite(name: &str, favorite: bool) -> Result<(), String> {
+    instances::set_favorite(name, favorite).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -27,6 +80,16 @@ pub async fn create_instance(
     Ok(())
 }
 
+#[tauri::command]
+pub async fn duplicate_instance(
+    name: &str,
+    new_name: &str,
+    options: Option<DuplicateOptions>,
+) -> Result<InstanceMetadata, String> {
+    instance_duplicator::duplicate_instance(name, new_name, options.unwrap_or_default())
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn remove_instance(name: &str) -> Result<(), String> {
     instances::remove(name).map_err(|e| e.to_string())
@@ -40,14 +103,24 @@ pub async fn launch_instance(name: &str, app_handle: AppHandle) -> Result<(), St
 }
 
 #[tauri::command]
-pub async fn kill_instance(name: &str, app_handle: AppHandle) -> Result<(), String> {
-    RUNNING_INSTANCES.remove(name, &app_handle).await;
+pub async fn kill_instance(name: &str) -> Result<(), String> {
+    let (instance, _) = instances::get_existing(name).map_err(|e| e.to_string())?;
+    GAME_PROCESSES
+        .stop(&instance.id, DEFAULT_STOP_TIMEOUT)
+        .await
+        .map_err(|e| e.to_string())?;
     Ok(())
 }
 
 #[tauri::command]
 pub async fn get_running_instances() -> Vec<String> {
-    RUNNING_INSTANCES.list().await
+    let running = GAME_PROCESSES.running();
+    instances::get_all_instances()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|instance| running.contains(&instance.id))
+        .map(|instance| instance.name)
+        .collect()
 }
 
 #[tauri::command]
@@ -58,3 +131,29 @@ pub async fn export_instance(instance_name: &str, output: &Path) -> Result<(), S
 
     Ok(())
 }
+
+#[tauri::command]
+pub async fn export_instance_mrpack(
+    instance_name: &str,
+    pack_version: &str,
+    output: &Path,
+) -> Result<(), String> {
+    let (instance, _) = instances::get_existing(instance_name).map_err(|e| e.to_string())?;
+    let output = output.join(format!("{instance_name}.mrpack"));
+    export_mrpack(&instance, pack_version, &output)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn export_instance_curseforge(
+    instance_name: &str,
+    pack_version: &str,
+    output: &Path,
+) -> Result<(), String> {
+    let (instance, _) = instances::get_existing(instance_name).map_err(|e| e.to_string())?;
+    let output = output.join(format!("{instance_name}.zip"));
+    export_curseforge_modpack(&instance, pack_version, &output)
+        .await
+        .map_err(|e| e.to_string())
+}
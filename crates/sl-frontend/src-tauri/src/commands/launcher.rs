@@ -1,4 +1,4 @@
-use sl_core::{INSTANCES_DIR, LAUNCHER_DIR};
+use sl_core::{launcher::instances, LAUNCHER_DIR};
 use std::path::Path;
 use tauri::AppHandle;
 use tauri_plugin_opener::OpenerExt;
@@ -20,7 +20,7 @@ pub fn open_synthlauncher_folder(app_handle: AppHandle) -> Result<(), String> {
 
 #[tauri::command]
 pub fn open_instance_folder(app_handle: AppHandle, name: String) -> Result<(), String> {
-    let folder_path = &*INSTANCES_DIR.join(name);
-    open_folder(app_handle, &folder_path)?;
+    let (instance, _) = instances::get_existing(&name).map_err(|e| e.to_string())?;
+    open_folder(app_handle, &instance.instance_dir())?;
     Ok(())
 }
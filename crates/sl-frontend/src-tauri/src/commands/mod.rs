@@ -1,4 +1,6 @@
 pub mod instances;
+pub mod backups;
+pub mod content;
 pub mod accounts;
 pub mod store;
 pub mod launcher;
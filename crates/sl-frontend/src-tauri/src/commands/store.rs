@@ -1,8 +1,8 @@
-use sl_core::{launcher::instances::instance_metadata::ModLoader, INSTANCES_DIR};
+use sl_core::launcher::instances::{self, instance_metadata::ModLoader};
 use sl_store::{
     curseforge::api::search::{query_curseforge_search, CurseforgeSearchResponse},
     facet_filters,
-    modrinth::{api::{project::ModrinthProjectVersion, search::{query_search, Params, SearchResult}, ProjectType}, install_project},
+    modrinth::{api::{project::ModrinthProjectVersion, search::{query_search, Params, SearchResult}, ProjectType}, install_project, ProjectInstall},
 };
 
 use crate::core::store::get_modrinth_project_versions_inner;
@@ -44,9 +44,9 @@ pub async fn get_modrinth_project_versions(
     Ok(versions)
 }
 
+/// Installs a project with the dependencies it requires, the optional ones are returned to be offered
 #[tauri::command]
-pub async fn install_modrinth_project(slug: &str, version: &str, instance_name: &str, project_type: ProjectType) -> Result<(), String> {
-    install_project(slug, version, &INSTANCES_DIR.join(instance_name), project_type).await.map_err(|e| e.to_string())?;
-
-    Ok(())
+pub async fn install_modrinth_project(slug: &str, version: &str, instance_name: &str, project_type: ProjectType) -> Result<ProjectInstall, String> {
+    let (instance, _) = instances::get_existing(instance_name).map_err(|e| e.to_string())?;
+    install_project(slug, version, &instance, project_type).await.map_err(|e| e.to_string())
 }
\ No newline at end of file
@@ -1,22 +1,31 @@
 use data_encoding::BASE32_NOPAD;
-use sl_core::launcher::instances;
-use sl_utils::errors::BackendError;
+use sl_core::launcher::{game_process::GameState, instances};
+use sl_utils::{errors::BackendError, progress::Progress};
+use std::time::Duration;
 use tauri::{AppHandle, Emitter};
 use tokio::io::{AsyncBufReadExt, BufReader};
 
-use crate::core::running_instances::RUNNING_INSTANCES;
-
 pub async fn launch_instance_inner(name: &str, app_handle: AppHandle) -> Result<(), BackendError> {
     let encoded = BASE32_NOPAD.encode(name.as_bytes());
     let emit_target = format!("{encoded}-console");
+    let progress_target = format!("{encoded}-progress");
+    let crash_target = format!("{encoded}-crash");
+
+    let (progress, mut progress_rx) = Progress::channel();
+    let progress_app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        while let Some(event) = progress_rx.recv().await {
+            let _ = progress_app_handle.emit(&progress_target, event);
+        }
+    });
 
     let (instance, _) = instances::get_existing(name)?;
-    let loaded_instance = instance.load_init().await?;
+    let loaded_instance = instance.load_init(progress).await?;
 
-    let (mut child, reader) = loaded_instance.execute().await?;
+    let (mut game, reader) = loaded_instance.execute().await?;
     let mut reader = BufReader::new(reader);
 
-    RUNNING_INSTANCES.add(name.to_string(), &app_handle).await;
+    let _ = app_handle.emit("running_instances_updates", name.to_string());
 
     let mut line = String::new();
 
@@ -25,43 +34,44 @@ pub async fn launch_instance_inner(name: &str, app_handle: AppHandle) -> Result<
     emit("Starting instance...")
         .expect("failed to emit the initial data to the instance's Console");
 
-    let mut dead_peacfully = false;
-
     loop {
+        // checked before reading so the lines written right before exiting aren't lost
+        let exited = game.state().is_exited();
         match reader.read_line(&mut line).await {
-            Ok(0) => {
-                if let Ok(Some(status)) = child.try_wait() {
-                    emit(&format!(
-                        "Exited with code: {}\n",
-                        status.code().unwrap_or(-1)
-                    ))
-                    .expect("failed to emit end");
-                    dead_peacfully = true;
-                    break;
-                }
-
-                if !RUNNING_INSTANCES.is_alive(name).await {
-                    break;
-                }
-            }
+            Ok(0) if exited => break,
+            Ok(0) => tokio::time::sleep(Duration::from_millis(100)).await,
             Ok(_) => {
                 let _ = emit(&line);
                 line.clear();
-                if !RUNNING_INSTANCES.is_alive(name).await {
-                    break;
-                }
             }
             Err(_) => break,
         }
     }
 
-    RUNNING_INSTANCES.remove(&name, &app_handle).await;
+    let state = game.wait().await;
+    match state {
+        GameState::Exited {
+            signal: Some(_), ..
+        } => emit("The instance has been killed!\n").expect("failed to emit end data"),
+        GameState::Exited { code, .. } => {
+            emit(&format!("Exited with code: {}\n", code.unwrap_or(-1)))
+                .expect("failed to emit end")
+        }
+        _ => unreachable!("waited for the instance to exit"),
+    }
 
-    // in case it was removed from the list or an error occurred
-    if !dead_peacfully {
-        _ = child.kill().await;
-        emit("The instance has been killed!\n").expect("failed to emit end data");
+    if !state.is_success() {
+        let analysis = game.analyze_crash();
+        for finding in &analysis.findings {
+            let _ = emit(&format!(
+                "{}: {}\n",
+                finding.kind.description(),
+                finding.kind.suggestion()
+            ));
+        }
+        let _ = app_handle.emit(&crash_target, analysis);
     }
 
+    let _ = app_handle.emit("running_instances_updates", name.to_string());
     Ok(())
 }
@@ -1,3 +1,2 @@
-pub mod running_instances;
 pub mod instances;
 pub mod store;
\ No newline at end of file
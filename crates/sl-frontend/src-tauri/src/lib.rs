@@ -1,9 +1,11 @@
 use commands::{
-    instances::{get_instances, create_instance, remove_instance, launch_instance, kill_instance, get_running_instances, export_instance}, 
+    instances::{get_instances, get_unreadable_instances, create_instance, duplicate_instance, remove_instance, launch_instance, kill_instance, get_running_instances, export_instance, export_instance_mrpack, export_instance_curseforge, get_instance_groups, get_instance_tags, set_instance_group, add_instance_tags, remove_instance_tags, set_instance_favorite}, 
     launcher::{open_synthlauncher_folder, open_instance_folder, open_folder}, 
     minecraft::{get_minecraft_versions},
     store::{search_modrinth_store, search_curseforge_store, get_modrinth_project_versions, install_modrinth_project},
-    accounts::{accounts_get, accounts_set_current, accounts_remove, accounts_create_offline}
+    accounts::{accounts_get, accounts_set_current, accounts_remove, accounts_create_offline},
+    backups::{get_instance_worlds, get_backups, create_backup, restore_backup, delete_backup},
+    content::{get_content, set_content_enabled, set_all_content_enabled, remove_content, get_content_lock, sync_content, check_mod_updates, update_mods, get_mod_rollbacks, rollback_mod},
 };
 
 mod commands;
@@ -21,12 +23,39 @@ pub fn run() {
             accounts_create_offline,
 
             get_instances,
+            get_unreadable_instances,
             create_instance,
+            duplicate_instance,
             remove_instance,
             launch_instance,
             export_instance,
+            export_instance_mrpack,
+            export_instance_curseforge,
             kill_instance,
             get_running_instances,
+            get_instance_groups,
+            get_instance_tags,
+            set_instance_group,
+            add_instance_tags,
+            remove_instance_tags,
+            set_instance_favorite,
+
+            get_instance_worlds,
+            get_backups,
+            create_backup,
+            restore_backup,
+            delete_backup,
+
+            get_content,
+            set_content_enabled,
+            set_all_content_enabled,
+            remove_content,
+            get_content_lock,
+            sync_content,
+            check_mod_updates,
+            update_mods,
+            get_mod_rollbacks,
+            rollback_mod,
             
             open_folder,
             open_synthlauncher_folder,
@@ -5,7 +5,9 @@ use lzma_rs::lzma_decompress;
 use serde::Deserialize;
 use sl_meta::minecraft::loaders::vanilla::JavaComponent;
 use sl_utils::{
-    errors::BackendError, requester::Requester,
+    errors::BackendError,
+    progress::{Phase, Progress},
+    requester::Requester,
 };
 
 use crate::jre_manifest::JreManifest;
@@ -62,6 +64,7 @@ pub async fn download_jre_manifest_version(
     jre_manifest: &JreManifest,
     dest: &Path,
     java_component: &JavaComponent,
+    progress: &Progress,
 ) -> Result<(), BackendError> {
     let downloads = jre_manifest.get_component_downloads(java_component);
     let dir = Arc::new(dest.join(java_component.to_string()));
@@ -114,9 +117,12 @@ pub async fn download_jre_manifest_version(
         }
     }
 
+    let tracker = progress.start(Phase::Java, tasks.len());
     while let Some(res) = tasks.next().await {
         res??;
+        tracker.item_finished();
     }
+    tracker.finish();
 
     Ok(())
 }
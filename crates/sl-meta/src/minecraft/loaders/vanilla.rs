@@ -23,6 +23,32 @@ pub enum RuleActionType {
     Disallow,
 }
 
+/// The launcher features that argument rules can be conditioned on
+#[derive(Debug, Clone, Default)]
+pub struct LaunchFeatures {
+    pub is_demo_user: bool,
+    pub has_custom_resolution: bool,
+    pub has_quick_plays_support: bool,
+    pub is_quick_play_singleplayer: bool,
+    pub is_quick_play_multiplayer: bool,
+    pub is_quick_play_realms: bool,
+}
+
+impl LaunchFeatures {
+    /// Returns whether the feature named `name` is enabled, unknown features are never enabled
+    pub fn get(&self, name: &str) -> bool {
+        match name {
+            "is_demo_user" => self.is_demo_user,
+            "has_custom_resolution" => self.has_custom_resolution,
+            "has_quick_plays_support" => self.has_quick_plays_support,
+            "is_quick_play_singleplayer" => self.is_quick_play_singleplayer,
+            "is_quick_play_multiplayer" => self.is_quick_play_multiplayer,
+            "is_quick_play_realms" => self.is_quick_play_realms,
+            _ => false,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Rule {
     pub action: RuleActionType,
@@ -31,13 +57,22 @@ pub struct Rule {
 }
 
 impl Rule {
-    fn matches(&self) -> bool {
+    fn matches(&self, features: &LaunchFeatures) -> bool {
         (self.os.is_none() || self.os.as_ref().is_some_and(|os| os.matches()))
-            && self.features.is_none()
+            && self.features.as_ref().is_none_or(|required| {
+                required
+                    .iter()
+                    .all(|(name, enabled)| features.get(name) == *enabled)
+            })
     }
 
+    /// Whether this rule allows its subject, assuming no launcher features are enabled
     pub fn is_allowed(&self) -> bool {
-        let matched = self.matches();
+        self.is_allowed_with(&LaunchFeatures::default())
+    }
+
+    pub fn is_allowed_with(&self, features: &LaunchFeatures) -> bool {
+        let matched = self.matches(features);
         match self.action {
             RuleActionType::Allow => matched,
             RuleActionType::Disallow => !matched,
@@ -77,11 +112,11 @@ pub enum Argument {
 }
 
 impl Argument {
-    fn into_raw(self) -> Vec<String> {
+    fn into_raw(self, features: &LaunchFeatures) -> Vec<String> {
         match self {
             Argument::Arg(arg) => vec![arg],
             Argument::Rule { rules, value } => {
-                if rules.iter().all(Rule::is_allowed) {
+                if rules.iter().all(|rule| rule.is_allowed_with(features)) {
                     match value {
                         ArgumentValue::Value(value) => vec![value],
                         ArgumentValue::Values(values) => values,
@@ -128,16 +163,40 @@ impl Arguments {
         }
     }
 
-    pub fn into_raw(self) -> (Vec<String>, Vec<String>) {
+    /// Evaluates the arguments' rules against `features`, returning the jvm and game arguments (in that order)
+    pub fn into_raw(self, features: &LaunchFeatures) -> (Vec<String>, Vec<String>) {
         match self {
             Arguments::Args { game, jvm } => {
-                let jvm: Vec<String> = jvm.into_iter().map(Argument::into_raw).flatten().collect();
+                let jvm: Vec<String> = jvm
+                    .into_iter()
+                    .flat_map(|arg| arg.into_raw(features))
+                    .collect();
 
-                let game = game.into_iter().map(Argument::into_raw).flatten().collect();
+                let game = game
+                    .into_iter()
+                    .flat_map(|arg| arg.into_raw(features))
+                    .collect();
                 (jvm, game)
             }
             Arguments::MinecraftArgs(args) => {
-                let game = args.split(' ').map(|arg| arg.to_string()).collect();
+                let mut game: Vec<String> = args.split(' ').map(|arg| arg.to_string()).collect();
+
+                // older versions have no rules, but still understand these
+                if features.has_custom_resolution {
+                    game.extend(
+                        [
+                            "--width",
+                            "${resolution_width}",
+                            "--height",
+                            "${resolution_height}",
+                        ]
+                        .map(String::from),
+                    );
+                }
+
+                if features.is_demo_user {
+                    game.push("--demo".to_string());
+                }
 
                 let jvm = [
                     "-Djava.library.path=${natives_directory}",
@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sl_core::REQUESTER;
+use sl_utils::errors::BackendError;
+
+use crate::curseforge::api::project::{CurseforgeFile, CurseforgeProjectLinks};
+
+/// The part of a project needed to place and link its files
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseforgeProjectInfo {
+    pub id: u32,
+    pub name: String,
+    /// Tells whether the project is a mod, a resource pack, a shader pack...
+    pub class_id: Option<u32>,
+    #[serde(default)]
+    pub links: CurseforgeProjectLinks,
+}
+
+#[derive(Debug, Deserialize)]
+struct CurseforgeResponse<T> {
+    data: T,
+}
+
+/// Gets the files with the given IDs, the ones that don't exist are left out
+pub async fn query_files(file_ids: &[u32]) -> Result<Vec<CurseforgeFile>, BackendError> {
+    let body = json!({ "fileIds": file_ids });
+    let response: CurseforgeResponse<_> = REQUESTER
+        .post_json("https://api.curseforge.com/v1/mods/files", &body)
+        .await?;
+    Ok(response.data)
+}
+
+/// Gets the projects with the given IDs, the ones that don't exist are left out
+pub async fn query_projects(
+    project_ids: &[u32],
+) -> Result<Vec<CurseforgeProjectInfo>, BackendError> {
+    let body = json!({ "modIds": project_ids });
+    let response: CurseforgeResponse<_> = REQUESTER
+        .post_json("https://api.curseforge.com/v1/mods", &body)
+        .await?;
+    Ok(response.data)
+}
@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+use sl_core::REQUESTER;
+use sl_utils::errors::BackendError;
+
+use crate::curseforge::api::MINECRAFT_GAME_ID;
+
+/// Whitespace bytes, which CurseForge leaves out when fingerprinting a file
+const IGNORED_BYTES: &[u8] = b"\t\n\r ";
+
+/// Computes the CurseForge fingerprint of a file's content, a MurmurHash2 (seed 1) of it without its whitespace
+pub fn fingerprint(data: &[u8]) -> u32 {
+    const M: u32 = 0x5bd1_e995;
+    const R: u32 = 24;
+
+    let data = data
+        .iter()
+        .copied()
+        .filter(|byte| !IGNORED_BYTES.contains(byte))
+        .collect::<Vec<_>>();
+
+    let mut hash = 1 ^ data.len() as u32;
+    let mut chunks = data.chunks_exact(4);
+    for chunk in &mut chunks {
+        let mut k = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+
+        hash = hash.wrapping_mul(M);
+        hash ^= k;
+    }
+
+    let remainder = chunks.remainder();
+    if !remainder.is_empty() {
+        for (i, byte) in remainder.iter().enumerate() {
+            hash ^= u32::from(*byte) << (8 * i);
+        }
+        hash = hash.wrapping_mul(M);
+    }
+
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(M);
+    hash ^ (hash >> 15)
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseforgeFingerprintFile {
+    pub id: u32,
+    pub mod_id: u32,
+    pub file_fingerprint: u32,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseforgeFingerprintMatch {
+    /// The ID of the project the file belongs to
+    pub id: u32,
+    pub file: CurseforgeFingerprintFile,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseforgeFingerprintMatches {
+    pub exact_matches: Vec<CurseforgeFingerprintMatch>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct CurseforgeFingerprintResponse {
+    data: CurseforgeFingerprintMatches,
+}
+
+/// Looks up the files with the given fingerprints, the files CurseForge doesn't know about are left out
+pub async fn query_fingerprint_matches(
+    fingerprints: &[u32],
+) -> Result<Vec<CurseforgeFingerprintMatch>, BackendError> {
+    let url = format!("https://api.curseforge.com/v1/fingerprints/{MINECRAFT_GAME_ID}");
+    let body = serde_json::json!({ "fingerprints": fingerprints });
+    let response: CurseforgeFingerprintResponse = REQUESTER.post_json(&url, &body).await?;
+    Ok(response.data.exact_matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_murmurhash2() {
+        // MurmurHash2 with seed 1, as computed by the reference implementation
+        assert_eq!(fingerprint(b""), 1_540_447_798);
+        assert_eq!(fingerprint(b"abcd"), 3_376_380_438);
+        assert_eq!(fingerprint(b"abcdefg"), 184_182_053);
+    }
+
+    #[test]
+    fn hashes_the_remainder() {
+        assert_eq!(fingerprint(b"a"), 626_045_324);
+        assert_eq!(fingerprint(b"ab"), 1_692_487_918);
+        assert_eq!(fingerprint(b"abc"), 1_621_425_345);
+        assert_eq!(fingerprint(b"abcde"), 3_469_237_630);
+        assert_eq!(fingerprint(b"abcdef"), 455_443_312);
+    }
+
+    #[test]
+    fn strips_whitespace() {
+        assert_eq!(
+            fingerprint(b"The quick brown fox jumps over the lazy dog"),
+            3_751_777_527
+        );
+        assert_eq!(
+            fingerprint(b"The quick brown fox jumps over the lazy dog"),
+            fingerprint(b"Thequickbrownfoxjumpsoverthelazydog")
+        );
+        assert_eq!(fingerprint(b"a\tb\nc\rd e"), fingerprint(b"abcde"));
+        assert_eq!(fingerprint(b" \t\r\n"), fingerprint(b""));
+    }
+}
@@ -1,3 +1,5 @@
+pub mod files;
+pub mod fingerprint;
 pub mod project;
 pub mod search;
 
@@ -7,4 +9,4 @@ pub const PAGE_SIZE: u32 = 16;
 pub const MODPACK_CLASS_ID: u32 = 4471;
 pub const MOD_CLASS_ID: u32 = 6;
 pub const RESOURCEPACK_CLASS_ID: u32 = 12;
-pub const SHADERSPACK_CLASS_ID: u32 = 6552;
\ No newline at end of file
+pub const SHADERSPACK_CLASS_ID: u32 = 6552;
@@ -15,11 +15,20 @@ pub struct CurseforgeProjectAuthor {
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CurseforgeFile {
+    pub id: u32,
+    pub mod_id: u32,
     pub file_name: Option<String>,
+    /// `None` when the author disabled third party downloads, the file has to be downloaded from the website then
     pub download_url: Option<String>,
     pub game_versions: Vec<String>,
 }
 
+#[derive(Debug, Deserialize, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseforgeProjectLinks {
+    pub website_url: Option<String>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CurseforgeProject {
@@ -1,2 +1,271 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, Read, Seek},
+    path::{Path, PathBuf},
+};
+
+use futures_util::{stream::FuturesUnordered, StreamExt};
+use serde::Serialize;
+use sl_core::{
+    launcher::instances::{
+        self,
+        content::DISABLED_SUFFIX,
+        content_lock::{self, ContentSource, FileOrigin, InstallReason},
+        instance_metadata::{InstanceMetadata, ModLoader},
+    },
+    REQUESTER,
+};
+use sl_utils::{
+    errors::{BackendError, InstanceImportErr},
+    fs::is_file_name,
+    wlog,
+    zip::ZipExtractor,
+};
+
+use crate::curseforge::{
+    api::{
+        files::{query_files, query_projects},
+        RESOURCEPACK_CLASS_ID, SHADERSPACK_CLASS_ID,
+    },
+    modpack::{read_modpack_manifest, ModpackManifest},
+};
+
 pub mod api;
 pub mod modpack;
+pub mod modpack_exporter;
+
+/// A file of a modpack that has to be downloaded by hand, as its author disabled third party downloads
+#[derive(Debug, Clone, Serialize)]
+pub struct ManualDownload {
+    pub project_name: String,
+    pub file_name: String,
+    /// The page the file can be downloaded from
+    pub url: String,
+    /// Where the file goes, relative to the instance's directory
+    pub path: PathBuf,
+}
+
+/// An installed CurseForge modpack, along with the files that couldn't be downloaded for it
+#[derive(Debug, Serialize)]
+pub struct CurseforgeInstall {
+    pub instance: InstanceMetadata,
+    pub manual_downloads: Vec<ManualDownload>,
+}
+
+/// Parses a manifest's mod loader ID, like `forge-47.2.0` or `fabric-0.15.11`
+fn parse_mod_loader(id: &str) -> Result<(ModLoader, String), InstanceImportErr> {
+    let unsupported = || InstanceImportErr::UnsupportedModLoader(id.to_string());
+    let (mod_loader, version) = id.split_once('-').ok_or_else(unsupported)?;
+
+    let mod_loader = match mod_loader {
+        "forge" => ModLoader::Forge,
+        "neoforge" => ModLoader::NeoForge,
+        "fabric" => ModLoader::Fabric,
+        "quilt" => ModLoader::Quilt,
+        _ => return Err(unsupported()),
+    };
+    Ok((mod_loader, version.to_string()))
+}
+
+/// The directory of the instance a project's files go in, depending on its class
+fn content_dir(class_id: Option<u32>) -> &'static str {
+    match class_id {
+        Some(RESOURCEPACK_CLASS_ID) => "resourcepacks",
+        Some(SHADERSPACK_CLASS_ID) => "shaderpacks",
+        _ => "mods",
+    }
+}
+
+/// Downloads the files of `manifest` into the instance concurrently and locks them, returning the ones that have to be downloaded by hand
+///
+/// the optional files are installed disabled, with [`DISABLED_SUFFIX`] so the game ignores them,
+/// every file is attempted and the ones that failed are reported together
+async fn download_modpack_files(
+    instance: &InstanceMetadata,
+    manifest: &ModpackManifest,
+) -> Result<Vec<ManualDownload>, BackendError> {
+    if manifest.files.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let file_ids = manifest
+        .files
+        .iter()
+        .map(|file| file.file_id)
+        .collect::<Vec<_>>();
+    let project_ids = manifest
+        .files
+        .iter()
+        .map(|file| file.project_id)
+        .collect::<Vec<_>>();
+
+    let mut files = query_files(&file_ids)
+        .await?
+        .into_iter()
+        .map(|file| (file.id, file))
+        .collect::<HashMap<_, _>>();
+    let projects = query_projects(&project_ids)
+        .await?
+        .into_iter()
+        .map(|project| (project.id, project))
+        .collect::<HashMap<_, _>>();
+
+    let mut manual_downloads = Vec::new();
+    let mut tasks = FuturesUnordered::new();
+    for modpack_file in &manifest.files {
+        let Some(file) = files.remove(&modpack_file.file_id) else {
+            wlog!(
+                "File {} of project {} doesn't exist anymore, skipping it",
+                modpack_file.file_id,
+                modpack_file.project_id
+            );
+            continue;
+        };
+        let project = projects.get(&modpack_file.project_id);
+        let file_name = file
+            .file_name
+            .unwrap_or_else(|| format!("{}-{}.jar", file.mod_id, file.id));
+        // the name comes from CurseForge, it must not lead out of the content's directory
+        if !is_file_name(&file_name) {
+            return Err(InstanceImportErr::UnsafePath(PathBuf::from(file_name)).into());
+        }
+
+        let file_name = match modpack_file.required {
+            true => file_name,
+            false => format!("{file_name}{DISABLED_SUFFIX}"),
+        };
+        let path =
+            Path::new(content_dir(project.and_then(|project| project.class_id))).join(&file_name);
+
+        let Some(download_url) = file.download_url else {
+            let url = project
+                .and_then(|project| project.links.website_url.as_ref())
+                .map_or_else(
+                    || format!("https://www.curseforge.com/projects/{}", file.mod_id),
+                    |website| format!("{website}/files/{}", file.id),
+                );
+            manual_downloads.push(ManualDownload {
+                project_name: project
+                    .map_or_else(|| file.mod_id.to_string(), |project| project.name.clone()),
+                file_name,
+                url,
+                path,
+            });
+            continue;
+        };
+
+        let full_path = instance.instance_dir().join(&path);
+        let origin = FileOrigin {
+            source: ContentSource::Modpack,
+            project_id: Some(modpack_file.project_id.to_string()),
+            version_id: Some(modpack_file.file_id.to_string()),
+            url: Some(download_url.clone()),
+            reason: InstallReason::User,
+        };
+        tasks.push(tokio::spawn(async move {
+            let result = async {
+                if let Some(parent) = full_path.parent() {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                REQUESTER
+                    .builder()
+                    .download_to(&download_url, &full_path)
+                    .await?;
+                Ok::<_, BackendError>(())
+            }
+            .await;
+            (path, origin, result)
+        }));
+    }
+
+    let mut locked = Vec::new();
+    let mut failed = Vec::new();
+    while let Some(result) = tasks.next().await {
+        let (path, origin, result) = result?;
+        match result {
+            Ok(()) => locked.push((path, origin)),
+            Err(err) => failed.push(format!("{} ({err})", path.display())),
+        }
+    }
+
+    if !failed.is_empty() {
+        failed.sort();
+        return Err(InstanceImportErr::FilesFailed(failed).into());
+    }
+
+    let instance = instance.clone();
+    tokio::task::spawn_blocking(move || content_lock::lock_files(&instance, locked)).await??;
+    Ok(manual_downloads)
+}
+
+/// Installs the CurseForge modpack zip at `path`, see [`install_modpack_from_reader`]
+pub async fn install_modpack_from_path(
+    path: &Path,
+    name: Option<String>,
+) -> Result<CurseforgeInstall, BackendError> {
+    install_modpack_from_reader(BufReader::new(File::open(path)?), name).await
+}
+
+/// Installs a CurseForge modpack zip as a new instance, applying its overrides and downloading its files
+///
+/// the instance is named `name`, or after the pack if `None`, and isn't launched
+pub async fn install_modpack_from_reader<R: Read + Seek>(
+    reader: R,
+    name: Option<String>,
+) -> Result<CurseforgeInstall, BackendError> {
+    let extract_dir = tempfile::TempDir::new()?;
+    ZipExtractor::new(reader).extract(extract_dir.path())?;
+
+    let manifest = read_modpack_manifest(extract_dir.path()).await?;
+    if !manifest.has_safe_overrides() {
+        return Err(InstanceImportErr::UnsafeOverrides(manifest.overrides).into());
+    }
+    let mod_loaders = &manifest.minecraft.mod_loaders;
+    let (mod_loader, mod_loader_version) = mod_loaders
+        .iter()
+        .find(|mod_loader| mod_loader.primary)
+        .or(mod_loaders.first())
+        .map(|mod_loader| parse_mod_loader(&mod_loader.id))
+        .transpose()?
+        .unzip();
+
+    let instance = InstanceMetadata::create(
+        name.unwrap_or_else(|| manifest.name.clone()),
+        &manifest.minecraft.version,
+        mod_loader.unwrap_or_default(),
+        mod_loader_version,
+        None,
+    )
+    .await?;
+
+    let instance_dir = instance.instance_dir();
+    let result = async {
+        let overrides_dir = extract_dir.path().join(&manifest.overrides);
+        if overrides_dir.is_dir() {
+            sl_utils::fs::copy_dir_all(&overrides_dir, &instance_dir)?;
+        }
+        download_modpack_files(&instance, &manifest).await
+    }
+    .await;
+
+    // a half installed pack wouldn't launch, the player retries from scratch
+    let manual_downloads = match result {
+        Ok(manual_downloads) => manual_downloads,
+        Err(err) => {
+            _ = instances::remove(&instance.id);
+            return Err(err);
+        }
+    };
+    if !manual_downloads.is_empty() {
+        wlog!(
+            "{} file(s) of the modpack can only be downloaded from CurseForge's website",
+            manual_downloads.len()
+        );
+    }
+
+    Ok(CurseforgeInstall {
+        instance,
+        manual_downloads,
+    })
+}
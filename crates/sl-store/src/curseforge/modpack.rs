@@ -1,59 +1,79 @@
-use std::{fs::File, io::BufReader, path::Path};
+use std::{
+    fs::File,
+    io::BufReader,
+    path::{Component, Path},
+};
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use sl_utils::errors::BackendError;
 use zip::ZipArchive;
 
-const MODPACK_MANIFEST_NAME: &str = "manifest.json";
+pub(crate) const MODPACK_MANIFEST_NAME: &str = "manifest.json";
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct ModLoader {
+    /// The loader and its version, like `forge-47.2.0` or `fabric-0.15.11`
     pub id: String,
     pub primary: bool
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Minecraft {
     pub version: String,
     pub mod_loaders: Vec<ModLoader>
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct ModpackFile {
+    #[serde(rename = "projectID")]
     pub project_id: u32,
+    #[serde(rename = "fileID")]
     pub file_id: u32,
     pub required: bool
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ModpackManifest {
     pub minecraft: Minecraft,
+    pub manifest_type: String,
+    pub manifest_version: u32,
     pub name: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
     pub author: String,
-    pub files: Vec<ModpackFile>
+    pub files: Vec<ModpackFile>,
+    /// The directory of the pack holding the files copied over the instance
+    #[serde(default = "default_overrides")]
+    pub overrides: String,
 }
 
-pub async fn unzip_modpack(modpack: &Path, output_dir: &Path) -> Result<(), BackendError> {
-    let mut archive = ZipArchive::new(BufReader::new(File::open(modpack)?))?;
-
-    for i in 0..archive.len() {
-        let mut file = archive.by_index(i)?;
-        let out_path = output_dir.join(file.name());
-
-        if file.is_dir() {
-            tokio::fs::create_dir_all(&out_path).await?;
-        } else {
-            if let Some(parent) = out_path.parent() {
-                tokio::fs::create_dir_all(parent).await?;
-            }
-            std::io::copy(&mut file, &mut File::create(&out_path)?)?;
-        }
+impl ModpackManifest {
+    /// Whether the overrides are a directory at the root of the pack, so they can't be read from outside of it
+    pub fn has_safe_overrides(&self) -> bool {
+        let mut components = Path::new(&self.overrides).components();
+        matches!(
+            (components.next(), components.next()),
+            (Some(Component::Normal(_)), None)
+        )
     }
+}
 
-    Ok(())
+fn default_overrides() -> String {
+    "overrides".to_string()
 }
 
 pub async fn read_modpack_manifest(modpack_path: &Path) -> Result<ModpackManifest, BackendError> {
     let json = tokio::fs::read_to_string(modpack_path.join(MODPACK_MANIFEST_NAME)).await?;
     Ok(serde_json::from_str(&json)?)
 }
+
+/// Whether the zip at `path` is a CurseForge modpack, which has a `manifest.json` at its root
+pub fn is_curseforge_modpack(path: &Path) -> bool {
+    File::open(path)
+        .ok()
+        .and_then(|file| ZipArchive::new(BufReader::new(file)).ok())
+        .is_some_and(|mut archive| archive.by_name(MODPACK_MANIFEST_NAME).is_ok())
+}
@@ -0,0 +1,125 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use sl_core::launcher::{
+    instances::instance_metadata::{self, InstanceMetadata},
+    is_offline_mode,
+};
+use sl_utils::{errors::BackendError, log, wlog};
+
+use crate::{
+    curseforge::{
+        api::fingerprint::{fingerprint, query_fingerprint_matches},
+        modpack::{Minecraft, ModLoader, ModpackFile, ModpackManifest, MODPACK_MANIFEST_NAME},
+    },
+    pack_export,
+};
+
+/// Looks up `files` on CurseForge by their fingerprints, returning the manifest entries of the ones it knows about along with their paths
+///
+/// disabled files are linked as optional, which installs them disabled again
+async fn resolve_files(
+    instance_dir: &Path,
+    files: Vec<PathBuf>,
+) -> Result<Vec<(PathBuf, ModpackFile)>, BackendError> {
+    if files.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    if is_offline_mode() {
+        wlog!("Offline mode, every mod and pack is embedded in the exported pack");
+        return Ok(Vec::new());
+    }
+
+    // identical files share a fingerprint, each of them gets its own entry
+    let mut by_fingerprint = HashMap::<u32, Vec<PathBuf>>::new();
+    for path in files {
+        let data = fs::read(instance_dir.join(&path))?;
+        by_fingerprint
+            .entry(fingerprint(&data))
+            .or_default()
+            .push(path);
+    }
+
+    let fingerprints = by_fingerprint.keys().copied().collect::<Vec<_>>();
+    let matches = query_fingerprint_matches(&fingerprints).await?;
+
+    let resolved = matches
+        .into_iter()
+        .filter_map(|found| {
+            let paths = by_fingerprint.remove(&found.file.file_fingerprint)?;
+            Some(paths.into_iter().map(move |path| {
+                let file = ModpackFile {
+                    project_id: found.id,
+                    file_id: found.file.id,
+                    required: !pack_export::is_disabled(&path),
+                };
+                (path, file)
+            }))
+        })
+        .flatten()
+        .collect();
+    Ok(resolved)
+}
+
+/// The `modLoaders` entry of the manifest, `None` for vanilla
+fn mod_loader(instance: &InstanceMetadata) -> Option<ModLoader> {
+    match instance.mod_loader {
+        instance_metadata::ModLoader::Vanilla => None,
+        mod_loader => Some(ModLoader {
+            id: format!("{mod_loader}-{}", instance.mod_loader_version),
+            primary: true,
+        }),
+    }
+}
+
+/// Exports `instance` as a CurseForge modpack zip to `output`
+///
+/// the mods, resource packs and shader packs CurseForge knows about are linked to, everything else is embedded in the pack's overrides
+pub async fn export_curseforge_modpack(
+    instance: &InstanceMetadata,
+    version: &str,
+    output: &Path,
+) -> Result<(), BackendError> {
+    let instance_dir = instance.instance_dir();
+
+    let candidates = pack_export::content_files(&instance_dir)?;
+    let (linked, files): (HashSet<_>, Vec<_>) = resolve_files(&instance_dir, candidates)
+        .await?
+        .into_iter()
+        .unzip();
+
+    let manifest = ModpackManifest {
+        minecraft: Minecraft {
+            version: instance.mc_version.clone(),
+            mod_loaders: mod_loader(instance).into_iter().collect(),
+        },
+        manifest_type: "minecraftModpack".to_string(),
+        manifest_version: 1,
+        name: instance.name.clone(),
+        version: version.to_string(),
+        author: String::new(),
+        files,
+        overrides: pack_export::OVERRIDES_DIR.to_string(),
+    };
+
+    let manifest = serde_json::to_vec_pretty(&manifest)?;
+    let embedded = pack_export::write_pack(
+        output,
+        MODPACK_MANIFEST_NAME,
+        &manifest,
+        &instance_dir,
+        &linked,
+    )?;
+
+    log!(
+        "Exported instance '{}' to '{}', {} file(s) linked to CurseForge and {embedded} embedded",
+        instance.name,
+        output.display(),
+        linked.len()
+    );
+    Ok(())
+}
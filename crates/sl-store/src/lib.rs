@@ -1,2 +1,3 @@
 pub mod modrinth;
-pub mod curseforge;
\ No newline at end of file
+pub mod curseforge;
+mod pack_export;
\ No newline at end of file
@@ -1,6 +1,7 @@
-use std::{path::{Path, PathBuf}, fmt::Write};
+use std::{collections::HashMap, path::{Path, PathBuf}, fmt::Write};
 
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use sl_core::REQUESTER;
 use sl_utils::{errors::BackendError};
 
@@ -22,13 +23,13 @@ pub struct ModrinthProject {
     pub gallery: Vec<GalleryImage>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ModrinthProjectFileHashes {
     pub sha1: String,
     pub sha512: String,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ModrinthProjectFile {
     pub hashes: ModrinthProjectFileHashes,
     pub url: String,
@@ -38,7 +39,26 @@ pub struct ModrinthProjectFile {
     pub file_type: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DependencyType {
+    Required,
+    Optional,
+    Incompatible,
+    /// Bundled in the version's file, nothing to install
+    Embedded,
+}
+
+/// A dependency of a version, on a whole project or on one of its versions
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ModrinthDependency {
+    pub version_id: Option<String>,
+    pub project_id: Option<String>,
+    pub file_name: Option<String>,
+    pub dependency_type: DependencyType,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ModrinthProjectVersion {
     pub id: String,
     pub project_id: String,
@@ -49,6 +69,23 @@ pub struct ModrinthProjectVersion {
     pub downloads: u32,
     pub version_type: String,
     pub files: Vec<ModrinthProjectFile>,
+    #[serde(default)]
+    pub dependencies: Vec<ModrinthDependency>,
+    #[serde(default)]
+    pub changelog: Option<String>,
+    /// RFC 3339
+    #[serde(default)]
+    pub date_published: String,
+}
+
+impl ModrinthProjectVersion {
+    /// The file to download, versions may also have secondary files like sources
+    pub fn primary_file(&self) -> Option<&ModrinthProjectFile> {
+        self.files
+            .iter()
+            .find(|file| file.primary)
+            .or_else(|| self.files.first())
+    }
 }
 
 #[must_use]
@@ -95,6 +132,47 @@ pub async fn query_project_version(
     Ok(json)
 }
 
+pub async fn query_version(id: &str) -> Result<ModrinthProjectVersion, BackendError> {
+    let url = format!("https://api.modrinth.com/v2/version/{}", id);
+    let json = REQUESTER.get_json(&url).await?;
+    Ok(json)
+}
+
+/// Looks up the versions the files with the given hashes belong to, `algorithm` is either `sha1` or `sha512`
+///
+/// the result maps each hash to its version, hashes of files Modrinth doesn't know about are left out
+pub async fn query_versions_from_hashes(
+    hashes: &[String],
+    algorithm: &str,
+) -> Result<HashMap<String, ModrinthProjectVersion>, BackendError> {
+    let body = json!({ "hashes": hashes, "algorithm": algorithm });
+    let json = REQUESTER
+        .post_json("https://api.modrinth.com/v2/version_files", &body)
+        .await?;
+    Ok(json)
+}
+
+/// Looks up the newest version, for one of `loaders` and `game_versions`, of the projects the files with the given hashes belong to
+///
+/// the result maps each hash to that version, which may be the one the file already is
+pub async fn query_latest_versions_from_hashes(
+    hashes: &[String],
+    algorithm: &str,
+    loaders: &[&str],
+    game_versions: &[&str],
+) -> Result<HashMap<String, ModrinthProjectVersion>, BackendError> {
+    let body = json!({
+        "hashes": hashes,
+        "algorithm": algorithm,
+        "loaders": loaders,
+        "game_versions": game_versions,
+    });
+    let json = REQUESTER
+        .post_json("https://api.modrinth.com/v2/version_files/update", &body)
+        .await?;
+    Ok(json)
+}
+
 pub async fn download_project_file(
     project_file: &ModrinthProjectFile,
     dest: &Path,
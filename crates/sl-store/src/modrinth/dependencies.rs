@@ -0,0 +1,239 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use sl_core::launcher::instances::{content::ContentKind, instance_metadata::InstanceMetadata};
+use sl_utils::{
+    errors::{BackendError, InstanceError},
+    wlog,
+};
+
+use crate::modrinth::{
+    api::{
+        project::{
+            query_project, query_project_versions, query_version, query_versions_from_hashes,
+            DependencyType, ModrinthDependency, ModrinthProjectVersion,
+        },
+        ProjectType,
+    },
+    mod_updater::{hash_content, modrinth_loaders},
+};
+
+/// The projects to install for a project to work in an instance
+#[derive(Debug, Default)]
+pub(crate) struct Resolution {
+    /// The project itself and the dependencies it requires, recursively
+    pub to_install: Vec<(ModrinthProjectVersion, ProjectType)>,
+    /// The optional dependencies that aren't installed
+    pub optional: Vec<ModrinthProjectVersion>,
+}
+
+/// The versions of the projects installed in the instance, those Modrinth doesn't know about are left out
+async fn installed_versions(
+    instance: &InstanceMetadata,
+) -> Result<Vec<ModrinthProjectVersion>, BackendError> {
+    let instance_clone = instance.clone();
+    let hashes = tokio::task::spawn_blocking(move || {
+        let mut hashes = Vec::new();
+        for kind in [
+            ContentKind::Mod,
+            ContentKind::ResourcePack,
+            ContentKind::ShaderPack,
+        ] {
+            hashes.extend(hash_content(&instance_clone, kind)?.into_keys());
+        }
+        Ok::<_, BackendError>(hashes)
+    })
+    .await??;
+
+    if hashes.is_empty() {
+        return Ok(Vec::new());
+    }
+    let versions = query_versions_from_hashes(&hashes, "sha512").await?;
+    Ok(versions.into_values().collect())
+}
+
+/// The newest version of the project `project_id` for the instance's Minecraft version, and its mod loader for mods
+async fn newest_version(
+    instance: &InstanceMetadata,
+    project_id: &str,
+    project_type: ProjectType,
+) -> Result<Option<ModrinthProjectVersion>, BackendError> {
+    let game_version = Some(instance.mc_version.as_str());
+    // packs are made for the shader loaders and Minecraft itself rather than mod loaders
+    let loaders = match project_type {
+        ProjectType::Mod => modrinth_loaders(instance.mod_loader),
+        _ => &[][..],
+    };
+    if loaders.is_empty() {
+        let versions = query_project_versions(project_id, game_version, None).await?;
+        return Ok(versions.into_iter().next());
+    }
+
+    for loader in loaders {
+        let versions = query_project_versions(project_id, game_version, Some(loader)).await?;
+        if let Some(version) = versions.into_iter().next() {
+            return Ok(Some(version));
+        }
+    }
+    Ok(None)
+}
+
+/// Resolves the version `dependency` points to, with the type of its project
+///
+/// `None` if the project has no version for the instance
+async fn resolve_dependency(
+    instance: &InstanceMetadata,
+    dependency: &ModrinthDependency,
+) -> Result<Option<(ModrinthProjectVersion, ProjectType)>, BackendError> {
+    let pinned = match &dependency.version_id {
+        Some(version_id) => Some(query_version(version_id).await?),
+        None => None,
+    };
+    let Some(project_id) = pinned
+        .as_ref()
+        .map(|version| version.project_id.clone())
+        .or_else(|| dependency.project_id.clone())
+    else {
+        return Ok(None);
+    };
+
+    let project = query_project(&project_id).await?;
+    let version = match pinned {
+        Some(version) => Some(version),
+        None => newest_version(instance, &project.id, project.project_type).await?,
+    };
+    Ok(version.map(|version| (version, project.project_type)))
+}
+
+/// Whether `dependency` points to `version`, or to any version of its project
+fn points_to(dependency: &ModrinthDependency, version: &ModrinthProjectVersion) -> bool {
+    dependency.version_id.as_deref() == Some(version.id.as_str())
+        || (dependency.version_id.is_none()
+            && dependency.project_id.as_deref() == Some(version.project_id.as_str()))
+}
+
+/// Whether `a` declares `b` incompatible
+fn declares_incompatible(a: &ModrinthProjectVersion, b: &ModrinthProjectVersion) -> bool {
+    a.dependencies.iter().any(|dependency| {
+        dependency.dependency_type == DependencyType::Incompatible && points_to(dependency, b)
+    })
+}
+
+/// Errors if a version declares another one incompatible, whichever of them is installed or to be installed
+fn check_incompatibilities(
+    installed: &[ModrinthProjectVersion],
+    to_install: &[(ModrinthProjectVersion, ProjectType)],
+) -> Result<(), InstanceError> {
+    let all = installed
+        .iter()
+        .chain(to_install.iter().map(|(version, _)| version))
+        .collect::<Vec<_>>();
+
+    for (version, _) in to_install {
+        for other in &all {
+            if declares_incompatible(version, other) || declares_incompatible(other, version) {
+                return Err(InstanceError::IncompatibleProject(
+                    version.name.clone(),
+                    other.name.clone(),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Finds what to install for `root`, a version of a project of type `root_type`, to work in the instance
+///
+/// required dependencies are resolved recursively for the instance's Minecraft version and mod loader,
+/// dependencies already installed are skipped while an installed `root` is refused
+pub(crate) async fn resolve(
+    instance: &InstanceMetadata,
+    root: ModrinthProjectVersion,
+    root_type: ProjectType,
+) -> Result<Resolution, BackendError> {
+    let installed = installed_versions(instance).await?;
+    let mut seen = installed
+        .iter()
+        .map(|version| version.project_id.clone())
+        .collect::<HashSet<_>>();
+
+    let mut resolution = Resolution::default();
+    if !seen.insert(root.project_id.clone()) {
+        return Err(InstanceError::ProjectAlreadyInstalled(root.name).into());
+    }
+
+    let mut optional = HashMap::new();
+    let mut queue = VecDeque::from([(root, root_type)]);
+    while let Some((version, project_type)) = queue.pop_front() {
+        for dependency in &version.dependencies {
+            let required = match dependency.dependency_type {
+                DependencyType::Required => true,
+                DependencyType::Optional => false,
+                DependencyType::Incompatible | DependencyType::Embedded => continue,
+            };
+            if dependency
+                .project_id
+                .as_ref()
+                .is_some_and(|project_id| seen.contains(project_id))
+            {
+                continue;
+            }
+
+            let Some((dependency_version, dependency_type)) =
+                resolve_dependency(instance, dependency).await?
+            else {
+                let dependency_name = dependency
+                    .project_id
+                    .clone()
+                    .or_else(|| dependency.version_id.clone())
+                    .unwrap_or_default();
+                match required {
+                    true => {
+                        return Err(InstanceError::MissingDependency(
+                            version.name.clone(),
+                            dependency_name,
+                        )
+                        .into())
+                    }
+                    false => {
+                        wlog!(
+                            "The optional dependency '{dependency_name}' of '{}' has no version for this instance",
+                            version.name
+                        );
+                        continue;
+                    }
+                }
+            };
+
+            if seen.contains(&dependency_version.project_id) {
+                continue;
+            }
+            if matches!(dependency_type, ProjectType::Modpack) {
+                wlog!(
+                    "'{}' depends on the modpack '{}', which isn't installed",
+                    version.name,
+                    dependency_version.name
+                );
+                continue;
+            }
+
+            match required {
+                true => {
+                    seen.insert(dependency_version.project_id.clone());
+                    // a project may be required by one and optional for another
+                    optional.remove(&dependency_version.project_id);
+                    queue.push_back((dependency_version, dependency_type));
+                }
+                false => {
+                    optional
+                        .entry(dependency_version.project_id.clone())
+                        .or_insert(dependency_version);
+                }
+            }
+        }
+        resolution.to_install.push((version, project_type));
+    }
+
+    check_incompatibilities(&installed, &resolution.to_install)?;
+    resolution.optional = optional.into_values().collect();
+    Ok(resolution)
+}
@@ -1,94 +1,237 @@
-use std::{path::Path, str::FromStr};
+use std::{
+    fs::File,
+    io::{BufReader, Read, Seek},
+    path::{Path, PathBuf},
+};
 
+use serde::Serialize;
 use sl_core::{
-    launcher::instances::instance_metadata::{InstanceMetadata, ModLoader},
-    INSTANCES_DIR, REQUESTER,
+    launcher::instances::{
+        self,
+        content::ContentKind,
+        content_lock::{self, ContentSource, FileOrigin, InstallReason},
+        instance_metadata::InstanceMetadata,
+    },
+    REQUESTER,
+};
+use sl_utils::{
+    errors::{BackendError, InstanceError, InstanceImportErr},
+    fs::is_file_name,
+    wlog,
+    zip::ZipExtractor,
 };
-use sl_utils::errors::BackendError;
 
 use crate::modrinth::{
-    api::{project::query_project_version, ProjectType},
-    mrpack::{download_modpack_files, read_modrinth_index, unzip_modpack, DependencyID},
+    api::{
+        project::{query_project_version, ModrinthProjectVersion},
+        ProjectType,
+    },
+    mrpack::{
+        download_modpack_files, read_modrinth_index, DependencyID, ModrinthIndex,
+        CLIENT_OVERRIDES_DIR,
+    },
 };
+use crate::pack_export::OVERRIDES_DIR;
 
 pub mod api;
+mod dependencies;
 pub mod mrpack;
+pub mod mod_updater;
+pub mod mrpack_exporter;
 
-pub async fn install_modpack(slug: &str, version: &str) -> Result<(), BackendError> {
+/// Installs the modpack `slug` at `version` from Modrinth, naming the instance after the pack
+///
+/// all of the pack's optional files are installed
+pub async fn install_modpack(slug: &str, version: &str) -> Result<InstanceMetadata, BackendError> {
     let project_version = query_project_version(slug, version).await?;
-    let instance_dir = INSTANCES_DIR.join(slug);
-    let mrpack_path = instance_dir.join(&project_version.files[0].filename);
-
-    tokio::fs::create_dir_all(&instance_dir).await?;
+    let download_dir = tempfile::TempDir::new()?;
+    let mrpack_path = download_dir.path().join(&project_version.files[0].filename);
 
     REQUESTER
         .builder()
         .download_to(&project_version.files[0].url, &mrpack_path)
         .await?;
 
-    unzip_modpack(&mrpack_path, &instance_dir).await?;
-    let index = read_modrinth_index(&instance_dir).await?;
-    let mod_loader = ModLoader::from_str(project_version.loaders[0].as_str())?;
-    let mod_loader_id = DependencyID::from(project_version.loaders[0].as_str());
-    let mod_loader_version = index.dependencies.get(&mod_loader_id).cloned();
+    install_mrpack_from_path(&mrpack_path, None, |_| true).await
+}
+
+/// Installs the modpack in the .mrpack file at `path`, see [`install_mrpack_from_reader`]
+pub async fn install_mrpack_from_path(
+    path: &Path,
+    name: Option<String>,
+    include_optional: impl FnMut(&ModrinthIndex) -> bool,
+) -> Result<InstanceMetadata, BackendError> {
+    install_mrpack_from_reader(BufReader::new(File::open(path)?), name, include_optional).await
+}
+
+/// Copies the overrides of the pack extracted at `pack_dir` into `instance_dir`
+///
+/// `client-overrides` are applied after `overrides` so they win over them, `server-overrides` are skipped
+fn apply_overrides(pack_dir: &Path, instance_dir: &Path) -> std::io::Result<()> {
+    for overrides in [OVERRIDES_DIR, CLIENT_OVERRIDES_DIR] {
+        let overrides = pack_dir.join(overrides);
+        if overrides.is_dir() {
+            sl_utils::fs::copy_dir_all(overrides, instance_dir)?;
+        }
+    }
+    Ok(())
+}
+
+/// Installs a modpack in the .mrpack format as a new instance, downloading its files
+///
+/// the instance is named `name`, or after the pack if `None`, and isn't launched,
+/// files the client doesn't support are skipped and `include_optional` decides on the optional ones
+pub async fn install_mrpack_from_reader<R: Read + Seek>(
+    reader: R,
+    name: Option<String>,
+    mut include_optional: impl FnMut(&ModrinthIndex) -> bool,
+) -> Result<InstanceMetadata, BackendError> {
+    // the instance's directory is named after its ID, which is only known once the pack's index was read
+    let extract_dir = tempfile::TempDir::new()?;
+    ZipExtractor::new(reader).extract(extract_dir.path())?;
+
+    let index = read_modrinth_index(extract_dir.path()).await?;
     let mc_version = index
         .dependencies
         .get(&DependencyID::Minecraft)
-        .unwrap_or(&project_version.game_versions[0]);
+        .ok_or(InstanceImportErr::NotAnInstance)?;
+    let (mod_loader, mod_loader_version) = index
+        .dependencies
+        .iter()
+        .find_map(|(id, version)| Some((id.mod_loader()?, version.clone())))
+        .unzip();
+
+    // checked before anything is written, a pack escaping the instance isn't installed at all
+    if let Some(unsafe_file) = index.files.iter().find(|file| !file.has_safe_path()) {
+        return Err(InstanceImportErr::UnsafePath(unsafe_file.path.clone()).into());
+    }
+    let files = index
+        .files
+        .iter()
+        .filter(|file| file.is_client_supported())
+        .filter(|file| !file.is_client_optional() || include_optional(file))
+        .cloned()
+        .collect::<Vec<_>>();
 
     let instance = InstanceMetadata::create(
-        slug.to_string(),
-        &mc_version,
-        mod_loader,
+        name.unwrap_or_else(|| index.name.clone()),
+        mc_version,
+        mod_loader.unwrap_or_default(),
         mod_loader_version,
         None,
     )
     .await?;
 
-    download_modpack_files(&instance_dir, &index.files).await?;
+    let instance_dir = instance.instance_dir();
+    let result = async {
+        apply_overrides(extract_dir.path(), &instance_dir)?;
+        download_modpack_files(&instance_dir, &files).await?;
 
-    let loaded_instance = instance.load_init().await?;
-    loaded_instance.execute().await?;
+        let locked = files
+            .iter()
+            .map(|file| {
+                let origin = FileOrigin {
+                    source: ContentSource::Modpack,
+                    project_id: None,
+                    version_id: None,
+                    url: file.downloads.first().cloned(),
+                    reason: InstallReason::User,
+                };
+                (file.path.clone(), origin)
+            })
+            .collect::<Vec<_>>();
+        let instance = instance.clone();
+        tokio::task::spawn_blocking(move || content_lock::lock_files(&instance, locked)).await?
+    }
+    .await;
 
-    Ok(())
+    // a half installed pack wouldn't launch, the player retries from scratch
+    if let Err(err) = result {
+        _ = instances::remove(&instance.id);
+        return Err(err);
+    }
+    Ok(instance)
+}
+
+/// The files installed by [`install_project`]
+#[derive(Debug, Default, Serialize)]
+pub struct ProjectInstall {
+    /// The paths of the installed files, relative to the instance
+    pub installed: Vec<PathBuf>,
+    /// The optional dependencies, not installed, to offer to the player
+    pub optional: Vec<ModrinthProjectVersion>,
 }
 
+fn content_dir(project_type: ProjectType) -> &'static str {
+    match project_type {
+        ProjectType::Mod => ContentKind::Mod.dir_name(),
+        ProjectType::Shader => ContentKind::ShaderPack.dir_name(),
+        ProjectType::Resourcepack => ContentKind::ResourcePack.dir_name(),
+        ProjectType::Modpack => panic!("Modpack doesn't have a path!"),
+    }
+}
+
+/// Installs the version `version` of the project `slug` into the instance, with the dependencies it requires
+///
+/// the dependencies already installed are skipped, and nothing is installed if the project already is or if a project is incompatible with another one
 pub async fn install_project(
     slug: &str,
     version: &str,
-    instance_path: &Path,
-    project_type: ProjectType
-) -> Result<(), BackendError> {
+    instance: &InstanceMetadata,
+    project_type: ProjectType,
+) -> Result<ProjectInstall, BackendError> {
     let project_version = query_project_version(slug, version).await?;
-    
-    let path = match project_type {
-        ProjectType::Mod => {
-            instance_path
-                .join("mods")
-                .join(&project_version.files[0].filename)
-        },
-        ProjectType::Shader => {
-                instance_path
-                .join("shaderpacks")
-                .join(&project_version.files[0].filename)
-        }
-        ProjectType::Resourcepack => {
-                instance_path
-                .join("resourcepacks")
-                .join(&project_version.files[0].filename)
-        
+    let resolution = dependencies::resolve(instance, project_version, project_type).await?;
+    // the names come from Modrinth, checked before anything is downloaded
+    if let Some(file) = resolution
+        .to_install
+        .iter()
+        .filter_map(|(version, _)| version.primary_file())
+        .find(|file| !is_file_name(&file.filename))
+    {
+        return Err(InstanceError::InvalidContentName(file.filename.clone()).into());
+    }
+
+    let instance_path = instance.instance_dir();
+    let mut installed = Vec::new();
+    let mut locked = Vec::new();
+    for (index, (version, project_type)) in resolution.to_install.iter().enumerate() {
+        let Some(file) = version.primary_file() else {
+            wlog!("'{}' has no file to install", version.name);
+            continue;
+        };
+        let path = Path::new(content_dir(*project_type)).join(&file.filename);
+
+        if let Some(parent) = instance_path.join(&path).parent() {
+            tokio::fs::create_dir_all(parent).await?;
         }
-        ProjectType::Modpack => panic!("Modpack doesn't have a path!")
-    };
-    
-    if let Some(parent) = path.parent() {
-        tokio::fs::create_dir_all(parent).await?;
+        REQUESTER
+            .builder()
+            .download_to(&file.url, &instance_path.join(&path))
+            .await?;
+
+        locked.push((
+            path.clone(),
+            FileOrigin {
+                source: ContentSource::Modrinth,
+                project_id: Some(version.project_id.clone()),
+                version_id: Some(version.id.clone()),
+                url: Some(file.url.clone()),
+                // the project asked for comes first, the rest are its dependencies
+                reason: match index {
+                    0 => InstallReason::User,
+                    _ => InstallReason::Dependency,
+                },
+            },
+        ));
+        installed.push(path);
     }
 
-    REQUESTER
-        .builder()
-        .download_to(&project_version.files[0].url, &path)
-        .await?;
+    let instance = instance.clone();
+    tokio::task::spawn_blocking(move || content_lock::lock_files(&instance, locked)).await??;
 
-    Ok(())
+    Ok(ProjectInstall {
+        installed,
+        optional: resolution.optional,
+    })
 }
@@ -0,0 +1,334 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use sl_core::{
+    launcher::{
+        backups::{self, BackupTrigger},
+        instances::{
+            content::{self, ContentKind, DISABLED_SUFFIX},
+            content_lock::{self, ContentSource, FileOrigin, InstallReason, LockedFile},
+            instance_metadata::{InstanceMetadata, ModLoader},
+        },
+    },
+    GAME_PROCESSES, REQUESTER,
+};
+use sl_utils::{
+    errors::{BackendError, HttpError, InstanceError},
+    fs::{hash_file, is_file_name},
+    log,
+};
+
+use crate::modrinth::api::project::{
+    query_latest_versions_from_hashes, query_versions_from_hashes, ModrinthProjectFile,
+};
+
+/// The directory of an instance the mods replaced by updates are moved to
+pub const ROLLBACK_DIR: &str = ".mod-rollback";
+const ROLLBACK_FILE_NAME: &str = "rollback.json";
+
+/// An update available on Modrinth for a mod of an instance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModUpdate {
+    /// The installed file, without [`DISABLED_SUFFIX`]
+    pub file_name: String,
+    pub enabled: bool,
+    pub project_id: String,
+    pub version_id: String,
+    pub current_version: String,
+    pub new_version: String,
+    /// `release`, `beta` or `alpha`
+    pub version_type: String,
+    pub changelog: Option<String>,
+    /// The file of the new version
+    pub file: ModrinthProjectFile,
+}
+
+/// A mod replaced by an update, kept in [`ROLLBACK_DIR`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModRollback {
+    /// The file the update installed, without [`DISABLED_SUFFIX`]
+    pub file_name: String,
+    /// The file it replaced
+    pub previous_file_name: String,
+    /// How the replaced file was locked, restored along with it
+    #[serde(default)]
+    pub previous_lock: Option<LockedFile>,
+}
+
+/// The names Modrinth gives the loaders whose mods `mod_loader` loads
+pub(crate) fn modrinth_loaders(mod_loader: ModLoader) -> &'static [&'static str] {
+    match mod_loader {
+        ModLoader::Vanilla => &[],
+        ModLoader::Fabric => &["fabric"],
+        ModLoader::Quilt => &["quilt", "fabric"],
+        ModLoader::Forge => &["forge"],
+        ModLoader::NeoForge => &["neoforge"],
+    }
+}
+
+/// Hashes the files of `kind` of the instance, the result maps each SHA-512 to its file
+///
+/// packs that are directories are left out
+pub(crate) fn hash_content(
+    instance: &InstanceMetadata,
+    kind: ContentKind,
+) -> Result<HashMap<String, content::ContentFile>, BackendError> {
+    let dir = instance.instance_dir().join(kind.dir_name());
+
+    let mut hashes = HashMap::new();
+    for file in content::list(instance, kind)? {
+        let path = mod_path(&dir, &file.file_name, file.enabled);
+        if path.is_file() {
+            hashes.insert(hash_file(&path)?.sha512, file);
+        }
+    }
+    Ok(hashes)
+}
+
+/// Looks up the updates of the instance's mods on Modrinth, for its Minecraft version and mod loader
+///
+/// mods Modrinth doesn't know about are skipped
+pub async fn check_mod_updates(
+    instance: &InstanceMetadata,
+) -> Result<Vec<ModUpdate>, BackendError> {
+    let loaders = modrinth_loaders(instance.mod_loader);
+    if loaders.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let instance_clone = instance.clone();
+    let mut mods =
+        tokio::task::spawn_blocking(move || hash_content(&instance_clone, ContentKind::Mod))
+            .await??;
+    if mods.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let hashes = mods.keys().cloned().collect::<Vec<_>>();
+    let current = query_versions_from_hashes(&hashes, "sha512").await?;
+    let latest = query_latest_versions_from_hashes(
+        &hashes,
+        "sha512",
+        loaders,
+        &[instance.mc_version.as_str()],
+    )
+    .await?;
+
+    let mut updates = Vec::new();
+    for (hash, latest) in latest {
+        let (Some(current), Some(file)) = (current.get(&hash), mods.remove(&hash)) else {
+            continue;
+        };
+        // the installed version may be newer than any made for the instance's version
+        if latest.id == current.id || latest.date_published <= current.date_published {
+            continue;
+        }
+        let Some(new_file) = latest.primary_file() else {
+            continue;
+        };
+
+        updates.push(ModUpdate {
+            file_name: file.file_name,
+            enabled: file.enabled,
+            project_id: latest.project_id.clone(),
+            version_id: latest.id.clone(),
+            current_version: current.version_number.clone(),
+            new_version: latest.version_number.clone(),
+            version_type: latest.version_type.clone(),
+            changelog: latest.changelog.clone(),
+            file: new_file.clone(),
+        });
+    }
+
+    updates.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+    Ok(updates)
+}
+
+fn rollback_dir(instance: &InstanceMetadata) -> PathBuf {
+    instance.instance_dir().join(ROLLBACK_DIR)
+}
+
+/// The mods that were replaced by updates and can be rolled back
+pub fn list_rollbacks(instance: &InstanceMetadata) -> Result<Vec<ModRollback>, BackendError> {
+    let path = rollback_dir(instance).join(ROLLBACK_FILE_NAME);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+fn write_rollbacks(
+    instance: &InstanceMetadata,
+    rollbacks: &[ModRollback],
+) -> Result<(), BackendError> {
+    let dir = rollback_dir(instance);
+    fs::create_dir_all(&dir)?;
+    fs::write(
+        dir.join(ROLLBACK_FILE_NAME),
+        serde_json::to_string_pretty(rollbacks)?,
+    )?;
+    Ok(())
+}
+
+/// The path of the file `file_name` in `dir`, disabled if `enabled` is false
+fn mod_path(dir: &Path, file_name: &str, enabled: bool) -> PathBuf {
+    match enabled {
+        true => dir.join(file_name),
+        false => dir.join(format!("{file_name}{DISABLED_SUFFIX}")),
+    }
+}
+
+/// Downloads the new version next to the mod and swaps it in, the replaced file is moved to [`ROLLBACK_DIR`]
+async fn apply_update(instance: &InstanceMetadata, update: &ModUpdate) -> Result<(), BackendError> {
+    // both names may come from the frontend, and the new one from Modrinth
+    for file_name in [&update.file_name, &update.file.filename] {
+        if !is_file_name(file_name) {
+            return Err(InstanceError::InvalidContentName(file_name.clone()).into());
+        }
+    }
+
+    let mods_dir = instance.instance_dir().join(ContentKind::Mod.dir_name());
+    let old_path = mod_path(&mods_dir, &update.file_name, update.enabled);
+    let new_path = mod_path(&mods_dir, &update.file.filename, update.enabled);
+    if new_path != old_path && new_path.exists() {
+        return Err(InstanceError::ContentExists(update.file.filename.clone()).into());
+    }
+
+    // hidden, so the game never loads a partial download
+    let part_path = mods_dir.join(format!(".{}.part", update.file.filename));
+    REQUESTER
+        .builder()
+        .download_to(&update.file.url, &part_path)
+        .await?;
+
+    let sha512 = {
+        let part_path = part_path.clone();
+        tokio::task::spawn_blocking(move || hash_file(&part_path))
+            .await??
+            .sha512
+    };
+    if !sha512.eq_ignore_ascii_case(&update.file.hashes.sha512) {
+        _ = fs::remove_file(&part_path);
+        return Err(HttpError::HashMismatch(update.file.url.clone()).into());
+    }
+
+    let rollback_dir = rollback_dir(instance);
+    fs::create_dir_all(&rollback_dir)?;
+    let kept_path = rollback_dir.join(&update.file_name);
+    fs::rename(&old_path, &kept_path)?;
+
+    if let Err(err) = fs::rename(&part_path, &new_path) {
+        _ = fs::rename(&kept_path, &old_path);
+        _ = fs::remove_file(&part_path);
+        return Err(err.into());
+    }
+    Ok(())
+}
+
+/// Installs the updates found by [`check_mod_updates`], the worlds are backed up first if the instance's config asks for it
+///
+/// the replaced mods are kept to be restored with [`rollback_mod`]
+pub async fn update_mods(
+    instance: &InstanceMetadata,
+    updates: &[ModUpdate],
+) -> Result<(), BackendError> {
+    GAME_PROCESSES.ensure_not_running(&instance.id)?;
+    if updates.is_empty() {
+        return Ok(());
+    }
+
+    let instance_clone = instance.clone();
+    tokio::task::spawn_blocking(move || {
+        backups::auto_backup(&instance_clone, BackupTrigger::BeforeUpdate)
+    })
+    .await??;
+
+    let mut rollbacks = list_rollbacks(instance)?;
+    for update in updates {
+        apply_update(instance, update).await?;
+
+        let mods_dir = Path::new(ContentKind::Mod.dir_name());
+        let previous_lock =
+            content_lock::unlock_files(instance, [mods_dir.join(&update.file_name).as_path()])?
+                .pop();
+        // a dependency stays one when updated
+        let reason = previous_lock
+            .as_ref()
+            .map_or(InstallReason::User, |locked| locked.reason);
+        let origin = FileOrigin {
+            source: ContentSource::Modrinth,
+            project_id: Some(update.project_id.clone()),
+            version_id: Some(update.version_id.clone()),
+            url: Some(update.file.url.clone()),
+            reason,
+        };
+        content_lock::lock_files(instance, [(mods_dir.join(&update.file.filename), origin)])?;
+
+        // an older rollback of the same file name would restore the wrong file
+        rollbacks.retain(|rollback| rollback.file_name != update.file.filename);
+        rollbacks.push(ModRollback {
+            file_name: update.file.filename.clone(),
+            previous_file_name: update.file_name.clone(),
+            previous_lock,
+        });
+        // written after every update, so the ones done before a failure can be rolled back
+        write_rollbacks(instance, &rollbacks)?;
+
+        log!(
+            "Updated '{}' from {} to {}",
+            update.file_name,
+            update.current_version,
+            update.new_version
+        );
+    }
+    Ok(())
+}
+
+/// Puts back the mod an update replaced with `file_name`, enabled or disabled like the update is
+///
+/// returns the name of the restored file
+pub fn rollback_mod(instance: &InstanceMetadata, file_name: &str) -> Result<String, BackendError> {
+    GAME_PROCESSES.ensure_not_running(&instance.id)?;
+
+    let (file_name, _) = content::parse_file_name(file_name);
+    let mut rollbacks = list_rollbacks(instance)?;
+    let index = rollbacks
+        .iter()
+        .rposition(|rollback| rollback.file_name == file_name)
+        .ok_or_else(|| InstanceError::NoRollback(file_name.to_string()))?;
+    let rollback = rollbacks.remove(index);
+
+    let mods_dir = instance.instance_dir().join(ContentKind::Mod.dir_name());
+    let enabled = !mod_path(&mods_dir, file_name, false).exists();
+    let updated_path = mod_path(&mods_dir, file_name, enabled);
+    let kept_path = rollback_dir(instance).join(&rollback.previous_file_name);
+    if !kept_path.exists() {
+        return Err(InstanceError::NoRollback(file_name.to_string()).into());
+    }
+
+    // the updated file may have been deleted since
+    if updated_path.exists() {
+        fs::remove_file(&updated_path)?;
+    }
+    fs::rename(
+        kept_path,
+        mod_path(&mods_dir, &rollback.previous_file_name, enabled),
+    )?;
+    write_rollbacks(instance, &rollbacks)?;
+
+    let lock_dir = Path::new(ContentKind::Mod.dir_name());
+    content_lock::unlock_files(instance, [lock_dir.join(file_name).as_path()])?;
+    let origin = rollback
+        .previous_lock
+        .map_or_else(FileOrigin::manual, FileOrigin::from);
+    content_lock::lock_files(
+        instance,
+        [(lock_dir.join(&rollback.previous_file_name), origin)],
+    )?;
+
+    Ok(rollback.previous_file_name)
+}
@@ -1,19 +1,21 @@
 use std::{
     collections::HashMap,
-    fs::File,
-    io::BufReader,
-    path::{Path, PathBuf},
+    path::{Component, Path, PathBuf},
 };
 
 use futures_util::{stream::FuturesUnordered, StreamExt};
-use serde::Deserialize;
-use sl_core::REQUESTER;
-use sl_utils::errors::BackendError;
-use zip::ZipArchive;
+use serde::{Deserialize, Serialize};
+use sl_core::{launcher::instances::instance_metadata::ModLoader, REQUESTER};
+use sl_utils::{
+    errors::{BackendError, HttpError, InstanceImportErr},
+    wlog,
+};
 
-const MODRINTH_INDEX_NAME: &'static str = "modrinth.index.json";
+pub(crate) const MODRINTH_INDEX_NAME: &str = "modrinth.index.json";
+/// Overrides only applied on clients, after the common ones
+pub(crate) const CLIENT_OVERRIDES_DIR: &str = "client-overrides";
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ModrinthPack {
     pub dependencies: HashMap<DependencyID, String>,
@@ -22,33 +24,36 @@ pub struct ModrinthPack {
     pub game: String,
     pub name: String,
     pub version_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub summary: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ModrinthIndex {
     pub path: PathBuf,
     pub hashes: FileHashes,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub env: Option<Env>,
     pub downloads: Vec<String>,
     pub file_size: u32,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct FileHashes {
     pub sha1: String,
     pub sha512: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub other_hashes: Option<HashMap<String, String>>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Env {
     pub client: EnvTypes,
     pub server: EnvTypes,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(rename_all = "kebab-case")]
 pub enum EnvTypes {
     Required,
@@ -56,7 +61,7 @@ pub enum EnvTypes {
     Unsupported,
 }
 
-#[derive(Debug, Deserialize, Hash, PartialEq, Eq)]
+#[derive(Debug, Deserialize, Serialize, Hash, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
 pub enum DependencyID {
     Minecraft,
@@ -79,23 +84,28 @@ impl From<&str> for DependencyID {
     }
 }
 
-pub async fn unzip_modpack(mrpack: &Path, output_dir: &Path) -> Result<(), BackendError> {
-    let mut archive = ZipArchive::new(BufReader::new(File::open(mrpack)?))?;
-
-    for i in 0..archive.len() {
-        let mut file = archive.by_index(i)?;
-        let out_path = output_dir.join(file.name());
+impl DependencyID {
+    /// The mod loader this dependency is, `None` for Minecraft itself
+    pub fn mod_loader(&self) -> Option<ModLoader> {
+        match self {
+            Self::Minecraft => None,
+            Self::Forge => Some(ModLoader::Forge),
+            Self::Neoforge => Some(ModLoader::NeoForge),
+            Self::FabricLoader => Some(ModLoader::Fabric),
+            Self::QuiltLoader => Some(ModLoader::Quilt),
+        }
+    }
 
-        if file.is_dir() {
-            tokio::fs::create_dir_all(&out_path).await?;
-        } else {
-            if let Some(parent) = out_path.parent() {
-                tokio::fs::create_dir_all(parent).await?;
-            }
-            std::io::copy(&mut file, &mut File::create(&out_path)?)?;
+    /// The dependency a pack declares for `mod_loader`, `None` for vanilla
+    pub fn from_mod_loader(mod_loader: ModLoader) -> Option<Self> {
+        match mod_loader {
+            ModLoader::Vanilla => None,
+            ModLoader::Fabric => Some(Self::FabricLoader),
+            ModLoader::Quilt => Some(Self::QuiltLoader),
+            ModLoader::Forge => Some(Self::Forge),
+            ModLoader::NeoForge => Some(Self::Neoforge),
         }
     }
-    Ok(())
 }
 
 pub async fn read_modrinth_index(modpack_path: &Path) -> Result<ModrinthPack, BackendError> {
@@ -103,27 +113,103 @@ pub async fn read_modrinth_index(modpack_path: &Path) -> Result<ModrinthPack, Ba
     Ok(serde_json::from_str(&json)?)
 }
 
+impl ModrinthIndex {
+    /// Whether the client needs the file at all, files only servers use are skipped
+    pub fn is_client_supported(&self) -> bool {
+        !matches!(
+            self.env,
+            Some(Env {
+                client: EnvTypes::Unsupported,
+                ..
+            })
+        )
+    }
+
+    /// Whether the player may choose not to install the file
+    pub fn is_client_optional(&self) -> bool {
+        matches!(
+            self.env,
+            Some(Env {
+                client: EnvTypes::Optional,
+                ..
+            })
+        )
+    }
+
+    /// Whether the file stays inside the instance, the spec forbids absolute paths and `..`
+    pub fn has_safe_path(&self) -> bool {
+        self.path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+    }
+}
+
+/// Checks the size and the hashes of the file downloaded from `url` to `path`
+async fn verify_file(path: &Path, modpack_file: &ModrinthIndex, url: &str) -> Result<(), HttpError> {
+    let path = path.to_path_buf();
+    let hashes = modpack_file.hashes.clone();
+    let file_size = modpack_file.file_size;
+
+    let digest = tokio::task::spawn_blocking(move || sl_utils::fs::hash_file(path))
+        .await
+        .map_err(std::io::Error::other)??;
+
+    if digest.size != u64::from(file_size) {
+        return Err(HttpError::SizeMismatch(url.to_string()));
+    }
+    if !digest.sha1.eq_ignore_ascii_case(&hashes.sha1)
+        || !digest.sha512.eq_ignore_ascii_case(&hashes.sha512)
+    {
+        return Err(HttpError::HashMismatch(url.to_string()));
+    }
+    Ok(())
+}
+
+/// Downloads a file of the pack, trying its URLs in order until one gives the expected file
 async fn download_modpack_file(
-    path: &Path,
+    instance_path: &Path,
     modpack_file: &ModrinthIndex,
-) -> Result<(), BackendError> {
-    let path = path.join(&modpack_file.path);
+) -> Result<(), HttpError> {
+    let path = instance_path.join(&modpack_file.path);
     if let Some(parent) = path.parent() {
         tokio::fs::create_dir_all(parent).await?;
     }
 
-    REQUESTER
-        .builder()
-        .download_to(&modpack_file.downloads[0], &path)
-        .await?;
+    let mut last_err = HttpError::InvalidURL;
+    for url in &modpack_file.downloads {
+        let result = match REQUESTER.builder().download_to(url, &path).await {
+            Ok(()) => verify_file(&path, modpack_file, url).await,
+            Err(err) => Err(err),
+        };
+
+        match result {
+            Ok(()) => return Ok(()),
+            Err(err) => {
+                wlog!(
+                    "Failed to download '{}' from '{url}': {err}",
+                    modpack_file.path.display()
+                );
+                last_err = err;
+            }
+        }
+    }
 
-    Ok(())
+    // leave no corrupted file behind
+    _ = tokio::fs::remove_file(&path).await;
+    Err(last_err)
 }
 
+/// Downloads the files of a pack into the instance at `instance_path`
+///
+/// every file is attempted, the ones that couldn't be downloaded are listed in the error
 pub async fn download_modpack_files(
     instance_path: &Path,
     modpack_files: &[ModrinthIndex],
 ) -> Result<(), BackendError> {
+    if let Some(unsafe_file) = modpack_files.iter().find(|file| !file.has_safe_path()) {
+        return Err(InstanceImportErr::UnsafePath(unsafe_file.path.clone()).into());
+    }
+
     let modpack_files = modpack_files.to_vec();
     let mut tasks = FuturesUnordered::new();
 
@@ -131,13 +217,22 @@ pub async fn download_modpack_files(
         let instance_path = instance_path.to_path_buf();
 
         tasks.push(tokio::spawn(async move {
-            download_modpack_file(&instance_path, &modpack_file).await
+            let result = download_modpack_file(&instance_path, &modpack_file).await;
+            (modpack_file.path, result)
         }));
     }
 
+    let mut failed = Vec::new();
     while let Some(result) = tasks.next().await {
-        result??;
+        let (path, result) = result?;
+        if let Err(err) = result {
+            failed.push(format!("{} ({err})", path.display()));
+        }
     }
 
+    if !failed.is_empty() {
+        failed.sort();
+        return Err(InstanceImportErr::FilesFailed(failed).into());
+    }
     Ok(())
 }
@@ -0,0 +1,144 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+    path::{Path, PathBuf},
+};
+
+use sl_core::launcher::{instances::instance_metadata::InstanceMetadata, is_offline_mode};
+use sl_utils::{errors::BackendError, log, wlog};
+
+use crate::{
+    modrinth::{
+        api::project::query_versions_from_hashes,
+        mrpack::{
+            DependencyID, Env, EnvTypes, FileHashes, ModrinthIndex, ModrinthPack,
+            MODRINTH_INDEX_NAME,
+        },
+    },
+    pack_export::{self, is_disabled, to_pack_path},
+};
+
+struct HashedFile {
+    /// Relative to the instance's directory
+    path: PathBuf,
+    sha1: String,
+    sha512: String,
+    size: u64,
+}
+
+fn hash_file(root: &Path, path: PathBuf) -> io::Result<HashedFile> {
+    let digest = sl_utils::fs::hash_file(root.join(&path))?;
+    Ok(HashedFile {
+        path,
+        sha1: digest.sha1,
+        sha512: digest.sha512,
+        size: digest.size,
+    })
+}
+
+/// Looks up `files` on Modrinth, returning the index entries of the ones it knows about along with their paths
+///
+/// disabled files keep their [`DISABLED_SUFFIX`](sl_core::launcher::instances::content::DISABLED_SUFFIX) in the pack and are optional,
+/// so launchers asking about optional files can leave them out
+async fn resolve_files(files: &[HashedFile]) -> Result<Vec<(&Path, ModrinthIndex)>, BackendError> {
+    if files.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    if is_offline_mode() {
+        wlog!("Offline mode, every mod and pack is embedded in the exported pack");
+        return Ok(Vec::new());
+    }
+
+    let hashes = files
+        .iter()
+        .map(|file| file.sha1.clone())
+        .collect::<Vec<_>>();
+    let versions = query_versions_from_hashes(&hashes, "sha1").await?;
+
+    let resolved = files
+        .iter()
+        .filter_map(|file| {
+            let version = versions.get(&file.sha1)?;
+            let download = version
+                .files
+                .iter()
+                .find(|download| download.hashes.sha1 == file.sha1)?;
+
+            // the index stores sizes as u32, bigger files are embedded as overrides instead
+            let file_size = u32::try_from(file.size).ok()?;
+
+            let env = is_disabled(&file.path).then_some(Env {
+                client: EnvTypes::Optional,
+                server: EnvTypes::Optional,
+            });
+            let index = ModrinthIndex {
+                path: PathBuf::from(to_pack_path(&file.path)),
+                hashes: FileHashes {
+                    sha1: file.sha1.clone(),
+                    sha512: file.sha512.clone(),
+                    other_hashes: None,
+                },
+                env,
+                downloads: vec![download.url.clone()],
+                file_size,
+            };
+            Some((file.path.as_path(), index))
+        })
+        .collect();
+    Ok(resolved)
+}
+
+/// Exports `instance` as a Modrinth pack (.mrpack) to `output`
+///
+/// the mods, resource packs and shader packs Modrinth knows about are linked to, everything else is embedded in the pack's overrides
+pub async fn export_mrpack(
+    instance: &InstanceMetadata,
+    version_id: &str,
+    output: &Path,
+) -> Result<(), BackendError> {
+    let instance_dir = instance.instance_dir();
+
+    let candidates = pack_export::content_files(&instance_dir)?
+        .into_iter()
+        .map(|path| hash_file(&instance_dir, path))
+        .collect::<io::Result<Vec<_>>>()?;
+
+    let (linked, files): (HashSet<_>, Vec<_>) = resolve_files(&candidates)
+        .await?
+        .into_iter()
+        .map(|(path, index)| (path.to_path_buf(), index))
+        .unzip();
+
+    let mut dependencies = HashMap::from([(DependencyID::Minecraft, instance.mc_version.clone())]);
+    if let Some(loader) = DependencyID::from_mod_loader(instance.mod_loader) {
+        dependencies.insert(loader, instance.mod_loader_version.clone());
+    }
+
+    let pack = ModrinthPack {
+        dependencies,
+        files,
+        format_version: 1,
+        game: "minecraft".to_string(),
+        name: instance.name.clone(),
+        version_id: version_id.to_string(),
+        summary: None,
+    };
+
+    let manifest = serde_json::to_vec_pretty(&pack)?;
+    let embedded = pack_export::write_pack(
+        output,
+        MODRINTH_INDEX_NAME,
+        &manifest,
+        &instance_dir,
+        &linked,
+    )?;
+
+    log!(
+        "Exported instance '{}' to '{}', {} file(s) linked to Modrinth and {embedded} embedded",
+        instance.name,
+        output.display(),
+        linked.len()
+    );
+    Ok(())
+}
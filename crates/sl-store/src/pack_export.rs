@@ -0,0 +1,109 @@
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+};
+
+use sl_core::launcher::instances::{
+    content, content_lock::CONTENT_LOCK_FILE_NAME, instance_config::CONFIG_FILE_NAME,
+    INSTANCE_FILE_NAME,
+};
+use sl_utils::{errors::BackendError, zip::ZipBuilder};
+
+/// Directories whose files are looked up on the platform, so the pack links to them instead of embedding them
+const CONTENT_DIRS: &[&str] = &["mods", "resourcepacks", "shaderpacks"];
+
+/// Top level entries of an instance that are never exported, they are either the launcher's own or produced by playing
+const EXCLUDED: &[&str] = &[
+    CONFIG_FILE_NAME,
+    CONTENT_LOCK_FILE_NAME,
+    "logs",
+    "crash-reports",
+    "screenshots",
+    "saves",
+];
+
+pub(crate) const OVERRIDES_DIR: &str = "overrides";
+
+fn is_excluded(file_name: &str) -> bool {
+    // the metadata with its backups and the PID file of a running game
+    EXCLUDED.contains(&file_name)
+        || file_name.starts_with(INSTANCE_FILE_NAME)
+        || file_name.starts_with('.')
+}
+
+/// Collects the paths, relative to `root`, of every file under `root/relative`
+fn collect_files(root: &Path, relative: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(root.join(relative))? {
+        let entry = entry?;
+        let path = relative.join(entry.file_name());
+
+        if entry.file_type()?.is_dir() {
+            collect_files(root, &path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// The mods, resource packs and shader packs of the instance, relative to its directory
+pub(crate) fn content_files(instance_dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for dir in CONTENT_DIRS {
+        if instance_dir.join(dir).is_dir() {
+            collect_files(instance_dir, Path::new(dir), &mut files)?;
+        }
+    }
+    Ok(files)
+}
+
+/// Whether the content file at `path` was disabled by the player, see [`content::DISABLED_SUFFIX`]
+pub(crate) fn is_disabled(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|file_name| !content::parse_file_name(&file_name.to_string_lossy()).1)
+}
+
+/// Packs always use `/` as their path separator
+pub(crate) fn to_pack_path(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Writes a pack made of its manifest and of every exported file of the instance that isn't in `linked`, which go into `overrides/`
+///
+/// returns how many files were embedded
+pub(crate) fn write_pack(
+    output: &Path,
+    manifest_name: &str,
+    manifest: &[u8],
+    instance_dir: &Path,
+    linked: &HashSet<PathBuf>,
+) -> Result<usize, BackendError> {
+    let mut overrides = Vec::new();
+    for entry in fs::read_dir(instance_dir)? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        if is_excluded(&file_name.to_string_lossy()) {
+            continue;
+        }
+
+        match entry.file_type()?.is_dir() {
+            true => collect_files(instance_dir, Path::new(&file_name), &mut overrides)?,
+            false => overrides.push(PathBuf::from(file_name)),
+        }
+    }
+    overrides.retain(|path| !linked.contains(path));
+
+    let mut zip = ZipBuilder::new(File::create(output)?);
+    zip.append_bytes(manifest, manifest_name)?;
+    for path in &overrides {
+        zip.append_file(instance_dir.join(path), Path::new(OVERRIDES_DIR).join(path))?;
+    }
+    zip.finish()?;
+
+    Ok(overrides.len())
+}
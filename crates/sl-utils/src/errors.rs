@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use thiserror::Error;
 use tokio::task::JoinError;
 use zip::result::ZipError;
@@ -14,6 +16,14 @@ pub enum HttpError {
     Status(reqwest::StatusCode),
     #[error("Invalid Header Value: {0}")]
     InvalidHeaderValue(#[from] reqwest::header::InvalidHeaderValue),
+    #[error("Missing file '{}' while in offline mode, go online once to download it", .0.display())]
+    OfflineMissingFile(PathBuf),
+    #[error("Cached file '{}' is unreadable, go online once to download it again", .0.display())]
+    UnreadableCachedFile(PathBuf),
+    #[error("File downloaded from '{0}' doesn't match its expected hash")]
+    HashMismatch(String),
+    #[error("File downloaded from '{0}' doesn't have its expected size")]
+    SizeMismatch(String),
     #[error("Unknown request error: {0}")]
     Other(reqwest::Error),
 }
@@ -34,6 +44,81 @@ pub enum InstanceError {
     Forge(#[from] ForgeInstallerErr),
     #[error("mod loader version isn't available for the given minecraft version")]
     IncompatibleModLoaderVersion,
+    #[error("Instance '{0}' is already running")]
+    AlreadyRunning(String),
+    #[error("Instance '{0}' isn't running")]
+    NotRunning(String),
+    #[error("Invalid config: {0}")]
+    InvalidConfig(String),
+    #[error("Instance '{0}' has no logged runs")]
+    NoRuns(String),
+    #[error("'{0}' was not found in the instance")]
+    ContentNotFound(String),
+    #[error("Both '{0}' and its disabled copy exist, remove one of them first")]
+    ContentConflict(String),
+    #[error("'{0}' is already installed, update it instead of installing it again")]
+    ProjectAlreadyInstalled(String),
+    #[error("'{0}' isn't a valid file name")]
+    InvalidContentName(String),
+    #[error("'{0}' already exists in the instance")]
+    ContentExists(String),
+    #[error("'{0}' wasn't installed by an update, there is nothing to roll back")]
+    NoRollback(String),
+    #[error("'{0}' is incompatible with '{1}'")]
+    IncompatibleProject(String, String),
+    #[error("'{0}' requires '{1}', which has no version for this instance")]
+    MissingDependency(String, String),
+    #[error("The {0} hook failed: {1}")]
+    HookFailed(&'static str, String),
+    #[error("The game's heap is set to {requested} MiB but the system only has {available} MiB of memory")]
+    NotEnoughMemory { requested: usize, available: usize },
+}
+
+#[derive(Debug, Error)]
+pub enum SchemaError {
+    #[error("'{}' was written by a newer version of the launcher (scheme version {found}, newest supported is {supported}), update the launcher to use it", path.display())]
+    UnsupportedVersion {
+        path: PathBuf,
+        found: u64,
+        supported: u64,
+    },
+    #[error("'{}' is corrupted: {source}", path.display())]
+    Corrupted {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    #[error("I/O error: {0}")]
+    IO(#[from] std::io::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum BackupError {
+    #[error("World '{0}' was not found")]
+    WorldNotFound(String),
+    #[error("'{0}' isn't a valid world name")]
+    InvalidWorldName(String),
+    #[error("World '{0}' already exists, restore the backup under another name")]
+    WorldExists(String),
+    #[error("Backup '{0}' was not found")]
+    SnapshotNotFound(String),
+    #[error("Chunk {0} of the backup store is missing or corrupted")]
+    CorruptedChunk(String),
+    #[error("Instance '{0}' is running, stop it before restoring a backup")]
+    InstanceRunning(String),
+    #[error("I/O error: {0}")]
+    IO(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum ModMetadataError {
+    #[error("The mod's '{0}' is malformed: {1}")]
+    Malformed(&'static str, String),
+    #[error("Zip error: {0}")]
+    Zip(#[from] ZipError),
+    #[error("I/O error: {0}")]
+    IO(#[from] std::io::Error),
 }
 
 #[derive(Debug, Error)]
@@ -79,8 +164,22 @@ pub enum InstanceImportErr {
     NotAnInstance,
     #[error("Attempt to import a corrupted Instance")]
     Corrupted,
+    #[error("An instance named '{0}' already exists")]
+    NameTaken(String),
+    #[error("The instance uses Minecraft {0}, which isn't a known version")]
+    MinecraftVersionNotFound(String),
+    #[error("The instance uses the mod loader '{0}', which isn't supported")]
+    UnsupportedModLoader(String),
+    #[error("The pack wants to write '{}' outside of the instance", .0.display())]
+    UnsafePath(PathBuf),
+    #[error("The pack's overrides '{0}' aren't a directory at the root of the pack")]
+    UnsafeOverrides(String),
+    #[error("{} of the pack's files couldn't be downloaded: {}", .0.len(), .0.join(", "))]
+    FilesFailed(Vec<String>),
     #[error("Fatal serde failure: {0}")]
     SerializationError(#[from] serde_json::Error),
+    #[error("{0}")]
+    Schema(#[from] SchemaError),
 }
 
 #[derive(Debug, Error)]
@@ -109,6 +208,12 @@ pub enum BackendError {
     StrumParseError(#[from] strum::ParseError),
     #[error("Microsoft auth service error: {0}")]
     MicrosoftAuthServiceError(#[from] MicrosoftAuthServiceError),
+    #[error("{0}")]
+    SchemaError(#[from] SchemaError),
+    #[error("Backup error: {0}")]
+    BackupError(#[from] BackupError),
+    #[error("Mod metadata error: {0}")]
+    ModMetadataError(#[from] ModMetadataError),
 }
 
 impl From<reqwest::Error> for HttpError {
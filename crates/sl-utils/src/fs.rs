@@ -1,4 +1,56 @@
-use std::path::Path;
+use std::{
+    fs::File,
+    io::{BufReader, Read},
+    path::{Component, Path},
+};
+
+use sha1::{Digest, Sha1};
+use sha2::Sha512;
+
+/// Whether `name` names a file right inside a directory, rather than a path that could lead out of it
+///
+/// both `/` and `\\` are refused whatever the platform, as names often come from other systems
+pub fn is_file_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    !name.contains(['/', '\\'])
+        && matches!(
+            (components.next(), components.next()),
+            (Some(Component::Normal(_)), None)
+        )
+}
+
+/// The hashes and size of a file, the ones Modrinth and the content lock identify files with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDigest {
+    pub sha1: String,
+    pub sha512: String,
+    pub size: u64,
+}
+
+/// Hashes the file at `path` with SHA-1 and SHA-512 at once, reading it in chunks instead of all at once
+pub fn hash_file(path: impl AsRef<Path>) -> std::io::Result<FileDigest> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut sha1 = Sha1::new();
+    let mut sha512 = Sha512::new();
+    let mut buffer = [0; 64 * 1024];
+    let mut size = 0u64;
+
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        sha1.update(&buffer[..read]);
+        sha512.update(&buffer[..read]);
+        size += read as u64;
+    }
+
+    Ok(FileDigest {
+        sha1: hex::encode(sha1.finalize()),
+        sha512: hex::encode(sha512.finalize()),
+        size,
+    })
+}
 
 /// Some helper function to recursively copy a directory
 pub fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> std::io::Result<()> {
@@ -18,3 +70,71 @@ pub fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> std::io::Re
     }
     Ok(())
 }
+
+/// Shares the content of the file at `src` with a new file at `dst` instead of copying it when the filesystem allows it
+///
+/// tries a reflink (copy on write) and copies otherwise, never a hardlink,
+/// as writing to either file in place would then change the other one too
+pub fn link_or_copy(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> std::io::Result<()> {
+    reflink_copy::reflink_or_copy(src, dst)?;
+    Ok(())
+}
+
+/// Same as [`copy_dir_all`] but shares the files with [`link_or_copy`]
+pub fn link_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> std::io::Result<()> {
+    std::fs::create_dir_all(&dst)?;
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        let ty = entry.file_type()?;
+
+        let src_path = entry.path();
+        let dest_path = dst.as_ref().join(entry.file_name());
+
+        if ty.is_dir() {
+            link_dir_all(src_path, dest_path)?
+        } else {
+            link_or_copy(src_path, dest_path)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_names_stay_in_their_directory() {
+        assert!(is_file_name("sodium.jar"));
+        assert!(is_file_name("..sodium.jar"));
+        for name in [
+            "",
+            ".",
+            "..",
+            "../sodium.jar",
+            "mods/sodium.jar",
+            "..\\sodium.jar",
+            "/etc",
+        ] {
+            assert!(!is_file_name(name), "'{name}' was accepted");
+        }
+    }
+
+    #[test]
+    fn hash_file_spans_several_reads() {
+        let path = std::env::temp_dir().join(format!("sl-hash-file-{}", std::process::id()));
+        // larger than the read buffer, so the hashes are updated more than once
+        std::fs::write(&path, b"abc".repeat(30_000)).unwrap();
+        let digest = hash_file(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            digest.unwrap(),
+            FileDigest {
+                sha1: "06db002c37a0c255b638356da168229ff287e1e3".to_string(),
+                sha512: "3d05bf827466fabff214365bdc5d2d8d42027fcc2087863bf33f9cf884596a28607fa3b5e9709423c27344ef51de889e74b17153b36f024e04a71566bc2dd855".to_string(),
+                size: 90_000,
+            }
+        );
+    }
+}
@@ -2,4 +2,5 @@ pub mod fs;
 pub mod errors;
 pub mod log;
 pub mod zip;
+pub mod progress;
 pub mod requester;
@@ -0,0 +1,116 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde::Serialize;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+/// A unit of (usually long running) work the launcher reports progress for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    Assets,
+    Libraries,
+    ClientJar,
+    ForgeInstaller,
+    NeoForgeInstaller,
+    Java,
+}
+
+impl Phase {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Phase::Assets => "Assets",
+            Phase::Libraries => "Libraries",
+            Phase::ClientJar => "Client jar",
+            Phase::ForgeInstaller => "Forge installer",
+            Phase::NeoForgeInstaller => "NeoForge installer",
+            Phase::Java => "Java runtime",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    PhaseStarted {
+        phase: Phase,
+        /// The amount of items this phase will go through, if known
+        total: Option<usize>,
+    },
+    ItemFinished {
+        phase: Phase,
+        current: usize,
+        total: usize,
+    },
+    BytesDownloaded {
+        phase: Phase,
+        downloaded: u64,
+        total: Option<u64>,
+    },
+    PhaseFinished {
+        phase: Phase,
+    },
+}
+
+/// A cheap to clone handle that progress events are sent through, does nothing if nobody is listening
+#[derive(Debug, Clone, Default)]
+pub struct Progress {
+    tx: Option<UnboundedSender<ProgressEvent>>,
+}
+
+impl Progress {
+    /// Creates a new progress handle, and the receiver of its events
+    pub fn channel() -> (Self, UnboundedReceiver<ProgressEvent>) {
+        let (tx, rx) = unbounded_channel();
+        (Self { tx: Some(tx) }, rx)
+    }
+
+    /// A progress handle that discards every event
+    pub const fn none() -> Self {
+        Self { tx: None }
+    }
+
+    pub fn send(&self, event: ProgressEvent) {
+        if let Some(ref tx) = self.tx {
+            let _ = tx.send(event);
+        }
+    }
+
+    /// Reports the start of `phase` and returns a tracker for its items
+    pub fn start(&self, phase: Phase, total: usize) -> PhaseTracker<'_> {
+        self.send(ProgressEvent::PhaseStarted {
+            phase,
+            total: Some(total),
+        });
+
+        PhaseTracker {
+            progress: self,
+            phase,
+            total,
+            current: AtomicUsize::new(0),
+        }
+    }
+}
+
+/// Counts the finished items of a phase, can be shared between concurrent tasks
+#[derive(Debug)]
+pub struct PhaseTracker<'a> {
+    progress: &'a Progress,
+    phase: Phase,
+    total: usize,
+    current: AtomicUsize,
+}
+
+impl PhaseTracker<'_> {
+    pub fn item_finished(&self) {
+        let current = self.current.fetch_add(1, Ordering::Relaxed) + 1;
+        self.progress.send(ProgressEvent::ItemFinished {
+            phase: self.phase,
+            current,
+            total: self.total,
+        });
+    }
+
+    pub fn finish(self) {
+        self.progress.send(ProgressEvent::PhaseFinished { phase: self.phase });
+    }
+}
@@ -1,18 +1,23 @@
 use std::{path::Path, time::Duration};
 
 use bytes::Bytes;
-use reqwest::{header::HeaderValue, Client, Response};
+use reqwest::{header::HeaderValue, Client, Method, Response};
 use tokio::{io::AsyncWriteExt, sync::mpsc::Sender, time::sleep};
 use tokio_stream::StreamExt;
 use url::Url;
 
-use crate::{errors::HttpError, log};
+use crate::{
+    errors::HttpError,
+    log,
+    progress::{Phase, Progress, ProgressEvent},
+};
 
 pub struct RequestBuilder<'a> {
     requester: &'a Requester,
     retries: u32,
     retry_timeout: Duration,
     progress_tx: Option<Sender<f32>>,
+    progress: Option<(Progress, Phase)>,
 }
 
 impl<'a> RequestBuilder<'a> {
@@ -22,6 +27,7 @@ impl<'a> RequestBuilder<'a> {
             retries: 3,
             retry_timeout: Duration::from_secs(1),
             progress_tx: None,
+            progress: None,
         }
     }
 
@@ -40,6 +46,12 @@ impl<'a> RequestBuilder<'a> {
         self
     }
 
+    /// Reports the downloaded bytes as [`ProgressEvent::BytesDownloaded`] events of `phase`
+    pub fn progress(mut self, progress: &Progress, phase: Phase) -> Self {
+        self.progress = Some((progress.clone(), phase));
+        self
+    }
+
     pub async fn download(&self, url: &str) -> Result<Bytes, HttpError> {
         self.requester
             .download(url, self.retries, self.retry_timeout)
@@ -54,6 +66,7 @@ impl<'a> RequestBuilder<'a> {
                 self.retries,
                 self.retry_timeout,
                 self.progress_tx.clone(),
+                self.progress.clone(),
             )
             .await
     }
@@ -69,6 +82,7 @@ impl Requester {
         Self {
             client: Client::builder()
                 .user_agent("SynthLauncher/1.0")
+                .connect_timeout(Duration::from_secs(10))
                 .build()
                 .expect("Failed to create a HTTP Client"),
         }
@@ -78,9 +92,10 @@ impl Requester {
         RequestBuilder::new(&self)
     }
 
-    pub async fn get(&self, url: &str) -> Result<Response, reqwest::Error> {
+    /// Starts a request, authenticated for the APIs that require it
+    fn request(&self, method: Method, url: &str) -> reqwest::RequestBuilder {
         let parsed = Url::parse(url).expect("Invalid URL");
-        let mut builder = self.client.get(url);
+        let mut builder = self.client.request(method, url);
 
         if let Some(domain) = parsed.domain() {
             if domain == "api.curseforge.com" {
@@ -93,7 +108,11 @@ impl Requester {
             }
         }
 
-        builder.send().await
+        builder
+    }
+
+    pub async fn get(&self, url: &str) -> Result<Response, reqwest::Error> {
+        self.request(Method::GET, url).send().await
     }
 
     pub async fn get_json<T: serde::de::DeserializeOwned>(
@@ -102,6 +121,18 @@ impl Requester {
     ) -> Result<T, HttpError> {
         Ok(self.get(url).await?.json::<T>().await?)
     }
+
+    pub async fn post_json<B: serde::Serialize + ?Sized, T: serde::de::DeserializeOwned>(
+        &self,
+        url: &str,
+        body: &B,
+    ) -> Result<T, HttpError> {
+        let response = self.request(Method::POST, url).json(body).send().await?;
+        if !response.status().is_success() {
+            return Err(HttpError::Status(response.status()));
+        }
+        Ok(response.json::<T>().await?)
+    }
 }
 
 impl Requester {
@@ -166,12 +197,14 @@ impl Requester {
         max_retries: u32,
         delay: Duration,
         progress_tx: Option<Sender<f32>>,
+        progress: Option<(Progress, Phase)>,
     ) -> Result<(), HttpError> {
         self.retry(
             || {
                 let url = url.to_string();
                 let path = path.to_path_buf();
                 let progress_tx = progress_tx.clone();
+                let progress = progress.clone();
                 let client = self.clone();
 
                 async move {
@@ -181,7 +214,8 @@ impl Requester {
                     }
 
                     let mut file = tokio::fs::File::create(&path).await?;
-                    let total_size = response.content_length().unwrap_or(0);
+                    let content_length = response.content_length();
+                    let total_size = content_length.unwrap_or(0);
                     let mut downloaded = 0u64;
                     let mut stream = response.bytes_stream();
 
@@ -194,6 +228,14 @@ impl Requester {
                         file.write_all(&chunk).await?;
                         downloaded += chunk.len() as u64;
 
+                        if let Some((progress, phase)) = &progress {
+                            progress.send(ProgressEvent::BytesDownloaded {
+                                phase: *phase,
+                                downloaded,
+                                total: content_length,
+                            });
+                        }
+
                         if let Some(tx) = &progress_tx {
                             if total_size > 0 {
                                 let percent = (downloaded as f32 / total_size as f32) * 100.0;
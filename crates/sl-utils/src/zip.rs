@@ -45,7 +45,8 @@ impl<W: Write + Seek> ZipBuilder<W> {
 
     fn append_path_all(&mut self, path: &Path) -> Result<(), ZipError> {
         let mut current = Some(path);
-        while let Some(path) = current {
+        // the parent of a top level entry is the empty path, which isn't a directory of the archive
+        while let Some(path) = current.filter(|path| !path.as_os_str().is_empty()) {
             self.append_dir_existing(path)?;
             current = path.parent();
         }
@@ -107,6 +108,25 @@ impl<W: Write + Seek> ZipBuilder<W> {
         std::io::copy(&mut reader, self.inner.by_ref())?;
         Ok(())
     }
+
+    /// Appends a file with the given content to the archive, taking in a path in the archive
+    pub fn append_bytes<PA: AsRef<Path>>(
+        &mut self,
+        data: &[u8],
+        path_in_archive: PA,
+    ) -> Result<(), ZipError> {
+        let path_in_archive = path_in_archive.as_ref();
+
+        let name = zip::unstable::path_to_string(path_in_archive);
+        if let Some(parent) = path_in_archive.parent() {
+            self.append_path_all(parent)?;
+        }
+
+        let options = zip::write::SimpleFileOptions::default();
+        self.inner.start_file(name, options)?;
+        self.inner.write_all(data)?;
+        Ok(())
+    }
 }
 
 pub struct ZipExtractor<'a, R: Read + Seek> {
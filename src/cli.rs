@@ -1,15 +1,30 @@
 use std::path::PathBuf;
 
-use clap::{Args, Parser, Subcommand};
-use sl_core::launcher::instances::instance_metadata::ModLoader;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use sl_core::launcher::instances::{
+    content::ContentKind, instance_metadata::ModLoader, instance_query::SortBy,
+};
 
 #[derive(Parser)]
 #[command(about, long_about = None)]
 pub struct Cli {
+    /// Only use cached manifests and already downloaded files
+    #[arg(long, global = true)]
+    pub offline: bool,
     #[command(subcommand)]
     pub command: Commands,
 }
 
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ExportFormat {
+    /// A zip of the instance's directory, only this launcher can import it
+    Zip,
+    /// A Modrinth pack, mods and packs available on Modrinth are linked to instead of embedded
+    Mrpack,
+    /// A CurseForge modpack zip, mods and packs available on CurseForge are linked to instead of embedded
+    Curseforge,
+}
+
 #[derive(Args, Default)]
 pub struct LoaderInfo {
     /// Can be "vanilla"/"fabric"/"quilt"/"forge"
@@ -20,15 +35,24 @@ pub struct LoaderInfo {
 
 #[derive(Subcommand)]
 pub enum Commands {
-    /// Imports an instance from a given path
+    /// Imports an instance from a directory or Zip file, MultiMC/Prism, official launcher and ATLauncher instances are converted
+    /// and Modrinth (.mrpack) and CurseForge modpacks are installed
     Import {
         path: PathBuf,
+        /// The name of the imported instance, the one it had or the pack's own name by default
+        #[arg(long)]
+        name: Option<String>,
     },
     /// Exports an instance to a given path
     Export {
         instance_name: String,
         #[arg(short, long)]
         output: PathBuf,
+        #[arg(long, value_enum, default_value_t = ExportFormat::Zip)]
+        format: ExportFormat,
+        /// The version of the exported pack, for the formats that have one
+        #[arg(long, default_value = "1.0.0")]
+        pack_version: String,
     },
 
     /// Creates a Minecraft instance
@@ -45,6 +69,93 @@ pub enum Commands {
     Launch {
         #[arg(required = true)]
         instance_name: String,
+        /// Joins the given singleplayer world once the game starts
+        #[arg(long, group = "quick_play")]
+        world: Option<String>,
+        /// Joins the given server once the game starts
+        #[arg(long, group = "quick_play")]
+        server: Option<String>,
+        /// Joins the realm with the given ID once the game starts
+        #[arg(long, group = "quick_play")]
+        realm: Option<String>,
+    },
+
+    /// Stops a running Minecraft instance, killing it if it doesn't exit in time
+    Stop {
+        #[arg(required = true)]
+        instance_name: String,
+        /// Kills the instance right away
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Lists the running Minecraft instances
+    ListRunning,
+
+    /// Shows the logs of an instance's runs
+    Logs {
+        #[arg(required = true)]
+        instance_name: String,
+        #[command(subcommand)]
+        action: LogsAction,
+    },
+
+    /// Creates a new instance from an existing one, sharing its mods and resource packs on disk when possible
+    Duplicate {
+        #[arg(required = true)]
+        instance_name: String,
+        #[arg(required = true)]
+        new_name: String,
+        /// Leaves the worlds out
+        #[arg(long)]
+        no_worlds: bool,
+        /// Leaves the screenshots out
+        #[arg(long)]
+        no_screenshots: bool,
+        /// Leaves the logs and crash reports out
+        #[arg(long)]
+        no_logs: bool,
+    },
+
+    /// Renames a Minecraft instance, its directory stays the same
+    Rename {
+        #[arg(required = true)]
+        instance_name: String,
+        #[arg(required = true)]
+        new_name: String,
+    },
+
+    /// Backs up, lists and restores the worlds of an instance
+    Backup {
+        #[arg(required = true)]
+        instance_name: String,
+        #[command(subcommand)]
+        action: BackupAction,
+    },
+
+    /// Lists, enables and disables the mods, resource packs and shader packs of an instance
+    Content {
+        #[arg(required = true)]
+        instance_name: String,
+        /// Can be "mod"/"resourcepack"/"shaderpack"
+        #[arg(long, default_value_t = ContentKind::Mod)]
+        kind: ContentKind,
+        #[command(subcommand)]
+        action: ContentAction,
+    },
+
+    /// Checks the mods of an instance for updates on Modrinth and installs them
+    Mods {
+        #[arg(required = true)]
+        instance_name: String,
+        #[command(subcommand)]
+        action: ModsAction,
+    },
+
+    /// Verifies every file of a Minecraft instance, re-downloading the corrupted ones
+    Repair {
+        #[arg(required = true)]
+        instance_name: String,
     },
 
     /// Adds an offline player account
@@ -62,8 +173,57 @@ pub enum Commands {
         name: String,
     },
 
-    /// Lists all player instances
-    ListInstances,
+    /// Lists the instances, all of them unless filtered
+    ListInstances {
+        /// Only lists the instances whose name contains this
+        #[arg(long)]
+        search: Option<String>,
+        /// Only lists the instances of this group
+        #[arg(long)]
+        group: Option<String>,
+        /// Only lists the instances having this tag, can be repeated
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        /// Only lists the favorite instances
+        #[arg(long)]
+        favorites: bool,
+        /// Only lists the instances using this mod loader
+        #[arg(long)]
+        loader: Option<ModLoader>,
+        /// Can be "name"/"last-played"/"playtime"/"launch-count"/"mc-version"
+        #[arg(long, default_value_t = SortBy::Name)]
+        sort: SortBy,
+        /// Reverses the order
+        #[arg(long)]
+        reverse: bool,
+    },
+
+    /// Moves an instance to a group, or out of its group if none is given
+    SetGroup {
+        #[arg(required = true)]
+        instance_name: String,
+        group: Option<String>,
+    },
+
+    /// Adds tags to an instance
+    Tag {
+        #[arg(required = true)]
+        instance_name: String,
+        #[arg(required = true)]
+        tags: Vec<String>,
+        /// Removes the tags instead
+        #[arg(long)]
+        remove: bool,
+    },
+
+    /// Marks an instance as a favorite
+    Favorite {
+        #[arg(required = true)]
+        instance_name: String,
+        /// Unmarks it instead
+        #[arg(long)]
+        remove: bool,
+    },
     
     /// Lists all player accounts
     ListAccounts,
@@ -74,3 +234,92 @@ pub enum Commands {
     /// Lists available Minecraft versions
     ListMinecraftVersions,
 }
+
+#[derive(Subcommand)]
+pub enum LogsAction {
+    /// Lists the runs whose logs are kept, newest first
+    List,
+    /// Prints the whole log of a run, the latest one if none is given
+    Read { run: Option<String> },
+    /// Prints the last lines of a run's log, the latest one if none is given
+    Tail {
+        run: Option<String>,
+        #[arg(short = 'n', long, default_value_t = 20)]
+        lines: usize,
+        /// Keeps printing the log until the run finishes
+        #[arg(short, long)]
+        follow: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ContentAction {
+    /// Lists the files and whether they are enabled
+    List,
+    /// Enables files, their names can be given with or without the `.disabled` suffix
+    Enable {
+        #[arg(required = true)]
+        files: Vec<String>,
+    },
+    /// Disables files, renaming them with a `.disabled` suffix so the game ignores them
+    Disable {
+        #[arg(required = true)]
+        files: Vec<String>,
+    },
+    /// Enables every file
+    EnableAll,
+    /// Disables every file, except the given ones which are enabled
+    DisableAll {
+        /// Can be repeated
+        #[arg(long)]
+        except: Vec<String>,
+    },
+    /// Deletes files, enabled or not, and removes them from the instance's content lock
+    Remove {
+        #[arg(required = true)]
+        files: Vec<String>,
+    },
+    /// Downloads the locked files missing from the instance and locks the mods and packs added by hand, whatever the kind
+    Sync,
+}
+
+#[derive(Subcommand)]
+pub enum ModsAction {
+    /// Lists the mods that have an update for the instance's Minecraft version and mod loader
+    CheckUpdates {
+        /// Also prints the changelogs
+        #[arg(long)]
+        changelog: bool,
+    },
+    /// Updates mods, every mod with an update if none is given
+    Update { mods: Vec<String> },
+    /// Puts back the mod an update replaced
+    Rollback { file_name: String },
+    /// Lists the mods that can be rolled back
+    ListRollbacks,
+}
+
+#[derive(Subcommand)]
+pub enum BackupAction {
+    /// Backs up a world, every world of the instance if none is given
+    Create {
+        #[arg(long)]
+        world: Option<String>,
+    },
+    /// Lists the backups, newest first
+    List {
+        #[arg(long)]
+        world: Option<String>,
+    },
+    /// Restores a backup over the world it was taken of, or as a new world
+    Restore {
+        backup: String,
+        /// The name of the world to restore the backup as
+        #[arg(long = "as")]
+        target: Option<String>,
+    },
+    /// Deletes a backup
+    Delete { backup: String },
+    /// Deletes the automatic backups the instance's config doesn't keep anymore
+    Prune,
+}
@@ -1,28 +1,120 @@
 use clap::Parser;
-use cli::{Cli, Commands};
+use cli::{BackupAction, Cli, Commands, ContentAction, ExportFormat, LogsAction, ModsAction};
 use sl_core::{
     launcher::{
+        backups::{self, BackupTrigger},
+        game_process::DEFAULT_STOP_TIMEOUT,
         init_launcher_dir,
+        set_offline_mode,
         instances::{
-            self, instance_importer::import_instance_from_path, instance_metadata::InstanceMetadata,
+            self, content, content_lock::{self, ContentLock}, instance_importer::import_instance_from_path, instance_metadata::InstanceMetadata,
+            crash_analyzer::CrashAnalysis, loaded_instance::QuickPlay, run_logs,
+            instance_duplicator::{self, DuplicateOptions},
+            instance_query::{query_instances, InstanceQuery},
         },
         player_accounts::{add_account, set_current_account, PlayerAccounts},
     },
-    VERSION_MANIFEST,
+    GAME_PROCESSES, VERSION_MANIFEST,
 };
 use sl_player::PlayerData;
-use sl_utils::{dlog, elog, errors::BackendError, log};
-use tokio::io::{self};
+use sl_store::{
+    curseforge::{
+        self, modpack::is_curseforge_modpack, modpack_exporter::export_curseforge_modpack,
+    },
+    modrinth::{
+        install_mrpack_from_path, mod_updater, mrpack::ModrinthIndex,
+        mrpack_exporter::export_mrpack,
+    },
+};
+use progress::progress_bars;
+use sl_utils::{
+    dlog, elog,
+    errors::{BackendError, InstanceError},
+    log, wlog,
+};
+use std::{io::SeekFrom, path::Path, time::Duration};
+use tokio::io::{self, AsyncSeekExt};
 
 mod cli;
+mod progress;
+
+fn report_crash(analysis: &CrashAnalysis) {
+    for report in &analysis.reports {
+        log!("Crash report written: {}", report.display());
+    }
+
+    for finding in &analysis.findings {
+        match finding.details {
+            Some(ref details) => elog!("{} ({details})", finding.kind.description()),
+            None => elog!("{}", finding.kind.description()),
+        }
+        log!("Suggested fix: {}", finding.kind.suggestion());
+        dlog!("Found in {}: {}", finding.source.display(), finding.evidence);
+    }
+}
+
+/// Asks whether to install an optional file of a pack, all of them are installed when not run from a terminal
+fn ask_optional_file(file: &ModrinthIndex) -> bool {
+    use std::io::{BufRead, IsTerminal, Write};
+
+    let stdin = std::io::stdin();
+    if !stdin.is_terminal() {
+        return true;
+    }
+
+    print!("Install the optional file '{}'? [Y/n] ", file.path.display());
+    _ = std::io::stdout().flush();
+
+    let mut answer = String::new();
+    if stdin.lock().read_line(&mut answer).is_err() {
+        return true;
+    }
+    !answer.trim().eq_ignore_ascii_case("n")
+}
+
+/// The given run, or the latest run of the instance if none is given
+fn resolve_run(
+    instance_name: &str,
+    instance_dir: &Path,
+    run: Option<String>,
+) -> Result<String, BackendError> {
+    if let Some(run) = run {
+        return Ok(run);
+    }
+
+    let latest = run_logs::list_runs(instance_dir)?.into_iter().next();
+    latest
+        .map(|run| run.id)
+        .ok_or_else(|| InstanceError::NoRuns(instance_name.to_string()).into())
+}
+
+async fn follow_run(instance_dir: &Path, run: &str) -> Result<(), BackendError> {
+    let Some(file) = run_logs::open_live_run(instance_dir, run)? else {
+        return Ok(());
+    };
+
+    let mut file = tokio::fs::File::from_std(file);
+    file.seek(SeekFrom::End(0)).await?;
+
+    let mut stdout = io::stdout();
+    loop {
+        let finished = run_logs::is_run_finished(instance_dir, run);
+        tokio::io::copy(&mut file, &mut stdout).await?;
+        if finished {
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+}
 
 async fn run_cli() -> Result<(), BackendError> {
+    let cli = Cli::parse();
+    set_offline_mode(cli.offline);
+
     init_launcher_dir().await?;
 
     dlog!("Launcher initialized!");
 
-    let cli = Cli::parse();
-
     match cli.command {
         Commands::Create {
             instance_name,
@@ -35,22 +127,299 @@ async fn run_cli() -> Result<(), BackendError> {
             let _ = InstanceMetadata::create(instance_name, &version, loader, loader_version, None)
                 .await?;
         }
-        Commands::Launch { instance_name } => {
+        Commands::Launch {
+            instance_name,
+            world,
+            server,
+            realm,
+        } => {
             let (instance, _) = instances::get_existing(&instance_name)?;
             dlog!("Instance found!");
-            let loaded_instance = instance.load_init().await?;
-            let (mut child, mut reader) = loaded_instance.execute().await?;
+            let (progress, progress_bars) = progress_bars();
+            let mut loaded_instance = instance.load_init(progress).await?;
+
+            let quick_play = world
+                .map(QuickPlay::Singleplayer)
+                .or(server.map(QuickPlay::Multiplayer))
+                .or(realm.map(QuickPlay::Realms));
+            if let Some(quick_play) = quick_play {
+                loaded_instance = loaded_instance.quick_play(quick_play);
+            }
+            let (mut game, mut reader) = loaded_instance.execute().await?;
+            // the instance and its progress handle are gone once executed
+            _ = progress_bars.await;
+            log!("Instance '{instance_name}' started with PID {}", game.pid());
+
+            // follows the log until the game exits, checking before copying so the last lines aren't lost
             let mut stdout = io::stdout();
-            tokio::io::copy(&mut reader, &mut stdout).await?;
+            loop {
+                let exited = game.state().is_exited();
+                tokio::io::copy(&mut reader, &mut stdout).await?;
+                if exited {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
 
-            if let Some(status) = child.try_wait()? {
-                if status.success() {
-                    dlog!("Instance exited successfully");
-                } else {
-                    dlog!("Instance exited with error code {:?}", status.code());
+            let state = game.wait().await;
+            if state.is_success() {
+                dlog!("Instance exited successfully");
+            } else {
+                wlog!("Instance exited abnormally: {state:?}");
+                report_crash(&game.analyze_crash());
+            }
+        }
+        Commands::Stop {
+            instance_name,
+            force,
+        } => {
+            let (instance, _) = instances::get_existing(&instance_name)?;
+            if force {
+                GAME_PROCESSES.kill(&instance.id)?;
+            } else {
+                let state = GAME_PROCESSES
+                    .stop(&instance.id, DEFAULT_STOP_TIMEOUT)
+                    .await?;
+                log!("Instance '{instance_name}' stopped: {state:?}");
+            }
+        }
+        Commands::ListRunning => {
+            let instances = instances::get_all_instances()?;
+            for game in GAME_PROCESSES.list() {
+                if !game.state.is_exited() {
+                    let name = instances
+                        .iter()
+                        .find(|instance| instance.id == game.instance_id)
+                        .map_or(game.instance_id.as_str(), |instance| &instance.name);
+                    println!(
+                        "{} (PID {}, started at {})",
+                        name, game.pid, game.started_at
+                    );
                 }
             }
         }
+        Commands::Duplicate {
+            instance_name,
+            new_name,
+            no_worlds,
+            no_screenshots,
+            no_logs,
+        } => {
+            let options = DuplicateOptions {
+                worlds: !no_worlds,
+                screenshots: !no_screenshots,
+                logs: !no_logs,
+            };
+            instance_duplicator::duplicate_instance(&instance_name, &new_name, options)?;
+        }
+        Commands::Rename {
+            instance_name,
+            new_name,
+        } => {
+            instances::rename_instance(&instance_name, &new_name)?;
+        }
+        Commands::Logs {
+            instance_name,
+            action,
+        } => {
+            let (_, instance_file) = instances::get_existing(&instance_name)?;
+            let instance_dir = instance_file.parent().expect("instance file has no parent");
+
+            match action {
+                LogsAction::List => {
+                    for run in run_logs::list_runs(instance_dir)? {
+                        let exit = match (run.exit_code, run.signal) {
+                            (Some(code), _) => format!("exit code {code}"),
+                            (None, Some(signal)) => format!("signal {signal}"),
+                            (None, None) if run.duration_secs.is_none() => "running".to_string(),
+                            (None, None) => "unknown exit".to_string(),
+                        };
+                        println!(
+                            "{}  account: {}, {}, duration: {}s",
+                            run.id,
+                            run.account_name,
+                            exit,
+                            run.duration_secs.unwrap_or_default()
+                        );
+                    }
+                }
+                LogsAction::Read { run } => {
+                    let run = resolve_run(&instance_name, instance_dir, run)?;
+                    print!("{}", run_logs::read_run(instance_dir, &run)?);
+                }
+                LogsAction::Tail { run, lines, follow } => {
+                    let run = resolve_run(&instance_name, instance_dir, run)?;
+                    for line in run_logs::tail_run(instance_dir, &run, lines)? {
+                        println!("{line}");
+                    }
+
+                    if follow {
+                        follow_run(instance_dir, &run).await?;
+                    }
+                }
+            }
+        }
+        Commands::Backup {
+            instance_name,
+            action,
+        } => {
+            let (instance, _) = instances::get_existing(&instance_name)?;
+
+            match action {
+                BackupAction::Create { world } => match world {
+                    Some(world) => {
+                        _ = backups::backup_world(&instance, &world, BackupTrigger::Manual)?
+                    }
+                    None => _ = backups::backup_instance(&instance, BackupTrigger::Manual)?,
+                },
+                BackupAction::List { world } => {
+                    for snapshot in backups::list_snapshots(&instance, world.as_deref())? {
+                        println!(
+                            "{}  world: {}, {}, {} bytes ({} stored)",
+                            snapshot.id,
+                            snapshot.world,
+                            snapshot.trigger,
+                            snapshot.size,
+                            snapshot.stored_size
+                        );
+                    }
+                }
+                BackupAction::Restore { backup, target } => {
+                    backups::restore(&instance, &backup, target.as_deref())?;
+                }
+                BackupAction::Delete { backup } => backups::delete_snapshot(&instance, &backup)?,
+                BackupAction::Prune => {
+                    let removed = backups::prune(&instance)?;
+                    log!("Deleted {removed} backup(s)");
+                }
+            }
+        }
+        Commands::Content {
+            instance_name,
+            kind,
+            action,
+        } => {
+            let (instance, _) = instances::get_existing(&instance_name)?;
+
+            match &action {
+                ContentAction::List => {
+                    let lock = ContentLock::load(&instance)?;
+                    for file in content::list(&instance, kind)? {
+                        let state = if file.enabled { "enabled" } else { "disabled" };
+                        let path = format!("{}/{}", kind.dir_name(), file.file_name);
+                        let source = lock
+                            .get(&path)
+                            .map_or("unlocked".to_string(), |locked| locked.source.to_string());
+                        println!("{}  {state}  {source}", file.file_name);
+                    }
+                }
+                ContentAction::Enable { files } | ContentAction::Disable { files } => {
+                    let enabled = matches!(action, ContentAction::Enable { .. });
+                    for file in files {
+                        content::set_enabled(&instance, kind, file, enabled)?;
+                    }
+                }
+                ContentAction::EnableAll => {
+                    let renamed = content::set_all_enabled(&instance, kind, true, &[])?;
+                    log!("Enabled {renamed} file(s)");
+                }
+                ContentAction::DisableAll { except } => {
+                    let renamed = content::set_all_enabled(&instance, kind, false, except)?;
+                    log!("Changed {renamed} file(s)");
+                }
+                ContentAction::Remove { files } => {
+                    for file in files {
+                        content::remove(&instance, kind, file)?;
+                        log!("Removed '{file}'");
+                    }
+                }
+                ContentAction::Sync => {
+                    let report = content_lock::sync(&instance).await?;
+                    for path in &report.added {
+                        log!("Locked '{path}', added by hand");
+                    }
+                    for path in &report.restored {
+                        log!("Restored '{path}'");
+                    }
+                    for missing in &report.missing {
+                        elog!("Missing {missing}");
+                    }
+                }
+            }
+        }
+        Commands::Mods {
+            instance_name,
+            action,
+        } => {
+            let (instance, _) = instances::get_existing(&instance_name)?;
+
+            match action {
+                ModsAction::CheckUpdates { changelog } => {
+                    let updates = mod_updater::check_mod_updates(&instance).await?;
+                    for update in &updates {
+                        println!(
+                            "{}: {} -> {} ({})",
+                            update.file_name,
+                            update.current_version,
+                            update.new_version,
+                            update.version_type
+                        );
+                        if let Some(text) = update.changelog.as_ref().filter(|_| changelog) {
+                            println!("{}\n", text.trim());
+                        }
+                    }
+                    log!("{} mod(s) can be updated", updates.len());
+                }
+                ModsAction::Update { mods } => {
+                    let mut updates = mod_updater::check_mod_updates(&instance).await?;
+                    if !mods.is_empty() {
+                        let (mods, missing): (Vec<_>, Vec<_>) = mods
+                            .iter()
+                            .map(|file_name| content::parse_file_name(file_name).0)
+                            .partition(|file_name| {
+                                updates.iter().any(|update| update.file_name == *file_name)
+                            });
+                        for file_name in missing {
+                            wlog!("'{file_name}' has no update");
+                        }
+                        updates.retain(|update| mods.contains(&update.file_name.as_str()));
+                    }
+                    mod_updater::update_mods(&instance, &updates).await?;
+                }
+                ModsAction::Rollback { file_name } => {
+                    let restored = mod_updater::rollback_mod(&instance, &file_name)?;
+                    log!("Restored '{restored}'");
+                }
+                ModsAction::ListRollbacks => {
+                    for rollback in mod_updater::list_rollbacks(&instance)? {
+                        println!("{} (replaced {})", rollback.file_name, rollback.previous_file_name);
+                    }
+                }
+            }
+        }
+        Commands::Repair { instance_name } => {
+            let (instance, _) = instances::get_existing(&instance_name)?;
+            let (progress, progress_bars) = progress_bars();
+            let loaded_instance = instance.load_init(progress).await?;
+            let report = loaded_instance.repair().await?;
+            drop(loaded_instance);
+            _ = progress_bars.await;
+
+            for path in &report.missing {
+                log!("Downloaded missing file: {}", path.display());
+            }
+            for path in &report.corrupted {
+                log!("Replaced corrupted file: {}", path.display());
+            }
+
+            log!(
+                "Checked {} files, fixed {} ({} missing, {} corrupted)",
+                report.checked,
+                report.fixed(),
+                report.missing.len(),
+                report.corrupted.len()
+            );
+        }
         Commands::AddOfflineAccount { name } => {
             add_account(PlayerData::offline(&name), name)?;
         }
@@ -73,11 +442,50 @@ async fn run_cli() -> Result<(), BackendError> {
         Commands::SetCurrentAccount { name } => {
             set_current_account(name)?;
         }
-        Commands::ListInstances => {
-            for (i, instance) in instances::get_all_instances()?.iter().enumerate() {
+        Commands::ListInstances {
+            search,
+            group,
+            tags,
+            favorites,
+            loader,
+            sort,
+            reverse,
+        } => {
+            let query = InstanceQuery {
+                search,
+                group,
+                tags,
+                favorites_only: favorites,
+                mod_loader: loader,
+                sort_by: sort,
+                reverse,
+                favorites_first: false,
+            };
+
+            for (i, instance) in query_instances(&query)?.iter().enumerate() {
                 println!("[{}] {:#?}", i, instance);
             }
         }
+        Commands::SetGroup {
+            instance_name,
+            group,
+        } => {
+            instances::set_group(&instance_name, group)?;
+        }
+        Commands::Tag {
+            instance_name,
+            tags,
+            remove,
+        } => match remove {
+            true => instances::remove_tags(&instance_name, &tags)?,
+            false => instances::add_tags(&instance_name, &tags)?,
+        },
+        Commands::Favorite {
+            instance_name,
+            remove,
+        } => {
+            instances::set_favorite(&instance_name, !remove)?;
+        }
         Commands::ListAccounts => {
             for (i, profile) in PlayerAccounts::load()?.accounts.iter() {
                 println!(
@@ -98,13 +506,40 @@ async fn run_cli() -> Result<(), BackendError> {
         Commands::Export {
             instance_name,
             output,
+            format,
+            pack_version,
         } => {
             let (instance, _) = instances::get_existing(&instance_name)?;
-            let exporter = instance.exporter_to_path(&output)?;
-            exporter.export()?;
+            match format {
+                ExportFormat::Zip => {
+                    let exporter = instance.exporter_to_path(&output)?;
+                    exporter.export()?;
+                }
+                ExportFormat::Mrpack => export_mrpack(&instance, &pack_version, &output).await?,
+                ExportFormat::Curseforge => {
+                    export_curseforge_modpack(&instance, &pack_version, &output).await?
+                }
+            }
         }
-        Commands::Import { path } => {
-            import_instance_from_path(&path)?;
+        Commands::Import { path, name } => {
+            let instance = if path.extension().is_some_and(|ext| ext == "mrpack") {
+                install_mrpack_from_path(&path, name, ask_optional_file).await?
+            } else if is_curseforge_modpack(&path) {
+                let install = curseforge::install_modpack_from_path(&path, name).await?;
+                for download in &install.manual_downloads {
+                    wlog!(
+                        "Download '{}' of {} from {} into '{}'",
+                        download.file_name,
+                        download.project_name,
+                        download.url,
+                        install.instance.instance_dir().join(&download.path).display()
+                    );
+                }
+                install.instance
+            } else {
+                import_instance_from_path(&path, name)?
+            };
+            log!("Imported instance '{}'", instance.name);
         }
     }
 
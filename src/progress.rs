@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use sl_utils::progress::{Phase, Progress, ProgressEvent};
+use tokio::{sync::mpsc::UnboundedReceiver, task::JoinHandle};
+
+/// Creates a progress handle whose events are rendered as progress bars in the terminal
+pub fn progress_bars() -> (Progress, JoinHandle<()>) {
+    let (progress, rx) = Progress::channel();
+    let handle = tokio::spawn(render(rx));
+    (progress, handle)
+}
+
+fn items_style() -> ProgressStyle {
+    ProgressStyle::with_template("{prefix:>18} [{bar:40}] {pos}/{len}")
+        .expect("invalid progress bar template")
+        .progress_chars("=> ")
+}
+
+fn bytes_style() -> ProgressStyle {
+    ProgressStyle::with_template("{prefix:>18} [{bar:40}] {bytes}/{total_bytes}")
+        .expect("invalid progress bar template")
+        .progress_chars("=> ")
+}
+
+async fn render(mut rx: UnboundedReceiver<ProgressEvent>) {
+    let bars = MultiProgress::new();
+    let mut phases: HashMap<Phase, ProgressBar> = HashMap::new();
+
+    let mut bar_of = |phase: Phase, total: Option<usize>| -> ProgressBar {
+        phases
+            .entry(phase)
+            .or_insert_with(|| {
+                let bar = bars.add(ProgressBar::new(total.unwrap_or(0) as u64));
+                bar.set_style(items_style());
+                bar.set_prefix(phase.name());
+                bar
+            })
+            .clone()
+    };
+
+    while let Some(event) = rx.recv().await {
+        match event {
+            ProgressEvent::PhaseStarted { phase, total } => {
+                bar_of(phase, total);
+            }
+            ProgressEvent::ItemFinished {
+                phase,
+                current,
+                total,
+            } => {
+                let bar = bar_of(phase, Some(total));
+                bar.set_style(items_style());
+                bar.set_length(total as u64);
+                bar.set_position(current as u64);
+            }
+            ProgressEvent::BytesDownloaded {
+                phase,
+                downloaded,
+                total,
+            } => {
+                let bar = bar_of(phase, None);
+                bar.set_style(bytes_style());
+                bar.set_length(total.unwrap_or(downloaded));
+                bar.set_position(downloaded);
+            }
+            ProgressEvent::PhaseFinished { phase } => {
+                bar_of(phase, None).finish();
+            }
+        }
+    }
+}